@@ -0,0 +1,23 @@
+use anyhow::Result;
+use clap::{ArgMatches, Command};
+use std::path::Path;
+
+mod set;
+mod view;
+
+pub const NAME: &str = "config";
+
+pub fn command() -> Command {
+    Command::new(NAME)
+        .about("Manage kbs settings")
+        .subcommands([view::command(), set::command()])
+        .arg_required_else_help(true)
+}
+
+pub fn execute(config_dir: &Path, matches: &ArgMatches) -> Result<()> {
+    match matches.subcommand() {
+        Some((view::NAME, sub_matches)) => view::execute(config_dir, sub_matches),
+        Some((set::NAME, sub_matches)) => set::execute(config_dir, sub_matches),
+        _ => Ok(()),
+    }
+}
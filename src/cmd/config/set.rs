@@ -0,0 +1,41 @@
+use crate::config::{settings, Settings};
+use anyhow::{anyhow, Result};
+use clap::{builder::PossibleValuesParser, Arg, ArgMatches, Command};
+use std::path::Path;
+
+pub const NAME: &str = "set";
+
+pub fn command() -> Command {
+    Command::new(NAME)
+        .about("Change a setting")
+        .arg_required_else_help(true)
+        .arg(
+            Arg::new("key")
+                .help("Setting to change")
+                .required(true)
+                .value_parser(PossibleValuesParser::new(settings::KEYS)),
+        )
+        .arg(
+            Arg::new("value")
+                .help("New value for the setting")
+                .required(true)
+                .value_parser(clap::value_parser!(String)),
+        )
+}
+
+pub fn execute(config_dir: &Path, matches: &ArgMatches) -> Result<()> {
+    let key = matches
+        .get_one::<String>("key")
+        .ok_or_else(|| anyhow!("failed to parse key argument"))?;
+    let value = matches
+        .get_one::<String>("value")
+        .ok_or_else(|| anyhow!("failed to parse value argument"))?;
+
+    let mut settings = Settings::load(config_dir)?;
+    settings.set(key, value)?;
+    settings.write(config_dir)?;
+
+    log::info!("set {key} to '{value}'");
+
+    Ok(())
+}
@@ -0,0 +1,21 @@
+use crate::config::{settings, Settings};
+use anyhow::Result;
+use clap::{ArgMatches, Command};
+use std::path::Path;
+
+pub const NAME: &str = "view";
+
+pub fn command() -> Command {
+    Command::new(NAME).about("Print the current settings")
+}
+
+pub fn execute(config_dir: &Path, _matches: &ArgMatches) -> Result<()> {
+    log::debug!(
+        "loading settings from {}",
+        settings::file_path(config_dir).display()
+    );
+
+    print!("{}", Settings::load(config_dir)?);
+
+    Ok(())
+}
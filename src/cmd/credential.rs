@@ -0,0 +1,62 @@
+use crate::kubeconfig::credentials;
+use anyhow::{anyhow, Result};
+use clap::{value_parser, Arg, ArgAction, ArgMatches, Command};
+use serde_json::json;
+use std::path::Path;
+
+pub const NAME: &str = "credential";
+
+const DEFAULT_API_VERSION: &str = "client.authentication.k8s.io/v1";
+
+pub fn command() -> Command {
+    Command::new(NAME)
+        .about("Print credentials from the credential store as ExecCredential. Called by kubectl for kubeconfigs imported with --exec-credentials")
+        .arg(
+            Arg::new("kubeconfig")
+                .help("Name of the kubeconfig")
+                .required(true)
+                .action(ArgAction::Set)
+                .value_parser(value_parser!(String)),
+        )
+        .arg(
+            Arg::new("user")
+                .help("Name of the user in the kubeconfig")
+                .long("user")
+                .short('u')
+                .required(true)
+                .action(ArgAction::Set)
+                .value_parser(value_parser!(String)),
+        )
+        .arg_required_else_help(true)
+}
+
+pub fn execute(config_dir: &Path, matches: &ArgMatches) -> Result<()> {
+    let name = matches
+        .get_one::<String>("kubeconfig")
+        .ok_or_else(|| anyhow!("failed to get kubeconfig argument"))?;
+
+    let user = matches
+        .get_one::<String>("user")
+        .ok_or_else(|| anyhow!("failed to get user argument"))?;
+
+    let credential = credentials::get(config_dir, name, user)?;
+
+    let credential = json!({
+        "apiVersion": api_version(),
+        "kind": "ExecCredential",
+        "status": credential,
+    });
+
+    println!("{}", serde_json::to_string_pretty(&credential)?);
+
+    Ok(())
+}
+
+// Respond with the API version kubectl asked for, if it passed one.
+fn api_version() -> String {
+    std::env::var("KUBERNETES_EXEC_INFO")
+        .ok()
+        .and_then(|info| serde_json::from_str::<serde_json::Value>(&info).ok())
+        .and_then(|info| info["apiVersion"].as_str().map(str::to_string))
+        .unwrap_or_else(|| DEFAULT_API_VERSION.to_string())
+}
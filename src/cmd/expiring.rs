@@ -0,0 +1,115 @@
+use super::output;
+use crate::metadata::{self, Metadata, Timestamp};
+use crate::{kubeconfig, Error};
+use anyhow::{anyhow, bail, Result};
+use clap::{Arg, ArgAction, ArgMatches, Command};
+use std::path::Path;
+use std::time::Duration;
+
+pub const NAME: &str = "expiring";
+
+const DAY: u64 = 24 * 60 * 60;
+
+pub fn command() -> Command {
+    Command::new(NAME)
+        .about("List certificates in kubeconfigs that expire soon. Exits with an error if there are any")
+        .arg(
+            Arg::new("within")
+                .help("List certificates expiring within this duration, e.g. '30d'")
+                .long("within")
+                .short('w')
+                .required(false)
+                .action(ArgAction::Set)
+                .default_value("30d")
+                .value_parser(humantime::parse_duration),
+        )
+        .arg(
+            Arg::new("selectors")
+                .help("Selector (label query) to filter on. Supports '=', '==', '!=', 'in', 'notin', '<key>' (exists) and '!<key>' (does not exist), e.g. 'env in (prod,staging),!legacy'")
+                .long("selector")
+                .short('l')
+                .required(false)
+                .action(ArgAction::Append)
+                .value_parser(metadata::selectors::parse),
+        )
+}
+
+pub fn execute(config_dir: &Path, matches: &ArgMatches) -> Result<()> {
+    let within = *matches
+        .get_one::<Duration>("within")
+        .ok_or_else(|| anyhow!("failed to parse within argument"))?;
+
+    let selectors = metadata::selectors::from_args(matches, "selectors")?;
+
+    let metadata_path = metadata::file_path(config_dir);
+    log::debug!("loading metadata from {}", metadata_path.display());
+    let metadata = match Metadata::from_file(&metadata_path) {
+        Ok(metadata) => metadata,
+        Err(Error::IO(err)) if err.kind() == std::io::ErrorKind::NotFound => Metadata::new(),
+        Err(err) => bail!(err),
+    };
+
+    let now = Timestamp::now();
+    let deadline = Timestamp(now.0 + within);
+    let mut rows = vec![];
+
+    for entry in kubeconfig::list(config_dir, &metadata, Some(selectors))? {
+        // kubeconfigs imported before expiry was recorded are read on demand.
+        let expiry = match metadata.get(&entry.name).and_then(|m| m.expiry) {
+            Some(expiry) => expiry,
+            None => match kubeconfig::get(config_dir, &entry.name) {
+                Ok((_, kubeconfig)) => kubeconfig::get_expiry(&kubeconfig),
+                Err(err) => {
+                    log::warn!("failed to read kubeconfig {}: {err}", entry.name);
+                    continue;
+                }
+            },
+        };
+
+        for (certificate, not_after) in [
+            ("client certificate", expiry.client_cert),
+            ("certificate authority", expiry.ca),
+        ] {
+            let Some(not_after) = not_after.filter(|not_after| *not_after <= deadline) else {
+                continue;
+            };
+
+            rows.push(vec![
+                entry.name.clone(),
+                certificate.to_string(),
+                not_after.to_string(),
+                match not_after.0.duration_since(now.0) {
+                    Ok(remaining) => format!("expires in {}", format_remaining(remaining)),
+                    Err(_) => "expired".to_string(),
+                },
+            ]);
+        }
+    }
+
+    let within = humantime::format_duration(within);
+
+    if rows.is_empty() {
+        log::info!("no certificates expiring within {within}");
+        return Ok(());
+    }
+
+    let count = rows.len();
+    output::print_table(&["NAME", "CERTIFICATE", "EXPIRY", "STATUS"], rows);
+
+    Err(anyhow!(
+        "{count} certificate(s) expired or expiring within {within}"
+    ))
+}
+
+// Format the remaining time until expiry with a sensible precision,
+// e.g. `12days` instead of `12days 3h 4m 5s`.
+fn format_remaining(remaining: Duration) -> humantime::FormattedDuration {
+    let seconds = remaining.as_secs();
+    let precision = match seconds {
+        s if s >= DAY => DAY,
+        s if s >= 60 * 60 => 60 * 60,
+        _ => 60,
+    };
+
+    humantime::format_duration(Duration::from_secs(seconds - seconds % precision))
+}
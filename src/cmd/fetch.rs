@@ -0,0 +1,171 @@
+use crate::config::{settings, Settings};
+use crate::kubeconfig::{self, template, template::NameTemplate, OnConflict};
+use crate::metadata::{self, labels, Metadata};
+use crate::{fetch, Error};
+use anyhow::{anyhow, bail, Result};
+use clap::{value_parser, Arg, ArgAction, ArgMatches, Command};
+use std::path::Path;
+
+pub const NAME: &str = "fetch";
+
+pub fn command() -> Command {
+    Command::new(NAME)
+        .visible_alias("f")
+        .about("Fetch a kubeconfig from a remote system and import it into data store")
+        .arg_required_else_help(true)
+        .arg(
+            Arg::new("source")
+                .help("Source to fetch kubeconfig from. Supports http(s):// URLs, ssh:// (or sftp://) URLs and 'cmd:<command>' to read the stdout of a local command")
+                .required(true)
+                .value_parser(fetch::parse),
+        )
+        .arg(
+            Arg::new("name")
+                .help("Override context name")
+                .long("name")
+                .short('n')
+                .required(false)
+                .num_args(1)
+                .value_parser(clap::value_parser!(String)),
+        )
+        .arg(
+            Arg::new("name-template")
+                .help("Template to name the imported kubeconfig by, see 'kbs import --help' for supported placeholders")
+                .long("name-template")
+                .required(false)
+                .env("KBS_NAME_TEMPLATE")
+                .num_args(1)
+                .value_parser(template::parse)
+                .conflicts_with("name"),
+        )
+        .arg(
+            Arg::new("labels")
+                .help("List of comma-separated key=value labels to add to the kubeconfig metadata")
+                .long("labels")
+                .short('l')
+                .required(false)
+                .env("KBS_LABELS")
+                .value_delimiter(',')
+                .value_parser(labels::parse),
+        )
+        .arg(
+            Arg::new("short")
+                .help("Instead of using the FQDN of the server, just use the first part/subdomain")
+                .long("short")
+                .short('s')
+                .required(false)
+                .action(ArgAction::SetTrue)
+                .value_parser(clap::value_parser!(bool))
+                .conflicts_with("name"),
+        )
+        .arg(
+            Arg::new("proxy-url")
+                .help("Configure a proxy url for the imported kubeconfig")
+                .long("proxy-url")
+                .short('p')
+                .required(false)
+                .env("KBS_PROXY_URL")
+                .num_args(1)
+                .value_parser(clap::value_parser!(String)),
+        )
+        .arg(
+            Arg::new("exec-credentials")
+                .help("Move user credentials to the credential store of kbs and let the kubeconfig call 'kbs credential' as exec plugin to retrieve them")
+                .long("exec-credentials")
+                .required(false)
+                .env("KBS_EXEC_CREDENTIALS")
+                .action(ArgAction::SetTrue)
+                .value_parser(clap::value_parser!(bool)),
+        )
+        .arg(
+            Arg::new("on-conflict")
+                .help("What to do if a kubeconfig with the same name exists already. 'rename' appends a numeric suffix, 'overwrite' keeps existing labels and 'prompt' asks interactively")
+                .long("on-conflict")
+                .required(false)
+                .action(ArgAction::Set)
+                .default_value(OnConflict::Error)
+                .value_parser(value_parser!(OnConflict)),
+        )
+}
+
+pub fn execute(config_dir: &Path, matches: &ArgMatches) -> Result<()> {
+    let source = matches
+        .get_one::<fetch::Source>("source")
+        .ok_or_else(|| anyhow!("failed to parse source argument"))?;
+
+    let settings = Settings::load(config_dir)?;
+
+    // default labels from settings are overridden by labels passed via flag.
+    let labels = [
+        settings.import.labels()?,
+        labels::from_args(matches, "labels")?,
+    ]
+    .concat();
+
+    let metadata_path = metadata::file_path(config_dir);
+    log::debug!("loading metadata from {}", metadata_path.display());
+    let metadata = match Metadata::from_file(&metadata_path) {
+        Ok(metadata) => metadata,
+        Err(Error::IO(err)) if err.kind() == std::io::ErrorKind::NotFound => {
+            log::debug!("failed to find metadata file, creating empty metadata store");
+            Metadata::new()
+        }
+        Err(err) => bail!(err),
+    };
+
+    let on_conflict = *matches
+        .get_one::<OnConflict>("on-conflict")
+        .ok_or_else(|| anyhow!("failed to parse on-conflict argument"))?;
+
+    let data = source.fetch()?;
+    let kubeconfig = kubeconfig::from_bytes(&data)?;
+
+    let name_template =
+        settings::resolve(matches, "name-template", settings.import.name_template()?);
+    let proxy = settings::resolve(matches, "proxy-url", settings.import.proxy_url.clone());
+    let exec_credentials = settings::resolve(
+        matches,
+        "exec-credentials",
+        settings.import.exec_credentials,
+    )
+    .unwrap_or_default();
+
+    let default_template = NameTemplate::default();
+    let naming = kubeconfig::Naming {
+        name: matches.get_one::<String>("name"),
+        template: name_template.as_ref().unwrap_or(&default_template),
+        path: None,
+        use_short: matches.get_flag("short"),
+    };
+
+    let name = kubeconfig::import(
+        config_dir,
+        &kubeconfig,
+        &naming,
+        proxy.as_ref(),
+        exec_credentials,
+        on_conflict,
+    )?;
+
+    let Some(name) = name else {
+        return Ok(());
+    };
+
+    log::info!("imported kubeconfig from {source} as {name}");
+
+    metadata
+        .set_imported(
+            name,
+            &labels,
+            metadata::SourceRecord::new(source.clone(), &data),
+            kubeconfig::get_expiry(&kubeconfig),
+        )?
+        .write(&metadata_path)?;
+
+    log::debug!(
+        "wrote metadata database update to {}",
+        metadata_path.display()
+    );
+
+    Ok(())
+}
@@ -0,0 +1,53 @@
+use super::output;
+use crate::config::Output;
+use crate::metadata::{self, Metadata};
+use crate::{kubeconfig, Error};
+use anyhow::{anyhow, bail, Result};
+use clap::{value_parser, Arg, ArgAction, ArgMatches, Command};
+use std::path::Path;
+
+pub const NAME: &str = "get";
+
+pub fn command() -> Command {
+    Command::new(NAME)
+        .about("Print details of a kubeconfig in the data store")
+        .arg(
+            Arg::new("kubeconfig")
+                .help("Name of the kubeconfig")
+                .required(true)
+                .action(ArgAction::Set)
+                .value_parser(value_parser!(String)),
+        )
+        .arg(
+            Arg::new("output")
+                .help("Output format. One of name, table, wide, json, yaml, custom-columns=<header>:<path>,..., jsonpath=<template> (e.g. 'jsonpath={.server}') or template=<template> (alias go-template=<template>, e.g. 'template={{ labels.env }}')")
+                .long("output")
+                .short('o')
+                .required(false)
+                .action(ArgAction::Set)
+                .default_value("yaml")
+                .value_parser(value_parser!(Output)),
+        )
+        .arg_required_else_help(true)
+}
+
+pub fn execute(config_dir: &Path, matches: &ArgMatches) -> Result<()> {
+    let name = matches
+        .get_one::<String>("kubeconfig")
+        .ok_or_else(|| anyhow!("failed to get kubeconfig argument"))?;
+
+    let output = matches
+        .get_one::<Output>("output")
+        .ok_or_else(|| anyhow!("cannot read output"))?;
+
+    let metadata_path = metadata::file_path(config_dir);
+    log::debug!("loading metadata from {}", metadata_path.display());
+    let metadata = match Metadata::from_file(&metadata_path) {
+        Ok(metadata) => metadata,
+        Err(Error::IO(err)) if err.kind() == std::io::ErrorKind::NotFound => Metadata::new(),
+        Err(err) => bail!(err),
+    };
+
+    let entry = kubeconfig::get_entry(config_dir, &metadata, name)?;
+    output::print_entry(output, &entry)
+}
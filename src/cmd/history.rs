@@ -0,0 +1,40 @@
+use super::output;
+use crate::store::git;
+use anyhow::Result;
+use clap::{value_parser, Arg, ArgAction, ArgMatches, Command};
+use std::path::Path;
+
+pub const NAME: &str = "history";
+
+pub fn command() -> Command {
+    Command::new(NAME)
+        .about("Show changes to the data store, if it is tracked in git")
+        .arg(
+            Arg::new("kubeconfig")
+                .help("Only show changes to this kubeconfig")
+                .required(false)
+                .action(ArgAction::Set)
+                .value_parser(value_parser!(String)),
+        )
+}
+
+pub fn execute(config_dir: &Path, matches: &ArgMatches) -> Result<()> {
+    let name = matches.get_one::<String>("kubeconfig");
+
+    let revisions = git::history(config_dir, name.map(String::as_str))?;
+
+    if revisions.is_empty() {
+        log::info!("no changes recorded");
+        return Ok(());
+    }
+
+    output::print_table(
+        &["REVISION", "DATE", "CHANGE"],
+        revisions
+            .into_iter()
+            .map(|revision| vec![revision.id, revision.time.to_string(), revision.message])
+            .collect(),
+    );
+
+    Ok(())
+}
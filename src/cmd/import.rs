@@ -1,7 +1,13 @@
+use crate::config::{settings, Settings};
+use crate::fetch::{self, secret};
+use crate::kubeconfig::{self, template, template::NameTemplate, OnConflict};
 use crate::metadata::{self, labels, Metadata};
-use crate::{kubeconfig, Error};
+use crate::Error;
 use anyhow::{anyhow, bail, Result};
-use clap::{value_parser, Arg, ArgAction, ArgMatches, Command};
+use clap::{value_parser, Arg, ArgAction, ArgGroup, ArgMatches, Command};
+use futures::executor;
+use glob::Pattern;
+use kube::config::Kubeconfig;
 use std::fs::{self};
 use std::path::{Path, PathBuf};
 
@@ -32,12 +38,13 @@ pub fn command() -> Command {
                 .long("labels")
                 .short('l')
                 .required(false)
+                .env("KBS_LABELS")
                 .value_delimiter(',')
                 .value_parser(labels::parse),
         )
         .arg(
             Arg::new("delete")
-                .help("Delete original kubeconfig file after import. This flag has no effect when importing a directory")
+                .help("Delete original kubeconfig file after import. When importing a directory, each successfully imported file is deleted")
                 .long("delete")
                 .short('d')
                 .required(false)
@@ -54,23 +61,129 @@ pub fn command() -> Command {
                 .value_parser(clap::value_parser!(bool))
                 .conflicts_with("name"),
         )
+        .arg(
+            Arg::new("name-template")
+                .help("Template to name imported kubeconfigs by, e.g. '{dir}-{host[1]}'. Supports the placeholders {dir}, {file}, {host}, {port}, {context}, {cluster} and {user}, selecting a dot-separated segment with [n] and extracting a part with a regular expression via {host:<regex>}")
+                .long("name-template")
+                .required(false)
+                .env("KBS_NAME_TEMPLATE")
+                .num_args(1)
+                .value_parser(template::parse)
+                .conflicts_with("name"),
+        )
+        .arg(
+            Arg::new("recursive")
+                .help("Import kubeconfigs from subdirectories as well when importing a directory")
+                .long("recursive")
+                .short('r')
+                .required(false)
+                .action(ArgAction::SetTrue)
+                .value_parser(clap::value_parser!(bool)),
+        )
+        .arg(
+            Arg::new("include")
+                .help("Only import files matching this glob pattern (relative to the imported directory). Can be passed multiple times")
+                .long("include")
+                .required(false)
+                .action(ArgAction::Append)
+                .value_parser(parse_pattern),
+        )
+        .arg(
+            Arg::new("exclude")
+                .help("Do not import files matching this glob pattern (relative to the imported directory). Can be passed multiple times")
+                .long("exclude")
+                .required(false)
+                .action(ArgAction::Append)
+                .value_parser(parse_pattern),
+        )
+        .arg(
+            Arg::new("split")
+                .help("Import each context of a kubeconfig with multiple contexts as separate kubeconfig, only containing the cluster and user referenced by the context")
+                .long("split")
+                .required(false)
+                .action(ArgAction::SetTrue)
+                .value_parser(clap::value_parser!(bool))
+                .conflicts_with("name"),
+        )
         .arg(
             Arg::new("proxy-url")
                 .help("Configure a proxy url for the imported kubeconfig")
                 .long("proxy-url")
                 .short('p')
                 .required(false)
+                .env("KBS_PROXY_URL")
                 .num_args(1)
                 .value_parser(clap::value_parser!(String)),
         )
+        .arg(
+            Arg::new("exec-credentials")
+                .help("Move user credentials to the credential store of kbs and let the kubeconfig call 'kbs credential' as exec plugin to retrieve them")
+                .long("exec-credentials")
+                .required(false)
+                .env("KBS_EXEC_CREDENTIALS")
+                .action(ArgAction::SetTrue)
+                .value_parser(clap::value_parser!(bool)),
+        )
+        .arg(
+            Arg::new("on-conflict")
+                .help("What to do if a kubeconfig with the same name exists already. 'rename' appends a numeric suffix, 'overwrite' keeps existing labels and 'prompt' asks interactively")
+                .long("on-conflict")
+                .required(false)
+                .action(ArgAction::Set)
+                .default_value(OnConflict::Error)
+                .value_parser(value_parser!(OnConflict)),
+        )
+        .arg(
+            Arg::new("from-secret")
+                .help("Import kubeconfig from a Secret (<namespace>/<name>) in the cluster of the current KUBECONFIG")
+                .long("from-secret")
+                .required(false)
+                .num_args(1)
+                .value_parser(secret::parse),
+        )
+        .arg(
+            Arg::new("secret-selector")
+                .help("Import kubeconfigs from all Secrets matching this label selector in the cluster of the current KUBECONFIG")
+                .long("secret-selector")
+                .required(false)
+                .num_args(1)
+                .value_parser(clap::value_parser!(String))
+                .conflicts_with("name"),
+        )
+        .arg(
+            Arg::new("secret-namespace")
+                .help("Namespace to look for Secrets in when using --secret-selector. Defaults to all namespaces")
+                .long("secret-namespace")
+                .required(false)
+                .num_args(1)
+                .requires("secret-selector")
+                .value_parser(clap::value_parser!(String)),
+        )
+        .arg(
+            Arg::new("secret-key")
+                .help("Key in the Secret data that holds the kubeconfig")
+                .long("secret-key")
+                .required(false)
+                .num_args(1)
+                .default_value(secret::DEFAULT_KEY)
+                .value_parser(clap::value_parser!(String)),
+        )
+        .group(
+            ArgGroup::new("input")
+                .args(["kubeconfig", "from-secret", "secret-selector"])
+                .required(true),
+        )
 }
 
 pub fn execute(config_dir: &Path, matches: &ArgMatches) -> Result<()> {
-    let kubeconfig_path = matches
-        .get_one::<PathBuf>("kubeconfig")
-        .ok_or_else(|| anyhow!("failed to parse kubeconfig argument"))?;
+    let settings = Settings::load(config_dir)?;
 
-    let labels = labels::from_args(matches, "labels")?;
+    // default labels from settings are overridden by labels passed via flag.
+    let labels = [
+        settings.import.labels()?,
+        labels::from_args(matches, "labels")?,
+    ]
+    .concat();
 
     let metadata_path = metadata::file_path(config_dir);
     log::debug!("loading metadata from {}", metadata_path.display());
@@ -83,64 +196,428 @@ pub fn execute(config_dir: &Path, matches: &ArgMatches) -> Result<()> {
         Err(err) => bail!(err),
     };
 
+    let on_conflict = *matches
+        .get_one::<OnConflict>("on-conflict")
+        .ok_or_else(|| anyhow!("failed to parse on-conflict argument"))?;
+
+    let name_template =
+        settings::resolve(matches, "name-template", settings.import.name_template()?);
+    let proxy = settings::resolve(matches, "proxy-url", settings.import.proxy_url.clone());
+    let exec_credentials = settings::resolve(
+        matches,
+        "exec-credentials",
+        settings.import.exec_credentials,
+    )
+    .unwrap_or_default();
+
+    let options = Options {
+        name_template: name_template.as_ref(),
+        use_short: matches.get_flag("short"),
+        split: matches.get_flag("split"),
+        proxy: proxy.as_ref(),
+        exec_credentials,
+        on_conflict,
+    };
+
+    // contexts that failed to import with --split.
+    let mut failed = 0;
+
+    let secret_key = matches
+        .get_one::<String>("secret-key")
+        .ok_or_else(|| anyhow!("failed to parse secret-key argument"))?;
+
+    if let Some(reference) = matches.get_one::<secret::Reference>("from-secret") {
+        let source = fetch::Source::Secret(secret::Reference {
+            key: secret_key.to_string(),
+            ..reference.clone()
+        });
+
+        let data = source.fetch()?;
+        let imported = import_data(
+            config_dir,
+            &source,
+            &data,
+            &kubeconfig::from_bytes(&data)?,
+            matches.get_one::<String>("name"),
+            &options,
+        )?;
+
+        failed += imported.failed.len();
+        for (name, record, expiry) in imported.records {
+            metadata = metadata.set_imported(name, &labels, record, expiry)?;
+        }
+    } else if let Some(selector) = matches.get_one::<String>("secret-selector") {
+        let namespace = matches.get_one::<String>("secret-namespace");
+
+        let secrets = executor::block_on(async {
+            let client = kube::Client::try_default().await?;
+            secret::list(
+                client,
+                namespace.map(|ns| ns.as_str()),
+                selector,
+                secret_key,
+            )
+            .await
+        })?;
+
+        if secrets.is_empty() {
+            log::warn!("no Secrets with key '{secret_key}' found for selector '{selector}'");
+        }
+
+        for (reference, data) in secrets {
+            let source = fetch::Source::Secret(reference);
+
+            let imported = match kubeconfig::from_bytes(&data).and_then(|kubeconfig| {
+                import_data(config_dir, &source, &data, &kubeconfig, None, &options)
+            }) {
+                Ok(imported) => imported,
+                Err(err) => {
+                    log::warn!("failed to import {source}: {err}");
+                    continue;
+                }
+            };
+
+            failed += imported.failed.len();
+            for (name, record, expiry) in imported.records {
+                metadata = metadata.set_imported(name, &labels, record, expiry)?;
+            }
+        }
+    } else {
+        let kubeconfig_path = matches
+            .get_one::<PathBuf>("kubeconfig")
+            .ok_or_else(|| anyhow!("failed to parse kubeconfig argument"))?;
+
+        (metadata, failed) = import_path(
+            config_dir,
+            kubeconfig_path,
+            matches,
+            &options,
+            &labels,
+            metadata,
+        )?;
+    }
+
+    metadata.write(&metadata_path)?;
+
+    log::debug!(
+        "wrote metadata database update to {}",
+        metadata_path.display()
+    );
+
+    // kubeconfigs imported from contexts that did not fail are recorded
+    // already, but the import as a whole has not succeeded.
+    if failed > 0 {
+        bail!("failed to import {failed} context(s)");
+    }
+
+    Ok(())
+}
+
+fn import_path(
+    config_dir: &Path,
+    kubeconfig_path: &Path,
+    matches: &ArgMatches,
+    options: &Options,
+    labels: &[labels::Label],
+    mut metadata: Metadata,
+) -> Result<(Metadata, usize)> {
+    // contexts that failed to import with --split.
+    let mut failed_contexts = 0;
+
     if kubeconfig_path.is_dir() {
-        let files = fs::read_dir(kubeconfig_path)?;
-        for file in files {
-            let entry = file?;
-            let path = entry.path();
-            let name = kubeconfig::import(
-                config_dir,
-                &path,
-                None,
-                matches.get_flag("short"),
-                matches.get_one::<String>("proxy-url"),
-            );
-
-            if let Err(err) = name {
-                log::warn!(
-                    "failed to import {}: {}",
-                    path.to_str().unwrap_or("<couldn't unwrap path>"),
-                    err
+        let include = patterns(matches, "include");
+        let exclude = patterns(matches, "exclude");
+
+        let (mut imported_files, mut skipped, mut failed) = (0, 0, 0);
+
+        for path in find_files(kubeconfig_path, matches.get_flag("recursive"))? {
+            let relative = path.strip_prefix(kubeconfig_path).unwrap_or(&path);
+            if !include.is_empty() && !include.iter().any(|p| p.matches_path(relative))
+                || exclude.iter().any(|p| p.matches_path(relative))
+            {
+                log::debug!("ignoring {} as it is filtered out", path.display());
+                continue;
+            }
+
+            log::debug!("trying to import {}", path.display());
+            let source = fetch::from_path(&path);
+            let data = match source.fetch() {
+                Ok(data) => data,
+                Err(err) => {
+                    log::warn!("failed to read {}: {}", path.display(), err);
+                    failed += 1;
+                    continue;
+                }
+            };
+
+            let kubeconfig = match kubeconfig::from_bytes(&data) {
+                Ok(kubeconfig) => kubeconfig,
+                Err(err) => {
+                    log::debug!(
+                        "skipping {} as it is not a kubeconfig: {}",
+                        path.display(),
+                        err
+                    );
+                    skipped += 1;
+                    continue;
+                }
+            };
+
+            // files like an empty `kind: Config` parse as kubeconfig, but
+            // hold nothing that could be imported.
+            if kubeconfig::get_servers(&kubeconfig).is_empty() || kubeconfig.contexts.is_empty() {
+                log::debug!(
+                    "skipping {} as it has no servers or contexts",
+                    path.display()
                 );
+                skipped += 1;
+                continue;
+            }
+
+            let imported = match import_data(config_dir, &source, &data, &kubeconfig, None, options)
+            {
+                Ok(imported) => imported,
+                Err(err) => {
+                    log::warn!(
+                        "failed to import {}: {}",
+                        path.to_str().unwrap_or("<couldn't unwrap path>"),
+                        err
+                    );
+                    failed += 1;
+                    continue;
+                }
+            };
+
+            let stored = !imported.records.is_empty();
+            for (name, record, expiry) in imported.records {
+                metadata = metadata.set_imported(name, labels, record, expiry)?;
+            }
+
+            // files with contexts that failed to import are kept.
+            if !imported.failed.is_empty() {
+                failed_contexts += imported.failed.len();
+                failed += 1;
+                continue;
+            }
+
+            if !stored {
+                skipped += 1;
                 continue;
             }
 
-            metadata = metadata.set(
-                name.unwrap(),
-                metadata::ConfigMetadata {
-                    labels: Some(labels::to_map(&labels)),
-                },
-            );
+            imported_files += 1;
+
+            if matches.get_flag("delete") {
+                fs::remove_file(&path)?;
+                log::debug!("deleted {}", path.display());
+            }
         }
+
+        log::info!(
+            "imported {imported_files} file(s) from {}, skipped {skipped}, failed {failed}",
+            kubeconfig_path.display()
+        );
     } else {
         // run import logic.
-        let name = kubeconfig::import(
+        log::debug!("trying to import {}", kubeconfig_path.display());
+        let source = fetch::from_path(kubeconfig_path);
+        let data = source.fetch()?;
+        let imported = import_data(
             config_dir,
-            kubeconfig_path,
+            &source,
+            &data,
+            &kubeconfig::from_bytes(&data)?,
             matches.get_one::<String>("name"),
-            matches.get_flag("short"),
-            matches.get_one::<String>("proxy-url"),
+            options,
         )?;
 
-        metadata = metadata.set(
-            name,
-            metadata::ConfigMetadata {
-                labels: Some(labels::to_map(&labels)),
-            },
-        );
+        let stored = !imported.records.is_empty();
+        for (name, record, expiry) in imported.records {
+            metadata = metadata.set_imported(name, labels, record, expiry)?;
+        }
 
-        if matches.get_flag("delete") {
+        // nothing has been stored if the import was skipped and contexts
+        // that failed to import would be lost, so keep the file.
+        failed_contexts += imported.failed.len();
+        if matches.get_flag("delete") && stored && imported.failed.is_empty() {
             fs::remove_file(kubeconfig_path)?;
             log::debug!("deleted {}", kubeconfig_path.display());
         }
     }
 
-    metadata.write(&metadata_path)?;
+    Ok((metadata, failed_contexts))
+}
 
-    log::debug!(
-        "wrote metadata database update to {}",
-        metadata_path.display()
+/// List all files in a directory (and its subdirectories if recursive is set),
+/// sorted by path.
+fn find_files(dir: &Path, recursive: bool) -> Result<Vec<PathBuf>> {
+    let mut files = vec![];
+
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        let path = entry.path();
+
+        // symlinks to directories are not followed to avoid loops.
+        if entry.file_type()?.is_dir() {
+            if recursive {
+                files.append(&mut find_files(&path, recursive)?);
+            } else {
+                log::debug!("ignoring directory {}", path.display());
+            }
+            continue;
+        }
+
+        files.push(path);
+    }
+
+    files.sort();
+    Ok(files)
+}
+
+fn parse_pattern(s: &str) -> Result<Pattern, glob::PatternError> {
+    Pattern::new(s)
+}
+
+fn patterns(matches: &ArgMatches, id: &str) -> Vec<Pattern> {
+    matches
+        .get_many::<Pattern>(id)
+        .map(|patterns| patterns.cloned().collect())
+        .unwrap_or_default()
+}
+
+/// Options shared by all kubeconfigs imported in a single run.
+struct Options<'a> {
+    name_template: Option<&'a NameTemplate>,
+    use_short: bool,
+    split: bool,
+    proxy: Option<&'a String>,
+    exec_credentials: bool,
+    on_conflict: OnConflict,
+}
+
+/// Kubeconfigs imported from a single source.
+#[derive(Default)]
+struct Imported {
+    /// Names of the imported kubeconfigs along with the metadata to store for them.
+    records: Vec<(String, metadata::SourceRecord, metadata::Expiry)>,
+    /// Contexts that failed to import with `--split`.
+    failed: Vec<String>,
+}
+
+/// Import the kubeconfig read from source into the data store. With `--split`,
+/// each context is imported as separate kubeconfig and contexts that fail to
+/// import are reported instead of failing the whole import.
+fn import_data(
+    config_dir: &Path,
+    source: &fetch::Source,
+    data: &[u8],
+    kubeconfig: &Kubeconfig,
+    name: Option<&String>,
+    options: &Options,
+) -> Result<Imported, Error> {
+    let path = match source {
+        fetch::Source::File(path) => Some(path.as_path()),
+        _ => None,
+    };
+
+    let default_template = NameTemplate::default();
+    let template = options.name_template.unwrap_or(&default_template);
+
+    if !options.split {
+        let naming = kubeconfig::Naming {
+            name,
+            template,
+            path,
+            use_short: options.use_short,
+        };
+
+        let name = kubeconfig::import(
+            config_dir,
+            kubeconfig,
+            &naming,
+            options.proxy,
+            options.exec_credentials,
+            options.on_conflict,
+        )?;
+
+        return Ok(Imported {
+            records: name
+                .map(|name| {
+                    log::info!("imported kubeconfig from {source} as {name}");
+                    (
+                        name,
+                        metadata::SourceRecord::new(source.clone(), data),
+                        kubeconfig::get_expiry(kubeconfig),
+                    )
+                })
+                .into_iter()
+                .collect(),
+            failed: vec![],
+        });
+    }
+
+    let split = kubeconfig::split(kubeconfig, options.name_template, path, options.use_short)?;
+    let mut imported = Imported::default();
+
+    for (context, err) in split.failed {
+        log::warn!("failed to import context '{context}' from {source}: {err}");
+        imported.failed.push(context);
+    }
+
+    for (name, part) in split.parts {
+        let context = part.current_context.clone().unwrap_or_default();
+
+        // the name has been determined while splitting, taking the name
+        // template into account.
+        let naming = kubeconfig::Naming {
+            name: Some(&name),
+            template,
+            path,
+            use_short: options.use_short,
+        };
+
+        match kubeconfig::import(
+            config_dir,
+            &part,
+            &naming,
+            options.proxy,
+            options.exec_credentials,
+            options.on_conflict,
+        ) {
+            Ok(Some(name)) => {
+                log::info!("imported context '{context}' from {source} as {name}");
+                imported.records.push((
+                    name,
+                    metadata::SourceRecord::new(source.clone(), data).split_from(context),
+                    kubeconfig::get_expiry(&part),
+                ));
+            }
+            Ok(None) => {}
+            Err(err) => {
+                log::warn!("failed to import context '{context}' from {source}: {err}");
+                imported.failed.push(context);
+            }
+        }
+    }
+
+    let names: Vec<&str> = imported
+        .records
+        .iter()
+        .map(|(name, _, _)| name.as_str())
+        .collect();
+    let mut summary = format!(
+        "created {} kubeconfig(s) from {source}: {}",
+        names.len(),
+        names.join(", ")
     );
+    if !imported.failed.is_empty() {
+        summary.push_str(&format!(
+            ", failed to import {} context(s): {}",
+            imported.failed.len(),
+            imported.failed.join(", ")
+        ));
+    }
+    log::info!("{summary}");
 
-    Ok(())
+    Ok(imported)
 }
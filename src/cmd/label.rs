@@ -25,10 +25,10 @@ pub fn command() -> Command {
         )
         .arg(
             Arg::new("selectors")
-                .help("Selector (label query) to filter on. Supports key=value comma-separated values")
+                .help("Selector (label query) to filter on. Supports '=', '==', '!=', 'in', 'notin', '<key>' (exists) and '!<key>' (does not exist), e.g. 'env in (prod,staging),!legacy'")
                 .long("selector")
                 .short('l')
-                .value_delimiter(',')
+                .action(ArgAction::Append)
                 .value_parser(metadata::selectors::parse)
                 .conflicts_with("kubeconfig"),
         )
@@ -62,10 +62,8 @@ pub fn execute(config_dir: &Path, matches: &ArgMatches) -> Result<()> {
         let selectors = metadata::selectors::from_args(matches, "selectors")?;
 
         metadata.kubeconfigs.iter().for_each(|k| {
-            if let Some(labels) = &k.1.labels {
-                if metadata::selectors::matches(&selectors, labels) {
-                    to_label.push(k.0.to_string());
-                }
+            if metadata::selectors::matches(&selectors, &k.1.labels) {
+                to_label.push(k.0.to_string());
             }
         })
     } else if matches.contains_id("kubeconfig") && !matches.contains_id("selectors") {
@@ -86,11 +84,8 @@ pub fn execute(config_dir: &Path, matches: &ArgMatches) -> Result<()> {
         if let Some(config_metadata) = metadata.get(f) {
             let mut config_metadata = config_metadata.clone();
 
-            config_metadata.labels = Some(labels::merge(
-                &config_metadata,
-                &labels,
-                matches.get_flag("overwrite"),
-            )?);
+            config_metadata.labels =
+                labels::merge(&config_metadata, &labels, matches.get_flag("overwrite"))?;
 
             metadata = metadata.set(f.to_string(), config_metadata);
         } else {
@@ -98,7 +93,10 @@ pub fn execute(config_dir: &Path, matches: &ArgMatches) -> Result<()> {
             metadata = metadata.set(
                 f.to_string(),
                 ConfigMetadata {
-                    labels: Some(labels::to_map(&labels)),
+                    labels: labels::to_map(&labels),
+                    source: None,
+                    last_used: None,
+                    expiry: None,
                 },
             );
         }
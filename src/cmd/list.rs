@@ -1,9 +1,9 @@
-use crate::config::Output;
-use crate::kubeconfig;
+use super::output;
+use crate::config::{settings, Output, Settings};
+use crate::kubeconfig::{self, filter, filter::FieldFilter, ListEntry};
 use crate::metadata::{self, Metadata};
 use anyhow::{anyhow, Result};
 use clap::{value_parser, Arg, ArgAction, ArgMatches, Command};
-use std::collections::btree_map::BTreeMap;
 use std::path::Path;
 
 pub const NAME: &str = "list";
@@ -14,13 +14,28 @@ pub fn command() -> Command {
         .about("List available kubeconfigs")
         .arg(
             Arg::new("selectors")
-                .help("Selector (label query) to filter on. Supports key=value comma-separated values")
+                .help("Selector (label query) to filter on. Supports '=', '==', '!=', 'in', 'notin', '<key>' (exists) and '!<key>' (does not exist), e.g. 'env in (prod,staging),!legacy'")
                 .long("selector")
                 .short('l')
                 .required(false)
-                .value_delimiter(',')
+                .action(ArgAction::Append)
                 .value_parser(metadata::selectors::parse),
         )
+        .arg(
+            Arg::new("fields")
+                .help("Filter on a field of the kubeconfig (server, user, auth, context or cluster). Supports '=', '==', '!=', '=~' (regex) and '!~', e.g. 'server=~\\.eks\\.amazonaws\\.com$' or 'auth=exec'")
+                .long("field")
+                .required(false)
+                .action(ArgAction::Append)
+                .value_parser(kubeconfig::filter::parse),
+        )
+        .arg(
+            Arg::new("search")
+                .help("Only list kubeconfigs whose name, labels or server URLs contain this text (case-insensitive)")
+                .long("search")
+                .required(false)
+                .action(ArgAction::Set),
+        )
         .arg(
             Arg::new("unset")
                 .help("Show pseudo-element '[unset]'")
@@ -32,10 +47,12 @@ pub fn command() -> Command {
         )
         .arg(
             Arg::new("output")
+                .help("Output format. One of name, table, wide, json, yaml, custom-columns=<header>:<path>,... (e.g. 'custom-columns=NAME:.name,ENV:.labels.env'), jsonpath=<template> (e.g. 'jsonpath={.items[*].server}') or template=<template> (alias go-template=<template>, e.g. 'template={% for item in items %}{{ item.name }} {% endfor %}')")
                 .long("output")
                 .short('o')
                 .required(false)
                 .action(ArgAction::Set)
+                .env("KBS_OUTPUT")
                 .default_value("name")
                 .value_parser(value_parser!(Output)),
         )
@@ -47,8 +64,8 @@ pub fn command() -> Command {
 pub fn execute(config_dir: &Path, matches: &ArgMatches) -> Result<()> {
     log::debug!("looking for kubeconfigs in {}", config_dir.display());
 
-    let output = matches
-        .get_one::<Output>("output")
+    let settings = Settings::load(config_dir)?;
+    let output = settings::resolve(matches, "output", settings.list.output()?)
         .ok_or_else(|| anyhow!("cannot read output"))?;
 
     let selectors = metadata::selectors::from_args(matches, "selectors")?;
@@ -59,41 +76,54 @@ pub fn execute(config_dir: &Path, matches: &ArgMatches) -> Result<()> {
 
     let kubeconfigs = kubeconfig::list(config_dir, &metadata, Some(selectors))?;
 
-    // print table header
-    if *output == Output::Table {
-        println!("{0: <25}\t{1: <25}", "NAME", "LABELS");
-    }
+    let fields: Vec<&FieldFilter> = matches
+        .get_many::<FieldFilter>("fields")
+        .unwrap_or_default()
+        .collect();
+    let search = matches.get_one::<String>("search");
+    let details = !matches!(output, Output::Name | Output::Table);
 
-    // loop over all kubeconfigs we found
-    for entry in kubeconfigs {
-        println!(
-            "{}",
-            match *output {
-                Output::Name => entry.name,
-                Output::Table => format!(
-                    "{0: <25}\t{1: <25}",
-                    entry.name,
-                    format_labels(&entry.labels)
-                ),
-            }
-        );
-    }
+    // field filters, search and detailed output need the kubeconfig content,
+    // so only read kubeconfigs from disk if any of them was requested.
+    let kubeconfigs: Vec<ListEntry> = if !fields.is_empty() || search.is_some() || details {
+        kubeconfigs
+            .into_iter()
+            .filter_map(|entry| {
+                let kubeconfig = match kubeconfig::get(config_dir, &entry.name) {
+                    Ok((_, kubeconfig)) => kubeconfig,
+                    Err(err) => {
+                        log::warn!("failed to read kubeconfig {}: {err}", entry.name);
+                        return None;
+                    }
+                };
 
-    if matches.get_flag("unset") {
-        println!("[unset]");
-    }
+                let matched = fields.iter().all(|field| field.matches(&kubeconfig))
+                    && search.is_none_or(|text| {
+                        filter::search(text, &entry.name, entry.labels.as_ref(), &kubeconfig)
+                    });
 
-    Ok(())
-}
+                match (matched, details) {
+                    (false, _) => None,
+                    (true, true) => Some(entry.with_details(&kubeconfig)),
+                    (true, false) => Some(entry),
+                }
+            })
+            .collect()
+    } else {
+        kubeconfigs
+    };
 
-fn format_labels(map: &Option<BTreeMap<String, String>>) -> String {
-    if let Some(labels) = map {
-        return labels
-            .iter()
-            .map(|(key, value)| -> String { format!("{key}={value}") })
-            .collect::<Vec<String>>()
-            .join(",");
+    output::print_list(&output, &kubeconfigs)?;
+
+    // the pseudo-element would break structured output.
+    if matches.get_flag("unset")
+        && matches!(
+            output,
+            Output::Name | Output::Table | Output::Wide | Output::CustomColumns(_)
+        )
+    {
+        println!("[unset]");
     }
 
-    "".to_string()
+    Ok(())
 }
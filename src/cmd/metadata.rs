@@ -0,0 +1,22 @@
+use anyhow::Result;
+use clap::{ArgMatches, Command};
+use std::path::Path;
+
+mod migrate;
+
+pub const NAME: &str = "metadata";
+
+pub fn command() -> Command {
+    Command::new(NAME)
+        .visible_alias("md")
+        .about("Manage the metadata store")
+        .subcommands([migrate::command()])
+        .arg_required_else_help(true)
+}
+
+pub fn execute(config_dir: &Path, matches: &ArgMatches) -> Result<()> {
+    match matches.subcommand() {
+        Some((migrate::NAME, sub_matches)) => migrate::execute(config_dir, sub_matches),
+        _ => Ok(()),
+    }
+}
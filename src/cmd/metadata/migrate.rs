@@ -0,0 +1,59 @@
+use crate::metadata::{self, migrations, Metadata};
+use crate::Error;
+use anyhow::Result;
+use clap::{Arg, ArgAction, ArgMatches, Command};
+use std::path::Path;
+
+pub const NAME: &str = "migrate";
+
+pub fn command() -> Command {
+    Command::new(NAME)
+        .about("Migrate the metadata store to the current schema version")
+        .arg(
+            Arg::new("dry-run")
+                .help("Only print the migrated metadata, do not write it")
+                .long("dry-run")
+                .required(false)
+                .action(ArgAction::SetTrue)
+                .value_parser(clap::value_parser!(bool)),
+        )
+}
+
+pub fn execute(config_dir: &Path, matches: &ArgMatches) -> Result<()> {
+    let metadata_path = metadata::file_path(config_dir);
+    log::debug!("loading metadata from {}", metadata_path.display());
+
+    let raw = match metadata::read_raw(&metadata_path) {
+        Ok(raw) => raw,
+        Err(Error::IO(err)) if err.kind() == std::io::ErrorKind::NotFound => {
+            log::info!("no metadata found, nothing to migrate");
+            return Ok(());
+        }
+        Err(err) => return Err(err.into()),
+    };
+
+    let version = migrations::version(&raw)?;
+    let steps = migrations::plan(&version)?;
+
+    if steps.is_empty() {
+        log::info!("metadata is at version {} already", metadata::VERSION);
+        return Ok(());
+    }
+
+    for step in steps.iter() {
+        log::info!("{} -> {}: {}", step.from, step.to, step.description);
+    }
+
+    if matches.get_flag("dry-run") {
+        // make sure the migrated metadata is valid before printing it.
+        let migrated = serde_json::from_value::<Metadata>(migrations::migrate(raw)?)?;
+        println!("{}", serde_json::to_string_pretty(&migrated)?);
+        return Ok(());
+    }
+
+    // loading metadata migrates it in memory, writing it backs up the
+    // original file.
+    Metadata::from_file(&metadata_path)?.write(&metadata_path)?;
+
+    Ok(())
+}
@@ -1,14 +1,30 @@
-use anyhow::Result;
+use crate::store::git;
+use anyhow::{anyhow, Result};
 use clap::{value_parser, Arg, ArgMatches, Command};
 use std::path::{Path, PathBuf};
+use std::time::Duration;
 
+pub mod config;
+pub mod credential;
+pub mod expiring;
+pub mod fetch;
+pub mod get;
+pub mod history;
 pub mod import;
 pub mod label;
 pub mod list;
+pub mod metadata;
 pub mod r#move;
+mod output;
 pub mod prune;
+pub mod refresh;
 pub mod remove;
+pub mod restore;
+pub mod rollback;
 pub mod shell;
+pub mod show;
+pub mod store;
+pub mod trash;
 pub mod r#use;
 pub mod version;
 
@@ -32,30 +48,115 @@ pub fn cli() -> Command {
                 .value_parser(value_parser!(PathBuf))
                 .help("Directory to use for configuration and data store. Defaults to ~/.config/kbs or $XDG_CONFIG_DIR/kbs")
         )
+        .arg(
+            Arg::new("lock-timeout")
+                .long("lock-timeout")
+                .global(true)
+                .default_value("30s")
+                .value_parser(humantime::parse_duration)
+                .help("How long to wait for other kbs processes modifying the data store to finish")
+        )
         .subcommands([
             import::command(),
             list::command(),
+            get::command(),
+            show::command(),
+            expiring::command(),
+            store::command(),
             r#use::command(),
             shell::command(),
             remove::command(),
+            restore::command(),
+            trash::command(),
             version::command(),
             label::command(),
             prune::command(),
             r#move::command(),
-            // TODO: add subcommand 'fetch' to fetch kubeconfigs from remote systems.
+            fetch::command(),
+            refresh::command(),
+            metadata::command(),
+            config::command(),
+            history::command(),
+            rollback::command(),
+            credential::command(),
         ])
 }
 
 pub fn execute(config_path: &Path, matches: Option<(&str, &ArgMatches)>) -> Result<()> {
     match matches {
         Some((list::NAME, sub_matches)) => handle(list::execute(config_path, sub_matches)),
-        Some((import::NAME, sub_matches)) => handle(import::execute(config_path, sub_matches)),
+        Some((get::NAME, sub_matches)) => handle(get::execute(config_path, sub_matches)),
+        Some((show::NAME, sub_matches)) => handle(show::execute(config_path, sub_matches)),
+        Some((expiring::NAME, sub_matches)) => handle(expiring::execute(config_path, sub_matches)),
+        Some((import::NAME, sub_matches)) => {
+            handle(tracked(config_path, sub_matches, import::NAME, || {
+                import::execute(config_path, sub_matches)
+            }))
+        }
         Some((r#use::NAME, sub_matches)) => handle(r#use::execute(config_path, sub_matches)),
-        Some((shell::NAME, sub_matches)) => handle(shell::execute(sub_matches)),
-        Some((remove::NAME, sub_matches)) => handle(remove::execute(config_path, sub_matches)),
-        Some((label::NAME, sub_matches)) => handle(label::execute(config_path, sub_matches)),
-        Some((prune::NAME, sub_matches)) => handle(prune::execute(config_path, sub_matches)),
-        Some((r#move::NAME, sub_matches)) => handle(r#move::execute(config_path, sub_matches)),
+        Some((shell::NAME, sub_matches)) => handle(shell::execute(config_path, sub_matches)),
+        Some((remove::NAME, sub_matches)) => {
+            handle(tracked(config_path, sub_matches, remove::NAME, || {
+                remove::execute(config_path, sub_matches)
+            }))
+        }
+        Some((restore::NAME, sub_matches)) => {
+            handle(tracked(config_path, sub_matches, restore::NAME, || {
+                restore::execute(config_path, sub_matches)
+            }))
+        }
+        Some((trash::NAME, sub_matches)) => {
+            handle(tracked(config_path, sub_matches, trash::NAME, || {
+                trash::execute(config_path, sub_matches)
+            }))
+        }
+        Some((label::NAME, sub_matches)) => {
+            handle(tracked(config_path, sub_matches, label::NAME, || {
+                label::execute(config_path, sub_matches)
+            }))
+        }
+        Some((prune::NAME, sub_matches)) => {
+            handle(tracked(config_path, sub_matches, prune::NAME, || {
+                prune::execute(config_path, sub_matches)
+            }))
+        }
+        Some((r#move::NAME, sub_matches)) => {
+            handle(tracked(config_path, sub_matches, r#move::NAME, || {
+                r#move::execute(config_path, sub_matches)
+            }))
+        }
+        Some((fetch::NAME, sub_matches)) => {
+            handle(tracked(config_path, sub_matches, fetch::NAME, || {
+                fetch::execute(config_path, sub_matches)
+            }))
+        }
+        Some((refresh::NAME, sub_matches)) => {
+            handle(tracked(config_path, sub_matches, refresh::NAME, || {
+                refresh::execute(config_path, sub_matches)
+            }))
+        }
+        Some((metadata::NAME, sub_matches)) => {
+            handle(tracked(config_path, sub_matches, metadata::NAME, || {
+                metadata::execute(config_path, sub_matches)
+            }))
+        }
+        Some((config::NAME, sub_matches)) => {
+            handle(tracked(config_path, sub_matches, config::NAME, || {
+                config::execute(config_path, sub_matches)
+            }))
+        }
+        Some((store::NAME, sub_matches)) => {
+            handle(tracked(config_path, sub_matches, store::NAME, || {
+                store::execute(config_path, sub_matches)
+            }))
+        }
+        Some((history::NAME, sub_matches)) => handle(history::execute(config_path, sub_matches)),
+        Some((rollback::NAME, sub_matches)) => handle(locked(config_path, sub_matches, || {
+            rollback::execute(config_path, sub_matches)
+        })),
+        Some((credential::NAME, sub_matches)) => {
+            handle(credential::execute(config_path, sub_matches))
+        }
         Some((version::NAME, _)) => handle(version::execute()),
         _ => {
             log::error!("unknown command");
@@ -64,6 +165,40 @@ pub fn execute(config_path: &Path, matches: Option<(&str, &ArgMatches)>) -> Resu
     }
 }
 
+// Run a command that modifies the data store while holding the store lock.
+fn locked<F>(config_path: &Path, matches: &ArgMatches, f: F) -> Result<()>
+where
+    F: FnOnce() -> Result<()>,
+{
+    let timeout = matches
+        .get_one::<Duration>("lock-timeout")
+        .ok_or_else(|| anyhow!("failed to parse lock-timeout argument"))?;
+
+    let _lock = crate::store::lock(config_path, *timeout)?;
+    f()
+}
+
+// Run a command like `locked`, committing its changes afterwards if the
+// data store is tracked in git. Changes are committed even if the command
+// failed, as it might have changed the data store partially.
+fn tracked<F>(config_path: &Path, matches: &ArgMatches, command: &str, f: F) -> Result<()>
+where
+    F: FnOnce() -> Result<()>,
+{
+    locked(config_path, matches, || {
+        let result = f();
+
+        if git::is_enabled(config_path) {
+            if let Err(err) = git::commit(config_path, command) {
+                log::error!("failed to commit changes to the data store: {err}");
+                return result.and(Err(err.into()));
+            }
+        }
+
+        result
+    })
+}
+
 fn handle(res: Result<()>) -> Result<()> {
     match res {
         Err(err) => {
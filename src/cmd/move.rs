@@ -43,13 +43,16 @@ pub fn execute(config_dir: &Path, matches: &ArgMatches) -> Result<()> {
         Err(err) => bail!(err),
     };
 
-    kubeconfig::get(config_dir, source)?;
+    if !kubeconfig::get_path(config_dir, source).is_file() {
+        bail!("kubeconfig not found: {source}");
+    }
 
-    if kubeconfig::get(config_dir, destination).is_ok() {
+    if kubeconfig::get_path(config_dir, destination).exists() {
         bail!("{destination} already exists");
     }
 
     kubeconfig::r#move(config_dir, source, destination)?;
+    kubeconfig::credentials::r#move(config_dir, source, destination)?;
 
     metadata
         .rename(source, destination)?
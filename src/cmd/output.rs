@@ -0,0 +1,449 @@
+use crate::config::{Column, Output};
+use crate::kubeconfig::ListEntry;
+use anyhow::{anyhow, bail, Result};
+use jsonpath_rust::{path::config::JsonPathConfig, JsonPathInst};
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::btree_map::BTreeMap;
+use std::str::FromStr;
+
+/// Placeholder for values that are not set.
+pub const NONE: &str = "<none>";
+
+/// Document that structured output of several kubeconfigs is based on.
+#[derive(Serialize)]
+struct List<'a> {
+    items: &'a [ListEntry],
+}
+
+/// Print kubeconfigs in the given output format. Structured output formats
+/// (json, yaml, jsonpath and templates) are based on a document with all
+/// kubeconfigs as `items`.
+pub fn print_list(output: &Output, entries: &[ListEntry]) -> Result<()> {
+    match output {
+        Output::Name => {
+            for entry in entries.iter() {
+                println!("{}", entry.name);
+            }
+        }
+        Output::Table => print_table(
+            &["NAME", "LABELS"],
+            entries
+                .iter()
+                .map(|entry| vec![entry.name.clone(), format_labels(&entry.labels)])
+                .collect(),
+        ),
+        Output::Wide => print_table(
+            &[
+                "NAME",
+                "LABELS",
+                "SERVER",
+                "AUTH",
+                "CERT EXPIRY",
+                "CA EXPIRY",
+                "LAST USED",
+            ],
+            entries
+                .iter()
+                .map(|entry| {
+                    vec![
+                        entry.name.clone(),
+                        format_labels(&entry.labels),
+                        format_optional(&entry.server),
+                        match entry.auth.is_empty() {
+                            true => NONE.to_string(),
+                            false => entry
+                                .auth
+                                .iter()
+                                .map(|auth| auth.to_string())
+                                .collect::<Vec<String>>()
+                                .join(","),
+                        },
+                        format_optional(&entry.cert_expiry),
+                        format_optional(&entry.ca_expiry),
+                        format_optional(&entry.last_used),
+                    ]
+                })
+                .collect(),
+        ),
+        Output::CustomColumns(columns) => print_custom_columns(columns, entries)?,
+        Output::Json | Output::Yaml | Output::JsonPath(_) | Output::Template(_) => {
+            print_document(output, &List { items: entries })?
+        }
+    }
+
+    Ok(())
+}
+
+/// Print a single kubeconfig in the given output format. Structured output
+/// formats are based on the kubeconfig itself instead of a list.
+pub fn print_entry(output: &Output, entry: &ListEntry) -> Result<()> {
+    match output {
+        Output::Json | Output::Yaml | Output::JsonPath(_) | Output::Template(_) => {
+            print_document(output, entry)
+        }
+        _ => print_list(output, std::slice::from_ref(entry)),
+    }
+}
+
+fn print_document<T: Serialize>(output: &Output, document: &T) -> Result<()> {
+    match output {
+        Output::Json => println!("{}", serde_json::to_string_pretty(document)?),
+        // serialize to JSON first so that YAML output has the same structure
+        // (serde_yaml would represent enums as tagged values).
+        Output::Yaml => print!(
+            "{}",
+            serde_yaml::to_string(&serde_json::to_value(document)?)?
+        ),
+        Output::JsonPath(template) => {
+            print!("{}", jsonpath(template, &serde_json::to_value(document)?)?)
+        }
+        Output::Template(template) => print!(
+            "{}",
+            minijinja::Environment::new()
+                .render_str(template, document)
+                .map_err(|err| anyhow!("failed to render template: {err}"))?
+        ),
+        Output::Name | Output::Table | Output::Wide | Output::CustomColumns(_) => {
+            bail!("{output:?} is not a structured output format")
+        }
+    }
+
+    Ok(())
+}
+
+fn print_custom_columns(columns: &[Column], entries: &[ListEntry]) -> Result<()> {
+    let mut rows = vec![];
+    for entry in entries.iter() {
+        let value = serde_json::to_value(entry)?;
+        rows.push(
+            columns
+                .iter()
+                .map(|column| {
+                    format_value(
+                        column
+                            .path
+                            .iter()
+                            .try_fold(&value, |value, segment| value.get(segment)),
+                    )
+                })
+                .collect(),
+        );
+    }
+
+    print_table(
+        &columns
+            .iter()
+            .map(|column| column.header.as_str())
+            .collect::<Vec<&str>>(),
+        rows,
+    );
+
+    Ok(())
+}
+
+// Print rows as table, with each column as wide as its widest cell.
+pub fn print_table(header: &[&str], rows: Vec<Vec<String>>) {
+    let header: Vec<String> = header.iter().map(|h| h.to_string()).collect();
+    let rows: Vec<Vec<String>> = std::iter::once(header).chain(rows).collect();
+
+    let widths: Vec<usize> = (0..rows[0].len())
+        .map(|i| {
+            rows.iter()
+                .map(|row| row[i].chars().count())
+                .max()
+                .unwrap_or_default()
+        })
+        .collect();
+
+    for row in rows.iter() {
+        let line = row
+            .iter()
+            .zip(widths.iter())
+            .map(|(cell, width)| format!("{cell:<width$}"))
+            .collect::<Vec<String>>()
+            .join("   ");
+
+        println!("{}", line.trim_end());
+    }
+}
+
+// Part of a parsed JSONPath template.
+#[derive(Debug, PartialEq)]
+enum Node {
+    Text(String),
+    Expression(String),
+    // `{range <expression>}...{end}` renders its nodes for each result of
+    // the expression, with expressions relative to that result.
+    Range(String, Vec<Node>),
+}
+
+// Render a kubectl-style JSONPath template. Text outside of braces is printed
+// as is, expressions in braces are evaluated against the document. Multiple
+// results of an expression are separated by space.
+fn jsonpath(template: &str, document: &Value) -> Result<String> {
+    let mut tokens = tokenize(template)?.into_iter();
+    let nodes = parse(&mut tokens, template, false)?;
+
+    let mut result = String::new();
+    render(&nodes, document, &mut result)?;
+    Ok(result)
+}
+
+// Split a template into text and expressions. Expressions end at the first
+// closing brace that is neither quoted nor nested, so filters like
+// `{.items[?(@.name == "}")]}` are kept intact.
+fn tokenize(template: &str) -> Result<Vec<Node>> {
+    let mut tokens = vec![];
+    let mut text = String::new();
+    let mut chars = template.chars();
+
+    while let Some(c) = chars.next() {
+        if c != '{' {
+            text.push(c);
+            continue;
+        }
+
+        if !text.is_empty() {
+            tokens.push(Node::Text(std::mem::take(&mut text)));
+        }
+
+        let mut expression = String::new();
+        let mut quote: Option<char> = None;
+        let mut depth = 0;
+
+        loop {
+            let c = chars
+                .next()
+                .ok_or_else(|| anyhow!("unclosed expression in jsonpath template `{template}`"))?;
+
+            match (quote, c) {
+                (Some(_), '\\') => {
+                    expression.push(c);
+                    if let Some(c) = chars.next() {
+                        expression.push(c);
+                    }
+                    continue;
+                }
+                (Some(q), c) if c == q => quote = None,
+                (Some(_), _) => {}
+                (None, '"' | '\'') => quote = Some(c),
+                (None, '{') => depth += 1,
+                (None, '}') if depth == 0 => break,
+                (None, '}') => depth -= 1,
+                (None, _) => {}
+            }
+
+            expression.push(c);
+        }
+
+        tokens.push(Node::Expression(expression.trim().to_string()));
+    }
+
+    if !text.is_empty() {
+        tokens.push(Node::Text(text));
+    }
+
+    Ok(tokens)
+}
+
+// Turn tokens into a tree of nodes, matching `{range}` with `{end}`.
+fn parse(
+    tokens: &mut impl Iterator<Item = Node>,
+    template: &str,
+    in_range: bool,
+) -> Result<Vec<Node>> {
+    let mut nodes = vec![];
+
+    while let Some(token) = tokens.next() {
+        match token {
+            Node::Expression(expression) if expression == "end" => {
+                if !in_range {
+                    bail!("unexpected {{end}} in jsonpath template `{template}`");
+                }
+                return Ok(nodes);
+            }
+            Node::Expression(expression) => match expression.strip_prefix("range ") {
+                Some(range) => nodes.push(Node::Range(
+                    range.trim().to_string(),
+                    parse(tokens, template, true)?,
+                )),
+                None => nodes.push(Node::Expression(expression)),
+            },
+            token => nodes.push(token),
+        }
+    }
+
+    if in_range {
+        bail!("missing {{end}} for {{range}} in jsonpath template `{template}`");
+    }
+
+    Ok(nodes)
+}
+
+fn render(nodes: &[Node], document: &Value, result: &mut String) -> Result<()> {
+    for node in nodes {
+        match node {
+            Node::Text(text) => result.push_str(text),
+            Node::Expression(expression) => result.push_str(
+                &evaluate(expression, document)?
+                    .iter()
+                    .map(|value| match value {
+                        Value::String(s) => s.clone(),
+                        value => value.to_string(),
+                    })
+                    .collect::<Vec<String>>()
+                    .join(" "),
+            ),
+            Node::Range(expression, nodes) => {
+                // like kubectl, ranging over a single list iterates over its
+                // elements.
+                let values = match evaluate(expression, document)? {
+                    values if values.len() == 1 && values[0].is_array() => {
+                        match values.into_iter().next() {
+                            Some(Value::Array(values)) => values,
+                            _ => vec![],
+                        }
+                    }
+                    values => values,
+                };
+
+                for value in values.iter() {
+                    render(nodes, value, result)?;
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn evaluate(expression: &str, document: &Value) -> Result<Vec<Value>> {
+    // quoted text is printed as is, e.g. `{"\n"}`.
+    if let Some(text) = expression
+        .strip_prefix('"')
+        .and_then(|expression| expression.strip_suffix('"'))
+    {
+        return Ok(vec![Value::String(
+            text.replace("\\n", "\n").replace("\\t", "\t"),
+        )]);
+    }
+
+    // kubectl omits the leading `$` that jsonpath_rust requires.
+    let path = match expression {
+        "." | "$" => "$".to_string(),
+        e if e.starts_with('$') => e.to_string(),
+        e if e.starts_with('.') || e.starts_with('[') => format!("${e}"),
+        e => bail!("invalid jsonpath expression `{e}`, expected it to start with `.`"),
+    };
+
+    let path = JsonPathInst::from_str(&path)
+        .map_err(|err| anyhow!("invalid jsonpath expression `{expression}`: {err}"))?;
+
+    Ok(path
+        .find_slice(document, JsonPathConfig::default())
+        .iter()
+        .map(|value| (**value).clone())
+        .collect())
+}
+
+pub fn format_labels(map: &Option<BTreeMap<String, String>>) -> String {
+    if let Some(labels) = map {
+        return labels
+            .iter()
+            .map(|(key, value)| -> String { format!("{key}={value}") })
+            .collect::<Vec<String>>()
+            .join(",");
+    }
+
+    "".to_string()
+}
+
+fn format_optional<T: std::fmt::Display>(value: &Option<T>) -> String {
+    value
+        .as_ref()
+        .map_or_else(|| NONE.to_string(), |value| value.to_string())
+}
+
+// Format a value for custom-columns output. Lists are joined by comma,
+// maps are printed as comma-separated key=value pairs.
+fn format_value(value: Option<&Value>) -> String {
+    match value {
+        None | Some(Value::Null) => NONE.to_string(),
+        Some(Value::String(s)) => s.clone(),
+        Some(Value::Array(values)) => values
+            .iter()
+            .map(|value| format_value(Some(value)))
+            .collect::<Vec<String>>()
+            .join(","),
+        Some(Value::Object(map)) => map
+            .iter()
+            .map(|(key, value)| format!("{key}={}", format_value(Some(value))))
+            .collect::<Vec<String>>()
+            .join(","),
+        Some(value) => value.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_jsonpath() {
+        let document = json!({
+            "items": [
+                {"name": "a", "labels": {"env": "prod"}},
+                {"name": "b", "labels": {"env": "dev"}},
+            ]
+        });
+
+        assert_eq!("a b", jsonpath("{.items[*].name}", &document).unwrap());
+        assert_eq!(
+            "env: prod\n",
+            jsonpath(r#"env: {.items[0].labels.env}{"\n"}"#, &document).unwrap()
+        );
+        assert_eq!(
+            "b",
+            jsonpath(r#"{.items[?(@.labels.env == "dev")].name}"#, &document).unwrap()
+        );
+        assert_eq!(
+            r#"{"env":"prod"}"#,
+            jsonpath("{$.items[0].labels}", &document).unwrap()
+        );
+
+        // braces in quotes don't end the expression.
+        assert_eq!(
+            "a b",
+            jsonpath(r#"{.items[?(@.name != "}")].name}"#, &document).unwrap()
+        );
+        assert!(jsonpath("{.items[*].name", &document).is_err());
+        assert!(jsonpath("{items}", &document).is_err());
+    }
+
+    #[test]
+    fn test_jsonpath_range() {
+        let document = json!({
+            "items": [
+                {"name": "a", "labels": {"env": "prod"}},
+                {"name": "b", "labels": {"env": "dev"}},
+            ]
+        });
+
+        assert_eq!(
+            "a=prod\nb=dev\n",
+            jsonpath(
+                r#"{range .items[*]}{.name}={.labels.env}{"\n"}{end}"#,
+                &document
+            )
+            .unwrap()
+        );
+        assert_eq!(
+            "a,b,",
+            jsonpath("{range .items}{.name},{end}", &document).unwrap()
+        );
+        assert!(jsonpath("{range .items[*]}{.name}", &document).is_err());
+        assert!(jsonpath("{.items[*].name}{end}", &document).is_err());
+    }
+}
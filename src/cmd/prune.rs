@@ -1,10 +1,17 @@
+use super::output;
+use crate::config::{settings, Settings};
 use crate::kubeconfig;
-use crate::metadata::{self, Metadata};
-use anyhow::Result;
+use crate::kubeconfig::reachability::{Failure, Reachability};
+use crate::metadata::{self, ConfigMetadata, Metadata, Timestamp};
+use anyhow::{anyhow, Result};
+use clap::builder::RangedU64ValueParser;
 use clap::{Arg, ArgAction, ArgMatches, Command};
-use futures::executor;
-use std::fs;
+use futures::StreamExt;
+use kube::config::Kubeconfig;
+use std::collections::HashMap;
+use std::io::{IsTerminal, Write};
 use std::path::Path;
+use std::time::Duration;
 
 pub const NAME: &str = "prune";
 
@@ -12,9 +19,45 @@ pub fn command() -> Command {
     Command::new(NAME)
         .visible_alias("p")
         .about("Remove kubeconfigs for Kubernetes API servers that are no longer accessible")
+        .long_about("Remove stale kubeconfigs. Kubeconfigs are pruned if they match any of the passed strategies (--expired, --unused-for, --unreachable). Without any strategy, kubeconfigs for Kubernetes API servers that are no longer accessible are pruned")
+        .arg(
+            Arg::new("expired")
+                .help("Prune kubeconfigs with an expired client certificate or token")
+                .long("expired")
+                .required(false)
+                .action(ArgAction::SetTrue)
+                .value_parser(clap::value_parser!(bool)),
+        )
+        .arg(
+            Arg::new("unused-for")
+                .help("Prune kubeconfigs that have not been used with 'kbs use' for this duration, e.g. '90d'")
+                .long("unused-for")
+                .required(false)
+                .action(ArgAction::Set)
+                .value_parser(humantime::parse_duration),
+        )
+        .arg(
+            Arg::new("unreachable")
+                .help("Prune kubeconfigs for Kubernetes API servers that are no longer accessible")
+                .long("unreachable")
+                .required(false)
+                .action(ArgAction::SetTrue)
+                .value_parser(clap::value_parser!(bool)),
+        )
+        .arg(
+            Arg::new("prune-on")
+                .help("Failures of --unreachable checks that kubeconfigs are pruned for. By default, only network-level failures are considered")
+                .long("prune-on")
+                .required(false)
+                .action(ArgAction::Set)
+                .value_delimiter(',')
+                .env("KBS_PRUNE_ON")
+                .default_value("network,dns,timeout")
+                .value_parser(clap::value_parser!(Failure)),
+        )
         .arg(
             Arg::new("dry-run")
-                .help("Only list kubeconfigs with unreachable Kubernetes API servers, do not delete them")
+                .help("Only list kubeconfigs that should be pruned, do not delete them")
                 .long("dry-run")
                 .short('n')
                 .required(false)
@@ -25,14 +68,34 @@ pub fn command() -> Command {
                 .require_equals(true)
                 .value_parser(clap::value_parser!(bool)),
         )
+        .arg(
+            Arg::new("timeout")
+                .help("How long to wait for a Kubernetes API server to respond")
+                .long("timeout")
+                .required(false)
+                .num_args(1)
+                .env("KBS_PRUNE_TIMEOUT")
+                .default_value("10s")
+                .value_parser(humantime::parse_duration),
+        )
+        .arg(
+            Arg::new("concurrency")
+                .help("How many Kubernetes API servers to check at the same time")
+                .long("concurrency")
+                .required(false)
+                .num_args(1)
+                .env("KBS_PRUNE_CONCURRENCY")
+                .default_value("10")
+                .value_parser(RangedU64ValueParser::<usize>::new().range(1..)),
+        )
         .arg(
             Arg::new("selectors")
-                .help("Selector (label query) to filter on. Supports key=value comma-separated values")
+                .help("Selector (label query) to filter on. Supports '=', '==', '!=', 'in', 'notin', '<key>' (exists) and '!<key>' (does not exist), e.g. 'env in (prod,staging),!legacy'")
                 .long("selector")
                 .short('l')
                 .required(false)
                 .num_args(0..)
-                .value_delimiter(',')
+                .action(ArgAction::Append)
                 .value_parser(metadata::selectors::parse),
         )
 }
@@ -43,41 +106,213 @@ pub fn execute(config_dir: &Path, matches: &ArgMatches) -> Result<()> {
     let selectors = metadata::selectors::from_args(matches, "selectors")?;
     let dry_run = matches.get_flag("dry-run");
 
+    let expired = matches.get_flag("expired");
+    let unused_for = matches.get_one::<Duration>("unused-for").copied();
+    // checking reachability is the default if no strategy has been passed.
+    let unreachable = matches.get_flag("unreachable") || (!expired && unused_for.is_none());
+    let settings = Settings::load(config_dir)?;
+    let timeout = settings::resolve(matches, "timeout", settings.prune.timeout()?)
+        .ok_or_else(|| anyhow!("failed to parse timeout argument"))?;
+    let concurrency = settings::resolve(matches, "concurrency", settings.prune.concurrency)
+        .ok_or_else(|| anyhow!("failed to parse concurrency argument"))?;
+    let prune_on: Vec<Failure> =
+        settings::resolve_many(matches, "prune-on", settings.prune.prune_on()?);
+
     let metadata_path = metadata::file_path(config_dir);
     log::debug!("loading metadata from {}", metadata_path.display());
     let mut metadata = Metadata::from_file(&metadata_path)?;
 
-    let kubeconfigs = kubeconfig::list(config_dir, &metadata, Some(selectors))?;
-
-    for entry in kubeconfigs.iter() {
-        log::info!("checking if '{}' should be pruned", entry.name);
-        let (kubecfg_path, kubecfg) = kubeconfig::get(config_dir, &entry.name)?;
-
-        let options = kube::config::KubeConfigOptions {
-            cluster: None,
-            context: None,
-            user: None,
-        };
-
-        let config = executor::block_on(kube::Config::from_custom_kubeconfig(kubecfg, &options))?;
-        let client = kube::client::Client::try_from(config)?;
-
-        let response = executor::block_on(client.apiserver_version());
-
-        if response.is_err() {
-            if !dry_run {
-                fs::remove_file(&kubecfg_path)?;
-                log::info!(
-                    "pruned kubeconfig '{}' at {}",
-                    entry.name,
-                    kubecfg_path.display()
-                );
-                metadata = metadata.remove(&entry.name);
-            } else {
-                log::info!("'{}' should be pruned", entry.name);
+    let mut kubeconfigs = vec![];
+    for entry in kubeconfig::list(config_dir, &metadata, Some(selectors))? {
+        let (_, kubecfg) = kubeconfig::get(config_dir, &entry.name)?;
+        kubeconfigs.push((entry.name, kubecfg));
+    }
+
+    let mut checked = HashMap::new();
+    if unreachable {
+        let results = check_reachability(
+            kubeconfigs
+                .iter()
+                .map(|(name, kubecfg)| (name.clone(), kubecfg.clone()))
+                .collect(),
+            timeout,
+            concurrency,
+        );
+
+        output::print_table(
+            &["NAME", "STATUS", "FAILURE", "REASON"],
+            results
+                .iter()
+                .map(|(name, result)| match result {
+                    Reachability::Reachable => vec![
+                        name.clone(),
+                        "reachable".to_string(),
+                        "".to_string(),
+                        "".to_string(),
+                    ],
+                    Reachability::Failed(failure, reason) => vec![
+                        name.clone(),
+                        match failure.is_network() {
+                            true => "unreachable",
+                            false => "error",
+                        }
+                        .to_string(),
+                        failure.to_string(),
+                        reason.clone(),
+                    ],
+                })
+                .collect(),
+        );
+
+        checked.extend(results);
+    }
+
+    let now = Timestamp::now();
+
+    for (name, kubecfg) in kubeconfigs.iter() {
+        let mut reasons = vec![];
+
+        if expired {
+            reasons.extend(check_expired(kubecfg, metadata.get(name), now));
+        }
+
+        if let Some(unused_for) = unused_for {
+            reasons.extend(check_unused(metadata.get(name), unused_for, now));
+        }
+
+        if let Some(Reachability::Failed(failure, reason)) = checked.get(name) {
+            match prune_on.contains(failure) {
+                true => reasons.push(format!("{failure} failure ({reason})")),
+                false => log::info!(
+                    "not pruning '{name}' for {failure} failure, pass --prune-on to change that: {reason}"
+                ),
             }
-        };
+        }
+
+        if reasons.is_empty() {
+            continue;
+        }
+
+        let reasons = reasons.join(", ");
+
+        if !dry_run {
+            kubeconfig::trash::put(
+                config_dir,
+                name,
+                metadata.get(name),
+                Some(format!("pruned: {reasons}")),
+            )?;
+            log::info!("pruned kubeconfig '{name}', moved it to trash: {reasons}");
+            metadata = metadata.remove(name);
+        } else {
+            log::info!("'{name}' should be pruned: {reasons}");
+        }
+    }
+
+    if !dry_run {
+        metadata.write(&metadata_path)?;
     }
 
     Ok(())
 }
+
+// Client certificates and tokens of kubeconfigs imported with exec
+// credentials are not in the kubeconfig anymore, so their expiry is taken
+// from metadata.
+fn check_expired(
+    kubecfg: &Kubeconfig,
+    config_metadata: Option<&ConfigMetadata>,
+    now: Timestamp,
+) -> Vec<String> {
+    let mut reasons = vec![];
+
+    if let Some(not_after) = kubeconfig::get_expiry(kubecfg)
+        .client_cert
+        .or_else(|| config_metadata.and_then(|m| m.expiry.as_ref()?.client_cert))
+        .filter(|not_after| *not_after < now)
+    {
+        reasons.push(format!("client certificate expired at {not_after}"));
+    }
+
+    if let Some(exp) = kubeconfig::get_token_expiry(kubecfg)
+        .or_else(|| config_metadata.and_then(|m| m.expiry.as_ref()?.token))
+        .filter(|exp| *exp < now)
+    {
+        reasons.push(format!("token expired at {exp}"));
+    }
+
+    reasons
+}
+
+// Kubeconfigs that have never been used are considered unused since their
+// import. Without either timestamp, a kubeconfig is never pruned as unused.
+fn check_unused(
+    config_metadata: Option<&ConfigMetadata>,
+    unused_for: Duration,
+    now: Timestamp,
+) -> Option<String> {
+    let config_metadata = config_metadata?;
+
+    let (since, reason) = match config_metadata.last_used {
+        Some(last_used) => (last_used, format!("last used at {last_used}")),
+        None => {
+            let imported_at = config_metadata.source.as_ref()?.imported_at?;
+            (
+                imported_at,
+                format!("never used since import at {imported_at}"),
+            )
+        }
+    };
+
+    match now.0.duration_since(since.0) {
+        Ok(unused) if unused >= unused_for => Some(reason),
+        _ => None,
+    }
+}
+
+// Check the Kubernetes API servers of all kubeconfigs, with at most
+// `concurrency` checks running at the same time. Results are returned in
+// the order of the passed kubeconfigs. The checks run on the tokio runtime
+// of main, which kube clients rely on for timers and IO.
+fn check_reachability(
+    kubeconfigs: Vec<(String, Kubeconfig)>,
+    timeout: Duration,
+    concurrency: usize,
+) -> Vec<(String, Reachability)> {
+    let total = kubeconfigs.len();
+    let progress = std::io::stderr().is_terminal();
+
+    let runtime = tokio::runtime::Handle::current();
+    let mut results: Vec<(usize, String, Reachability)> = tokio::task::block_in_place(|| {
+        runtime.block_on(async {
+            let mut checks = futures::stream::iter(kubeconfigs.into_iter().enumerate())
+                .map(|(i, (name, kubecfg))| async move {
+                    log::debug!("checking if '{name}' is reachable");
+                    let result = kubeconfig::reachability::check(kubecfg, timeout).await;
+                    (i, name, result)
+                })
+                .buffer_unordered(concurrency);
+
+            let mut results = vec![];
+            while let Some(result) = checks.next().await {
+                results.push(result);
+                if progress {
+                    eprint!("\rchecked {}/{total} kubeconfigs", results.len());
+                    let _ = std::io::stderr().flush();
+                }
+            }
+
+            if progress && total > 0 {
+                eprintln!();
+            }
+
+            results
+        })
+    });
+
+    results.sort_by_key(|(i, _, _)| *i);
+    results
+        .into_iter()
+        .map(|(_, name, result)| (name, result))
+        .collect()
+}
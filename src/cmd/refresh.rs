@@ -0,0 +1,169 @@
+use crate::fetch;
+use crate::kubeconfig;
+use crate::metadata::{self, Metadata};
+use anyhow::{bail, Result};
+use clap::{value_parser, Arg, ArgAction, ArgMatches, Command};
+use kube::config::Kubeconfig;
+use std::path::Path;
+
+pub const NAME: &str = "refresh";
+
+pub fn command() -> Command {
+    Command::new(NAME)
+        .visible_alias("r")
+        .about("Re-import kubeconfigs from the source they were originally imported from")
+        .arg(
+            Arg::new("kubeconfig")
+                .help("Name of the kubeconfig to refresh. Refreshes all kubeconfigs if omitted")
+                .value_parser(value_parser!(String))
+                .conflicts_with("selectors"),
+        )
+        .arg(
+            Arg::new("selectors")
+                .help("Selector (label query) to filter on. Supports '=', '==', '!=', 'in', 'notin', '<key>' (exists) and '!<key>' (does not exist), e.g. 'env in (prod,staging),!legacy'")
+                .long("selector")
+                .short('l')
+                .required(false)
+                .action(ArgAction::Append)
+                .value_parser(metadata::selectors::parse),
+        )
+        .arg(
+            Arg::new("dry-run")
+                .help("Only report which kubeconfigs changed at their source, do not update them")
+                .long("dry-run")
+                .required(false)
+                .action(ArgAction::SetTrue)
+                .value_parser(clap::value_parser!(bool)),
+        )
+}
+
+pub fn execute(config_dir: &Path, matches: &ArgMatches) -> Result<()> {
+    let dry_run = matches.get_flag("dry-run");
+
+    let metadata_path = metadata::file_path(config_dir);
+    log::debug!("loading metadata from {}", metadata_path.display());
+    let mut metadata = Metadata::from_file(&metadata_path)?;
+
+    let names: Vec<String> = match matches.get_one::<String>("kubeconfig") {
+        Some(name) => vec![name.to_string()],
+        None => {
+            let selectors = metadata::selectors::from_args(matches, "selectors")?;
+            kubeconfig::list(config_dir, &metadata, Some(selectors))?
+                .into_iter()
+                .map(|entry| entry.name)
+                .collect()
+        }
+    };
+
+    let mut failed = 0;
+
+    for name in names.iter() {
+        let config_metadata = match metadata.get(name) {
+            Some(config_metadata) => config_metadata.clone(),
+            None => {
+                log::info!("skipping '{name}' as it has no metadata");
+                continue;
+            }
+        };
+
+        let record = match &config_metadata.source {
+            Some(record) if record.origin != fetch::Source::Stdin => record,
+            Some(_) => {
+                log::info!("skipping '{name}' as it was imported from stdin");
+                continue;
+            }
+            None => {
+                log::info!("skipping '{name}' as no source was recorded on import");
+                continue;
+            }
+        };
+
+        let data = match record.origin.fetch() {
+            Ok(data) => data,
+            Err(err) => {
+                log::warn!("failed to fetch '{name}' from {}: {err}", record.origin);
+                failed += 1;
+                continue;
+            }
+        };
+
+        if record.hash.as_ref() == Some(&metadata::hash(&data)) {
+            log::info!("'{name}' is up to date");
+            continue;
+        }
+
+        let new_kubeconfig =
+            match kubeconfig::from_bytes(&data).and_then(|new_kubeconfig| match &record.context {
+                Some(context) => kubeconfig::extract(&new_kubeconfig, context),
+                None => Ok(new_kubeconfig),
+            }) {
+                Ok(kubeconfig) => kubeconfig,
+                Err(err) => {
+                    log::warn!("failed to parse '{name}' from {}: {err}", record.origin);
+                    failed += 1;
+                    continue;
+                }
+            };
+
+        if dry_run {
+            log::info!("'{name}' has changed at {}", record.origin);
+            continue;
+        }
+
+        // failing to save one kubeconfig should not lose the metadata of
+        // those refreshed before it.
+        if let Err(err) = save(config_dir, name, &new_kubeconfig) {
+            log::warn!("failed to save '{name}': {err}");
+            failed += 1;
+            continue;
+        }
+
+        log::info!("refreshed '{name}' from {}", record.origin);
+
+        metadata = metadata.set(
+            name.to_string(),
+            metadata::ConfigMetadata {
+                source: Some(metadata::SourceRecord {
+                    context: record.context.clone(),
+                    ..metadata::SourceRecord::new(record.origin.clone(), &data)
+                }),
+                expiry: Some(kubeconfig::get_expiry(&new_kubeconfig)),
+                ..config_metadata
+            },
+        );
+    }
+
+    if !dry_run {
+        metadata.write(&metadata_path)?;
+        log::debug!(
+            "wrote metadata database update to {}",
+            metadata_path.display()
+        );
+    }
+
+    if failed > 0 {
+        bail!("failed to refresh {failed} kubeconfig(s)");
+    }
+
+    Ok(())
+}
+
+fn save(config_dir: &Path, name: &str, new_kubeconfig: &Kubeconfig) -> Result<()> {
+    // keep a proxy URL configured on the existing kubeconfig, as
+    // it was most likely set on import and is not part of the source.
+    let (_, existing) = kubeconfig::get(config_dir, name)?;
+    let proxy = kubeconfig::get_proxy_url(&existing);
+
+    // the same goes for credentials moved to the credential store.
+    let exec_credentials = kubeconfig::credentials::exists(config_dir, name);
+
+    kubeconfig::save(
+        config_dir,
+        new_kubeconfig,
+        name,
+        proxy.as_ref(),
+        exec_credentials,
+    )?;
+
+    Ok(())
+}
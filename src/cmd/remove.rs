@@ -4,7 +4,7 @@ use crate::{config, kubeconfig};
 use anyhow::Result;
 use anyhow::{anyhow, bail};
 use clap::{value_parser, Arg, ArgAction, ArgGroup, ArgMatches, Command};
-use std::{fs, path::Path};
+use std::path::Path;
 
 pub const NAME: &str = "remove";
 
@@ -16,11 +16,11 @@ pub fn command() -> Command {
         .arg(Arg::new("kubeconfig").value_parser(value_parser!(String)))
         .arg(
             Arg::new("selectors")
-                .help("Selector (label query) to filter on. Supports key=value comma-separated values")
+                .help("Selector (label query) to filter on. Supports '=', '==', '!=', 'in', 'notin', '<key>' (exists) and '!<key>' (does not exist), e.g. 'env in (prod,staging),!legacy'")
                 .long("selector")
                 .short('l')
                 .num_args(0..)
-                .value_delimiter(',')
+                .action(ArgAction::Append)
                 .value_parser(metadata::selectors::parse),
         )
         .arg(
@@ -62,7 +62,7 @@ pub fn execute(config_dir: &Path, matches: &ArgMatches) -> Result<()> {
                 vec![
                     (kubeconfig::ListEntry {
                         name: config.to_string(),
-                        labels: None,
+                        ..Default::default()
                     }),
                 ]
             } else if matches.contains_id("active") {
@@ -70,7 +70,7 @@ pub fn execute(config_dir: &Path, matches: &ArgMatches) -> Result<()> {
                 vec![
                     (kubeconfig::ListEntry {
                         name: current,
-                        labels: None,
+                        ..Default::default()
                     }),
                 ]
             } else {
@@ -81,9 +81,13 @@ pub fn execute(config_dir: &Path, matches: &ArgMatches) -> Result<()> {
 
     for entry in removals.iter() {
         let kubeconfig_path = kubeconfig::get_path(config_dir, &entry.name);
-        if kubeconfig::get(config_dir, &entry.name).is_ok() {
-            fs::remove_file(&kubeconfig_path)?;
-            log::info!("removed kubeconfig at {}", kubeconfig_path.display());
+        if kubeconfig_path.is_file() {
+            kubeconfig::trash::put(config_dir, &entry.name, metadata.get(&entry.name), None)?;
+            log::info!(
+                "moved kubeconfig {} to trash, use 'kbs restore {}' to restore it",
+                entry.name,
+                entry.name
+            );
             metadata = metadata.remove(&entry.name);
         } else {
             return Err(anyhow!("kubeconfig not found: {:?}", &entry.name));
@@ -0,0 +1,46 @@
+use crate::kubeconfig::trash;
+use crate::metadata::{self, Metadata};
+use crate::Error;
+use anyhow::{anyhow, bail, Result};
+use clap::{value_parser, Arg, ArgAction, ArgMatches, Command};
+use std::path::Path;
+
+pub const NAME: &str = "restore";
+
+pub fn command() -> Command {
+    Command::new(NAME)
+        .about("Restore a removed or pruned kubeconfig from trash, including its labels")
+        .arg(
+            Arg::new("kubeconfig")
+                .help("Name of the kubeconfig. If it has been removed several times, the most recently removed one is restored")
+                .required(true)
+                .action(ArgAction::Set)
+                .value_parser(value_parser!(String)),
+        )
+        .arg_required_else_help(true)
+}
+
+pub fn execute(config_dir: &Path, matches: &ArgMatches) -> Result<()> {
+    let name = matches
+        .get_one::<String>("kubeconfig")
+        .ok_or_else(|| anyhow!("failed to get kubeconfig argument"))?;
+
+    let metadata_path = metadata::file_path(config_dir);
+    log::debug!("loading metadata from {}", metadata_path.display());
+    let mut metadata = match Metadata::from_file(&metadata_path) {
+        Ok(metadata) => metadata,
+        Err(Error::IO(err)) if err.kind() == std::io::ErrorKind::NotFound => Metadata::new(),
+        Err(err) => bail!(err),
+    };
+
+    let entry = trash::restore(config_dir, name)?;
+
+    if let Some(config_metadata) = entry.metadata {
+        metadata = metadata.set(name.clone(), config_metadata);
+        metadata.write(&metadata_path)?;
+    }
+
+    log::info!("restored kubeconfig {name} removed at {}", entry.deleted_at);
+
+    Ok(())
+}
@@ -0,0 +1,62 @@
+use crate::kubeconfig::{self, trash};
+use crate::store::git;
+use anyhow::{anyhow, Result};
+use clap::{value_parser, Arg, ArgAction, ArgMatches, Command};
+use std::collections::BTreeSet;
+use std::path::Path;
+
+pub const NAME: &str = "rollback";
+
+pub fn command() -> Command {
+    Command::new(NAME)
+        .about("Restore the data store to an earlier revision, if it is tracked in git")
+        .arg(
+            Arg::new("revision")
+                .help("Revision to restore, as shown by 'kbs history' (e.g. '3f2a1b4' or 'HEAD~1')")
+                .required(true)
+                .action(ArgAction::Set)
+                .value_parser(value_parser!(String)),
+        )
+        .arg(
+            Arg::new("force")
+                .help("Roll back even if the encryption of the data store has changed since the revision, which can bring back plain kubeconfigs")
+                .long("force")
+                .required(false)
+                .action(ArgAction::SetTrue)
+                .value_parser(clap::value_parser!(bool)),
+        )
+        .arg_required_else_help(true)
+}
+
+pub fn execute(config_dir: &Path, matches: &ArgMatches) -> Result<()> {
+    let revision = matches
+        .get_one::<String>("revision")
+        .ok_or_else(|| anyhow!("failed to get revision argument"))?;
+
+    // kubeconfigs in the trash that do not exist in the data store now.
+    let trashed: BTreeSet<String> = trash::list(config_dir)?
+        .into_iter()
+        .map(|entry| entry.name)
+        .filter(|name| !kubeconfig::get_path(config_dir, name).exists())
+        .collect();
+
+    match git::rollback(config_dir, revision, matches.get_flag("force"))? {
+        Some(id) => log::info!("restored data store to revision {revision} as {id}"),
+        None => log::info!("data store is at revision {revision} already"),
+    }
+
+    // kubeconfigs brought back by the rollback are restored from the trash,
+    // so they don't exist twice. Entries are sorted newest first.
+    let mut restored = BTreeSet::new();
+    for entry in trash::list(config_dir)? {
+        if trashed.contains(&entry.name)
+            && kubeconfig::get_path(config_dir, &entry.name).exists()
+            && restored.insert(entry.name.clone())
+        {
+            log::debug!("removing kubeconfig {} from trash", entry.name);
+            trash::delete(&entry)?;
+        }
+    }
+
+    Ok(())
+}
@@ -1,5 +1,6 @@
 use anyhow::Result;
 use clap::{ArgMatches, Command};
+use std::path::Path;
 
 mod completion;
 mod magic;
@@ -14,10 +15,10 @@ pub fn command() -> Command {
         .arg_required_else_help(true)
 }
 
-pub fn execute(matches: &ArgMatches) -> Result<()> {
+pub fn execute(config_dir: &Path, matches: &ArgMatches) -> Result<()> {
     match matches.subcommand() {
         Some((completion::NAME, sub_matches)) => completion::execute(sub_matches),
-        Some((magic::NAME, sub_matches)) => magic::execute(sub_matches),
+        Some((magic::NAME, sub_matches)) => magic::execute(config_dir, sub_matches),
         _ => Ok(()),
     }
 }
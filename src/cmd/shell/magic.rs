@@ -1,8 +1,12 @@
-use anyhow::{anyhow, Result};
+use crate::config::{settings, Settings};
+use anyhow::{anyhow, bail, Result};
 use clap::{builder::PossibleValue, value_parser, Arg, ArgAction, ArgMatches, Command};
+use std::path::Path;
 
 pub const NAME: &str = "magic";
 
+const DEFAULT_FZF_OPTIONS: &str = "--height=30% --reverse --inline-info --border";
+
 pub fn command() -> Command {
     Command::new(NAME)
         .about(
@@ -14,9 +18,20 @@ pub fn command() -> Command {
                 .action(ArgAction::Set)
                 .value_parser(value_parser!(Shell)),
         )
+        .arg(
+            Arg::new("fzf-options")
+                .help("Options passed to fzf when selecting a kubeconfig")
+                .long("fzf-options")
+                .required(false)
+                .num_args(1)
+                .env("KBS_FZF_OPTIONS")
+                .default_value(DEFAULT_FZF_OPTIONS)
+                .allow_hyphen_values(true)
+                .value_parser(clap::value_parser!(String)),
+        )
 }
 
-pub fn execute(matches: &ArgMatches) -> Result<()> {
+pub fn execute(config_dir: &Path, matches: &ArgMatches) -> Result<()> {
     let shell = matches
         .get_one::<Shell>("shell")
         .ok_or_else(|| anyhow!("cannot read shell"))?;
@@ -25,11 +40,35 @@ pub fn execute(matches: &ArgMatches) -> Result<()> {
         Shell::Zsh => include_str!("./files/zsh/kbs.source"),
         Shell::Bash => include_str!("./files/bash/kbs.source"),
     };
-    print!("{magic}");
+    let settings = Settings::load(config_dir)?;
+    let fzf_options = settings::resolve(matches, "fzf-options", settings.shell.fzf_options)
+        .ok_or_else(|| anyhow!("cannot read fzf options"))?;
+
+    print!(
+        "{}",
+        magic.replace("{{FZF_COMMAND}}", &fzf_command(&fzf_options)?)
+    );
 
     Ok(())
 }
 
+// Build the quoted fzf command line the shell magic aliases. The options are
+// split into words like a shell would and each of them is quoted again, so
+// they cannot break out of the alias definition.
+fn fzf_command(options: &str) -> Result<String> {
+    let Some(options) = shlex::split(options) else {
+        bail!("invalid fzf options, quotes are not balanced: {options}");
+    };
+
+    let command = shlex::try_join(
+        ["fzf", "-0", "--header-lines=1"]
+            .into_iter()
+            .chain(options.iter().map(String::as_str)),
+    )?;
+
+    Ok(shlex::try_quote(&command)?.into_owned())
+}
+
 #[derive(Clone, Copy, Debug)]
 #[non_exhaustive]
 enum Shell {
@@ -49,3 +88,33 @@ impl clap::ValueEnum for Shell {
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fzf_command() {
+        assert_eq!(
+            r#""fzf -0 '--header-lines=1' '--height=30%' --reverse --inline-info --border""#,
+            fzf_command(DEFAULT_FZF_OPTIONS).unwrap()
+        );
+
+        // options are split into words and quoted, shell syntax has no effect.
+        let command = fzf_command(r#"--preview 'cat {}' "$(touch pwned)"; echo"#).unwrap();
+        assert_eq!(
+            Some(vec![
+                "fzf".to_string(),
+                "-0".to_string(),
+                "--header-lines=1".to_string(),
+                "--preview".to_string(),
+                "cat {}".to_string(),
+                "$(touch pwned);".to_string(),
+                "echo".to_string(),
+            ]),
+            shlex::split(&shlex::split(&command).unwrap()[0])
+        );
+
+        assert!(fzf_command("--preview 'cat {}").is_err());
+    }
+}
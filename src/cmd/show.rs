@@ -0,0 +1,145 @@
+use super::output::NONE;
+use crate::metadata::{self, Metadata, Timestamp};
+use crate::{kubeconfig, Error};
+use anyhow::{anyhow, bail, Result};
+use clap::{value_parser, Arg, ArgAction, ArgMatches, Command};
+use std::path::Path;
+
+pub const NAME: &str = "show";
+
+pub fn command() -> Command {
+    Command::new(NAME)
+        .about("Show a summary and the content of a kubeconfig, with credentials redacted")
+        .arg(
+            Arg::new("kubeconfig")
+                .help("Name of the kubeconfig to show")
+                .required(true)
+                .action(ArgAction::Set)
+                .value_parser(value_parser!(String)),
+        )
+        .arg(
+            Arg::new("reveal")
+                .help("Show credentials (client keys, tokens, passwords) instead of redacting them")
+                .long("reveal")
+                .required(false)
+                .action(ArgAction::SetTrue)
+                .value_parser(clap::value_parser!(bool)),
+        )
+        .arg_required_else_help(true)
+}
+
+pub fn execute(config_dir: &Path, matches: &ArgMatches) -> Result<()> {
+    let name = matches
+        .get_one::<String>("kubeconfig")
+        .ok_or_else(|| anyhow!("failed to get kubeconfig argument"))?;
+
+    let metadata_path = metadata::file_path(config_dir);
+    log::debug!("loading metadata from {}", metadata_path.display());
+    let metadata = match Metadata::from_file(&metadata_path) {
+        Ok(metadata) => metadata,
+        Err(Error::IO(err)) if err.kind() == std::io::ErrorKind::NotFound => Metadata::new(),
+        Err(err) => bail!(err),
+    };
+
+    let (_, kubeconfig) = kubeconfig::get(config_dir, name)
+        .map_err(|err| anyhow!("failed to load kubeconfig {name}: {err}"))?;
+    let config_metadata = metadata.get(name);
+    let now = Timestamp::now();
+
+    // the summary is printed as YAML comments, so the output as a whole
+    // is still a valid kubeconfig (when using --reveal).
+    let mut summary: Vec<(&str, String)> = vec![
+        ("Name", name.to_string()),
+        ("Server", join(kubeconfig::get_servers(&kubeconfig))),
+        (
+            "CA Fingerprint",
+            join(
+                kubeconfig::get_ca_fingerprints(&kubeconfig).unwrap_or_else(|err| {
+                    log::warn!("failed to read certificate authorities of {name}: {err}");
+                    vec![]
+                }),
+            ),
+        ),
+        (
+            "Auth",
+            join(
+                kubeconfig::get_auth(&kubeconfig)
+                    .iter()
+                    .map(|auth| auth.to_string())
+                    .collect(),
+            ),
+        ),
+        (
+            "Client Cert",
+            join(
+                kubeconfig::get_client_certs(&kubeconfig)
+                    .unwrap_or_else(|err| {
+                        log::warn!("failed to read client certificates of {name}: {err}");
+                        vec![]
+                    })
+                    .iter()
+                    .map(|cert| {
+                        let state = match cert.not_after < now {
+                            true => "expired",
+                            false => "expires",
+                        };
+                        format!("{} ({state} {})", cert.subject, cert.not_after)
+                    })
+                    .collect(),
+            ),
+        ),
+        (
+            "Proxy URL",
+            join(kubeconfig::get_proxy_url(&kubeconfig).into_iter().collect()),
+        ),
+        (
+            "Labels",
+            join(
+                config_metadata
+                    .map(|m| {
+                        m.labels
+                            .iter()
+                            .map(|(key, value)| format!("{key}={value}"))
+                            .collect()
+                    })
+                    .unwrap_or_default(),
+            ),
+        ),
+    ];
+
+    match config_metadata.and_then(|m| m.source.as_ref()) {
+        Some(source) => {
+            summary.push(("Source", source.origin.to_string()));
+            if let Some(context) = &source.context {
+                summary.push(("Source Context", context.to_string()));
+            }
+            if let Some(imported_at) = &source.imported_at {
+                summary.push(("Imported At", imported_at.to_string()));
+            }
+            if let Some(hash) = &source.hash {
+                summary.push(("Hash", hash.to_string()));
+            }
+        }
+        None => summary.push(("Source", NONE.to_string())),
+    }
+
+    for (key, value) in summary.iter() {
+        println!("# {:<16}{value}", format!("{key}:"));
+    }
+
+    let content = match matches.get_flag("reveal") {
+        true => serde_yaml::to_string(&kubeconfig)?,
+        false => serde_yaml::to_string(&kubeconfig::redact(&kubeconfig)?)?,
+    };
+
+    print!("{content}");
+
+    Ok(())
+}
+
+fn join(values: Vec<String>) -> String {
+    match values.is_empty() {
+        true => NONE.to_string(),
+        false => values.join(", "),
+    }
+}
@@ -0,0 +1,51 @@
+use crate::kubeconfig;
+use crate::metadata::Metadata;
+use anyhow::Result;
+use clap::{ArgMatches, Command};
+use std::path::{Path, PathBuf};
+
+mod decrypt;
+mod encrypt;
+mod export;
+mod import;
+mod track;
+
+pub const NAME: &str = "store";
+
+pub fn command() -> Command {
+    Command::new(NAME)
+        .about("Manage the kubeconfig data store")
+        .subcommands([
+            encrypt::command(),
+            decrypt::command(),
+            track::command(),
+            export::command(),
+            import::command(),
+        ])
+        .arg_required_else_help(true)
+}
+
+pub fn execute(config_dir: &Path, matches: &ArgMatches) -> Result<()> {
+    match matches.subcommand() {
+        Some((encrypt::NAME, sub_matches)) => encrypt::execute(config_dir, sub_matches),
+        Some((decrypt::NAME, sub_matches)) => decrypt::execute(config_dir, sub_matches),
+        Some((track::NAME, sub_matches)) => track::execute(config_dir, sub_matches),
+        Some((export::NAME, sub_matches)) => export::execute(config_dir, sub_matches),
+        Some((import::NAME, sub_matches)) => import::execute(config_dir, sub_matches),
+        _ => Ok(()),
+    }
+}
+
+// All files in the data store that hold credentials, i.e. kubeconfigs, the
+// credential store and the trash.
+fn files(config_dir: &Path) -> Result<Vec<PathBuf>> {
+    let mut files: Vec<PathBuf> = kubeconfig::list(config_dir, &Metadata::new(), None)?
+        .iter()
+        .map(|entry| kubeconfig::get_path(config_dir, &entry.name))
+        .collect();
+
+    files.extend(kubeconfig::credentials::files(config_dir)?);
+    files.extend(kubeconfig::trash::files(config_dir)?);
+
+    Ok(files)
+}
@@ -0,0 +1,34 @@
+use super::files;
+use crate::store::{self, Encryption};
+use anyhow::{anyhow, Result};
+use clap::{ArgMatches, Command};
+use std::fs;
+use std::path::Path;
+
+pub const NAME: &str = "decrypt";
+
+pub fn command() -> Command {
+    Command::new(NAME).about("Decrypt all kubeconfigs in the data store and stop encrypting them")
+}
+
+pub fn execute(config_dir: &Path, _matches: &ArgMatches) -> Result<()> {
+    let encryption =
+        Encryption::load(config_dir)?.ok_or_else(|| anyhow!("data store is not encrypted"))?;
+
+    for path in files(config_dir)? {
+        let data = fs::read(&path)?;
+
+        if !store::is_encrypted(&data) {
+            continue;
+        }
+
+        store::write(&path, &encryption.decrypt(config_dir, &data)?)?;
+        log::info!("decrypted {}", path.display());
+    }
+
+    // only stop encrypting once all kubeconfigs have been decrypted, so
+    // the data store can still be read if decrypting is interrupted.
+    Encryption::remove(config_dir)?;
+
+    Ok(())
+}
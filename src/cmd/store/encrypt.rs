@@ -0,0 +1,66 @@
+use super::files;
+use crate::store::{self, Encryption};
+use anyhow::{bail, Result};
+use clap::{value_parser, Arg, ArgAction, ArgMatches, Command};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+pub const NAME: &str = "encrypt";
+
+pub fn command() -> Command {
+    Command::new(NAME)
+        .about("Encrypt all kubeconfigs in the data store and all kubeconfigs imported later on")
+        .arg(
+            Arg::new("identity")
+                .help("age identity file (e.g. created by age-keygen) to encrypt kubeconfigs for. If not set, a new identity is generated and protected with a passphrase, which is read from $KBS_PASSPHRASE or prompted for")
+                .long("identity")
+                .short('i')
+                .required(false)
+                .action(ArgAction::Set)
+                .value_parser(value_parser!(PathBuf)),
+        )
+}
+
+pub fn execute(config_dir: &Path, matches: &ArgMatches) -> Result<()> {
+    let identity = matches.get_one::<PathBuf>("identity").map(PathBuf::as_path);
+
+    // an existing encryption is reused, so an interrupted run can be
+    // completed by running the command again.
+    let encryption = match Encryption::load(config_dir)? {
+        Some(_) if identity.is_some() => {
+            bail!("data store is already encrypted, run 'kbs store decrypt' first to change the identity")
+        }
+        Some(encryption) => {
+            log::info!("data store is already encrypted, encrypting remaining kubeconfigs");
+            encryption
+        }
+        None => {
+            let encryption = Encryption::init(config_dir, identity)?;
+            log::info!("encrypting kubeconfigs for {}", encryption.recipient);
+            encryption
+        }
+    };
+
+    // encryption is set up before converting kubeconfigs, so the data store
+    // can be read even if converting is interrupted.
+    let mut encrypted = 0;
+    for path in files(config_dir)? {
+        let data = fs::read(&path)?;
+
+        if store::is_encrypted(&data) {
+            continue;
+        }
+
+        store::write(&path, &encryption.encrypt(&data)?)?;
+        log::info!("encrypted {}", path.display());
+        encrypted += 1;
+    }
+
+    log::info!("encrypted {encrypted} file(s)");
+
+    if store::git::is_enabled(config_dir) {
+        log::warn!("kubeconfigs in earlier revisions of the data store are not encrypted, see 'kbs history'");
+    }
+
+    Ok(())
+}
@@ -0,0 +1,115 @@
+use crate::metadata::{self, Metadata};
+use crate::store::Bundle;
+use crate::{kubeconfig, Error};
+use anyhow::{anyhow, bail, Result};
+use clap::{value_parser, Arg, ArgAction, ArgMatches, Command};
+use std::path::{Path, PathBuf};
+
+pub const NAME: &str = "export";
+
+pub fn command() -> Command {
+    Command::new(NAME)
+        .about("Export kubeconfigs and their metadata to a bundle, which can be imported with 'kbs store import'")
+        .arg(
+            Arg::new("kubeconfigs")
+                .help("Names of the kubeconfigs to export. If neither names nor selectors are passed, all kubeconfigs are exported")
+                .required(false)
+                .action(ArgAction::Append)
+                .value_parser(value_parser!(String)),
+        )
+        .arg(
+            Arg::new("selectors")
+                .help("Selector (label query) to filter on. Supports '=', '==', '!=', 'in', 'notin', '<key>' (exists) and '!<key>' (does not exist), e.g. 'env in (prod,staging),!legacy'")
+                .long("selector")
+                .short('l')
+                .required(false)
+                .action(ArgAction::Append)
+                .value_parser(metadata::selectors::parse),
+        )
+        .arg(
+            Arg::new("output")
+                .help("File to write the bundle to, e.g. 'bundle.tar.zst'")
+                .long("output")
+                .short('o')
+                .required(true)
+                .action(ArgAction::Set)
+                .value_parser(value_parser!(PathBuf)),
+        )
+        .arg(
+            Arg::new("encrypt")
+                .help("Encrypt the bundle with a passphrase, which is read from $KBS_BUNDLE_PASSPHRASE or prompted for")
+                .long("encrypt")
+                .required(false)
+                .action(ArgAction::SetTrue)
+                .value_parser(clap::value_parser!(bool)),
+        )
+}
+
+pub fn execute(config_dir: &Path, matches: &ArgMatches) -> Result<()> {
+    let output = matches
+        .get_one::<PathBuf>("output")
+        .ok_or_else(|| anyhow!("failed to get output argument"))?;
+
+    let names: Vec<&String> = matches
+        .get_many::<String>("kubeconfigs")
+        .map(|names| names.collect())
+        .unwrap_or_default();
+
+    let selectors = metadata::selectors::from_args(matches, "selectors")?;
+
+    let metadata_path = metadata::file_path(config_dir);
+    log::debug!("loading metadata from {}", metadata_path.display());
+    let metadata = match Metadata::from_file(&metadata_path) {
+        Ok(metadata) => metadata,
+        Err(Error::IO(err)) if err.kind() == std::io::ErrorKind::NotFound => Metadata::new(),
+        Err(err) => bail!(err),
+    };
+
+    let mut entries = kubeconfig::list(config_dir, &metadata, Some(selectors))?;
+
+    if !names.is_empty() {
+        if let Some(name) = names
+            .iter()
+            .find(|name| !entries.iter().any(|entry| entry.name == ***name))
+        {
+            bail!("kubeconfig {name} not found or not matching selectors");
+        }
+
+        entries.retain(|entry| names.contains(&&entry.name));
+    }
+
+    if entries.is_empty() {
+        bail!("no kubeconfigs to export");
+    }
+
+    let mut bundle = Bundle::new();
+
+    for entry in entries.iter() {
+        let (_, kubecfg) = kubeconfig::get(config_dir, &entry.name)?;
+
+        // the credential store is specific to this data store.
+        let kubecfg = kubeconfig::credentials::inline(config_dir, &kubecfg, &entry.name)?;
+
+        bundle.add(
+            &entry.name,
+            serde_yaml::to_string(&kubecfg)?.into_bytes(),
+            metadata.get(&entry.name),
+        );
+        log::debug!("exporting kubeconfig {}", entry.name);
+    }
+
+    let encrypt = matches.get_flag("encrypt");
+    bundle.write(output, encrypt)?;
+
+    log::info!(
+        "exported {} kubeconfig(s) to {}",
+        entries.len(),
+        output.display()
+    );
+
+    if !encrypt {
+        log::warn!("bundle holds credentials in plain text, pass --encrypt to protect it with a passphrase");
+    }
+
+    Ok(())
+}
@@ -0,0 +1,183 @@
+use crate::config::{settings, Settings};
+use crate::kubeconfig::{self, template::NameTemplate, OnConflict};
+use crate::metadata::{self, labels, ConfigMetadata, Metadata};
+use crate::store::Bundle;
+use crate::Error;
+use anyhow::{anyhow, bail, Result};
+use clap::{value_parser, Arg, ArgAction, ArgMatches, Command};
+use std::path::{Path, PathBuf};
+
+pub const NAME: &str = "import";
+
+pub fn command() -> Command {
+    Command::new(NAME)
+        .about("Import kubeconfigs and their metadata from a bundle created by 'kbs store export'")
+        .arg(
+            Arg::new("bundle")
+                .help("Bundle to import. Encrypted bundles are decrypted with a passphrase, which is read from $KBS_BUNDLE_PASSPHRASE or prompted for")
+                .required(true)
+                .action(ArgAction::Set)
+                .value_parser(value_parser!(PathBuf)),
+        )
+        .arg(
+            Arg::new("on-conflict")
+                .help("What to do if a different kubeconfig with the same name exists already. 'rename' appends a numeric suffix, 'overwrite' merges labels and 'prompt' asks interactively. Identical kubeconfigs only get their labels merged")
+                .long("on-conflict")
+                .required(false)
+                .action(ArgAction::Set)
+                .default_value(OnConflict::Rename)
+                .value_parser(value_parser!(OnConflict)),
+        )
+        .arg(
+            Arg::new("exec-credentials")
+                .help("Move user credentials to the credential store of kbs and let the kubeconfig call 'kbs credential' as exec plugin to retrieve them")
+                .long("exec-credentials")
+                .required(false)
+                .env("KBS_EXEC_CREDENTIALS")
+                .action(ArgAction::SetTrue)
+                .value_parser(clap::value_parser!(bool)),
+        )
+        .arg_required_else_help(true)
+}
+
+pub fn execute(config_dir: &Path, matches: &ArgMatches) -> Result<()> {
+    let path = matches
+        .get_one::<PathBuf>("bundle")
+        .ok_or_else(|| anyhow!("failed to get bundle argument"))?;
+
+    let on_conflict = *matches
+        .get_one::<OnConflict>("on-conflict")
+        .ok_or_else(|| anyhow!("failed to parse on-conflict argument"))?;
+
+    let settings = Settings::load(config_dir)?;
+    let exec_credentials = settings::resolve(
+        matches,
+        "exec-credentials",
+        settings.import.exec_credentials,
+    )
+    .unwrap_or_default();
+
+    let metadata_path = metadata::file_path(config_dir);
+    log::debug!("loading metadata from {}", metadata_path.display());
+    let mut metadata = match Metadata::from_file(&metadata_path) {
+        Ok(metadata) => metadata,
+        Err(Error::IO(err)) if err.kind() == std::io::ErrorKind::NotFound => Metadata::new(),
+        Err(err) => bail!(err),
+    };
+
+    let bundle = Bundle::read(path)?;
+    let template = NameTemplate::default();
+    let mut imported = 0;
+    let mut result = Ok(());
+
+    for (name, data) in bundle.kubeconfigs.iter() {
+        let kubecfg = match kubeconfig::from_bytes(data) {
+            Ok(kubecfg) => kubecfg,
+            Err(err) => {
+                log::warn!("skipping kubeconfig {name} in bundle: {err}");
+                continue;
+            }
+        };
+
+        let bundle_labels: Vec<labels::Label> = bundle
+            .metadata
+            .get(name)
+            .map(|m| {
+                m.labels
+                    .iter()
+                    .map(|(key, value)| labels::Label {
+                        key: key.clone(),
+                        value: Some(value.clone()),
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        let identical = is_identical(config_dir, name, &kubecfg);
+
+        let target = match identical {
+            true => {
+                log::info!(
+                    "kubeconfig {name} exists with the same content already, merging labels"
+                );
+                name.clone()
+            }
+            false => {
+                let naming = kubeconfig::Naming {
+                    name: Some(name),
+                    template: &template,
+                    path: None,
+                    use_short: false,
+                };
+
+                match kubeconfig::import(
+                    config_dir,
+                    &kubecfg,
+                    &naming,
+                    None,
+                    exec_credentials,
+                    on_conflict,
+                ) {
+                    Ok(Some(target)) => {
+                        log::info!("imported kubeconfig {name} from bundle as {target}");
+                        target
+                    }
+                    Ok(None) => continue,
+                    // metadata of kubeconfigs imported so far is written
+                    // before failing.
+                    Err(err) => {
+                        result = Err(err);
+                        break;
+                    }
+                }
+            }
+        };
+
+        // the recorded source and expiry come along with a new (or
+        // overwritten) kubeconfig, while labels are merged into those of an
+        // existing kubeconfig.
+        let config_metadata = match metadata.get(&target) {
+            Some(existing) if identical => ConfigMetadata {
+                labels: labels::merge(existing, &bundle_labels, true)?,
+                ..existing.clone()
+            },
+            Some(existing) => ConfigMetadata {
+                labels: labels::merge(existing, &bundle_labels, true)?,
+                last_used: existing.last_used,
+                ..bundle.metadata.get(name).cloned().unwrap_or_default()
+            },
+            None => bundle.metadata.get(name).cloned().unwrap_or_default(),
+        };
+
+        metadata = metadata.set(target, config_metadata);
+        imported += 1;
+    }
+
+    metadata.write(&metadata_path)?;
+    log::info!(
+        "imported {imported} of {} kubeconfig(s) from {}",
+        bundle.kubeconfigs.len(),
+        path.display()
+    );
+
+    Ok(result?)
+}
+
+// Check if the kubeconfig stored under name is the same as kubecfg, taking
+// credentials moved to the credential store into account.
+fn is_identical(config_dir: &Path, name: &str, kubecfg: &kube::config::Kubeconfig) -> bool {
+    if !kubeconfig::get_path(config_dir, name).exists() {
+        return false;
+    }
+
+    let stored = kubeconfig::get(config_dir, name)
+        .and_then(|(_, stored)| kubeconfig::credentials::inline(config_dir, &stored, name));
+
+    match stored {
+        Ok(stored) => serde_yaml::to_string(&stored).ok() == serde_yaml::to_string(kubecfg).ok(),
+        Err(err) => {
+            log::debug!("failed to compare kubeconfig {name}: {err}");
+            false
+        }
+    }
+}
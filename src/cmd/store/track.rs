@@ -0,0 +1,21 @@
+use crate::store::git;
+use anyhow::Result;
+use clap::{ArgMatches, Command};
+use std::path::Path;
+
+pub const NAME: &str = "track";
+
+pub fn command() -> Command {
+    Command::new(NAME)
+        .about("Track the data store in a local git repository, recording every change to it")
+}
+
+pub fn execute(config_dir: &Path, _matches: &ArgMatches) -> Result<()> {
+    git::init(config_dir)?;
+    log::info!(
+        "tracking data store at {} in git, see 'kbs history' for changes",
+        config_dir.display()
+    );
+
+    Ok(())
+}
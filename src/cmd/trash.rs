@@ -0,0 +1,23 @@
+use anyhow::Result;
+use clap::{ArgMatches, Command};
+use std::path::Path;
+
+mod empty;
+mod list;
+
+pub const NAME: &str = "trash";
+
+pub fn command() -> Command {
+    Command::new(NAME)
+        .about("Manage removed kubeconfigs, which can be restored with 'kbs restore'")
+        .subcommands([list::command(), empty::command()])
+        .arg_required_else_help(true)
+}
+
+pub fn execute(config_dir: &Path, matches: &ArgMatches) -> Result<()> {
+    match matches.subcommand() {
+        Some((list::NAME, sub_matches)) => list::execute(config_dir, sub_matches),
+        Some((empty::NAME, sub_matches)) => empty::execute(config_dir, sub_matches),
+        _ => Ok(()),
+    }
+}
@@ -0,0 +1,62 @@
+use crate::kubeconfig::trash;
+use crate::metadata::Timestamp;
+use anyhow::Result;
+use clap::{Arg, ArgAction, ArgGroup, ArgMatches, Command};
+use std::path::Path;
+use std::time::Duration;
+
+pub const NAME: &str = "empty";
+
+pub fn command() -> Command {
+    Command::new(NAME)
+        .about("Permanently delete removed kubeconfigs")
+        .arg(
+            Arg::new("older-than")
+                .help("Only delete kubeconfigs removed longer ago than this duration, e.g. '30d'")
+                .long("older-than")
+                .required(false)
+                .action(ArgAction::Set)
+                .value_parser(humantime::parse_duration),
+        )
+        .arg(
+            Arg::new("all")
+                .help("Delete all kubeconfigs in the trash")
+                .long("all")
+                .required(false)
+                .action(ArgAction::SetTrue)
+                .value_parser(clap::value_parser!(bool)),
+        )
+        .group(
+            ArgGroup::new("selection")
+                .args(["older-than", "all"])
+                .required(true),
+        )
+}
+
+pub fn execute(config_dir: &Path, matches: &ArgMatches) -> Result<()> {
+    let older_than = matches.get_one::<Duration>("older-than");
+    let now = Timestamp::now();
+
+    let mut deleted = 0;
+
+    for entry in trash::list(config_dir)? {
+        if let Some(older_than) = older_than {
+            match now.0.duration_since(entry.deleted_at.0) {
+                Ok(age) if age > *older_than => {}
+                _ => continue,
+            }
+        }
+
+        trash::delete(&entry)?;
+        log::debug!(
+            "deleted kubeconfig {} removed at {}",
+            entry.name,
+            entry.deleted_at
+        );
+        deleted += 1;
+    }
+
+    log::info!("deleted {deleted} kubeconfig(s) from trash");
+
+    Ok(())
+}
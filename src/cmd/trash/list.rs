@@ -0,0 +1,39 @@
+use crate::cmd::output;
+use crate::kubeconfig::trash;
+use anyhow::Result;
+use clap::{ArgMatches, Command};
+use std::path::Path;
+
+pub const NAME: &str = "list";
+
+pub fn command() -> Command {
+    Command::new(NAME)
+        .visible_alias("ls")
+        .about("List removed kubeconfigs, most recently removed first")
+}
+
+pub fn execute(config_dir: &Path, _matches: &ArgMatches) -> Result<()> {
+    let entries = trash::list(config_dir)?;
+
+    if entries.is_empty() {
+        log::info!("trash is empty");
+        return Ok(());
+    }
+
+    output::print_table(
+        &["NAME", "REMOVED", "LABELS", "REASON"],
+        entries
+            .into_iter()
+            .map(|entry| {
+                vec![
+                    entry.name,
+                    entry.deleted_at.to_string(),
+                    output::format_labels(&entry.metadata.map(|m| m.labels)),
+                    entry.reason.unwrap_or_else(|| "removed".to_string()),
+                ]
+            })
+            .collect(),
+    );
+
+    Ok(())
+}
@@ -1,6 +1,8 @@
-use crate::{config, kubeconfig};
+use crate::metadata::{self, Metadata};
+use crate::{config, kubeconfig, store, Error};
 use anyhow::{anyhow, Result};
 use clap::{value_parser, Arg, ArgAction, ArgMatches, Command};
+use std::fs;
 use std::path::Path;
 
 pub const NAME: &str = "use";
@@ -42,15 +44,47 @@ pub fn execute(config_path: &Path, matches: &ArgMatches) -> Result<()> {
         Err(e) => return Err(e),
     };
 
-    if let Ok(kubecfg) = kubeconfig::get(config_path, &config) {
-        if requires_store {
-            config::save_last_active(config_path, &config)?;
-            log::debug!("stored {config} as last active kubeconfig");
-        }
+    let (path, kubecfg) = kubeconfig::get(config_path, &config)
+        .map_err(|err| anyhow!("failed to load kubeconfig: {err}"))?;
 
-        print!("export KUBECONFIG={}", kubecfg.0.display());
-        return Ok(());
+    if requires_store {
+        config::save_last_active(config_path, &config)?;
+        log::debug!("stored {config} as last active kubeconfig");
+    }
+
+    // failing to record usage should not prevent using the kubeconfig.
+    if let Err(err) = record_usage(config_path, &config) {
+        log::warn!("failed to record usage of {config}: {err}");
     }
 
-    Err(anyhow!("failed to load kubeconfig"))
+    // kubeconfigs are only stored in plain text outside of the data store.
+    let path = match store::is_encrypted(&fs::read(&path)?) {
+        true => store::write_runtime(&config, serde_yaml::to_string(&kubecfg)?.as_bytes())?,
+        false => path,
+    };
+
+    print!("export KUBECONFIG={}", path.display());
+    Ok(())
+}
+
+// Usage is recorded on a best-effort basis. `kbs use` does not wait for
+// other processes modifying the data store (e.g. a long-running prune), as
+// that would block activating kubeconfigs.
+fn record_usage(config_path: &Path, name: &str) -> Result<(), Error> {
+    let Some(_lock) = store::try_lock(config_path)? else {
+        log::debug!("data store is locked, not recording usage of {name}");
+        return Ok(());
+    };
+
+    let metadata_path = metadata::file_path(config_path);
+    let metadata = match Metadata::from_file(&metadata_path) {
+        Ok(metadata) => metadata,
+        Err(Error::IO(err)) if err.kind() == std::io::ErrorKind::NotFound => Metadata::new(),
+        Err(err) => return Err(err),
+    };
+
+    metadata.set_used(name).write(&metadata_path)?;
+    log::debug!("recorded usage of {name} in {}", metadata_path.display());
+
+    Ok(())
 }
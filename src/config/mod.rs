@@ -1,10 +1,14 @@
-use crate::Error;
-use clap::builder::PossibleValue;
+use crate::{store, Error};
 use std::{
     env, fs, io,
     path::{Path, PathBuf},
+    str::FromStr,
 };
 
+pub mod settings;
+
+pub use settings::Settings;
+
 #[cfg(test)]
 mod tests;
 
@@ -28,32 +32,104 @@ pub fn get_last_active(config_path: &Path) -> io::Result<String> {
     fs::read_to_string(config_path.join(ACTIVE_FILE_NAME))
 }
 
-pub fn save_last_active(config_path: &Path, name: &String) -> io::Result<()> {
-    fs::write(config_path.join(ACTIVE_FILE_NAME), name)
+pub fn save_last_active(config_path: &Path, name: &String) -> Result<(), Error> {
+    store::write(&config_path.join(ACTIVE_FILE_NAME), name.as_bytes())
 }
 
-#[derive(Clone, Copy, Debug, PartialEq)]
+/// Output format for listing kubeconfigs.
+#[derive(Clone, Debug, PartialEq)]
 #[non_exhaustive]
 pub enum Output {
     Name,
     Table,
+    Wide,
+    Json,
+    Yaml,
+    CustomColumns(Vec<Column>),
+    /// kubectl-style JSONPath template, e.g. `{.items[*].name}`.
+    JsonPath(String),
+    /// Jinja-style template rendered with minijinja.
+    Template(String),
 }
 
-impl clap::ValueEnum for Output {
-    fn value_variants<'a>() -> &'a [Self] {
-        &[Output::Name, Output::Table]
-    }
+/// Column for custom-columns output, e.g. `ENV:.labels.env`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Column {
+    pub header: String,
+    pub path: Vec<String>,
+}
+
+const CUSTOM_COLUMNS_PREFIX: &str = "custom-columns=";
+const JSONPATH_PREFIX: &str = "jsonpath=";
+const TEMPLATE_PREFIX: &str = "template=";
+// alias for users coming from kubectl, templates still use Jinja syntax.
+const GO_TEMPLATE_PREFIX: &str = "go-template=";
+
+impl FromStr for Output {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Output, Error> {
+        if let Some(spec) = s.strip_prefix(CUSTOM_COLUMNS_PREFIX) {
+            return Ok(Output::CustomColumns(
+                spec.split(',')
+                    .map(parse_column)
+                    .collect::<Result<Vec<Column>, Error>>()?,
+            ));
+        }
 
-    fn to_possible_value<'a>(&self) -> Option<PossibleValue> {
-        Some(match self {
-            Output::Name => PossibleValue::new("name"),
-            Output::Table => PossibleValue::new("table"),
-        })
+        if let Some(template) = s.strip_prefix(JSONPATH_PREFIX) {
+            return Ok(Output::JsonPath(template.to_string()));
+        }
+
+        if let Some(template) = s
+            .strip_prefix(TEMPLATE_PREFIX)
+            .or_else(|| s.strip_prefix(GO_TEMPLATE_PREFIX))
+        {
+            return Ok(Output::Template(template.to_string()));
+        }
+
+        match s {
+            "name" => Ok(Output::Name),
+            "table" => Ok(Output::Table),
+            "wide" => Ok(Output::Wide),
+            "json" => Ok(Output::Json),
+            "yaml" => Ok(Output::Yaml),
+            _ => Err(Error::Message(format!(
+                "unknown output format `{s}`, supported formats are name, table, wide, json, yaml, {CUSTOM_COLUMNS_PREFIX}<header>:<path>,..., {JSONPATH_PREFIX}<template> and {TEMPLATE_PREFIX}<template> (or {GO_TEMPLATE_PREFIX}<template>)"
+            ))),
+        }
     }
 }
 
-impl From<Output> for clap::builder::OsStr {
-    fn from(value: Output) -> clap::builder::OsStr {
-        value.into()
+// Parse a single column of custom-columns output. Dots in a path segment
+// (e.g. in label keys) can be escaped as `\.`.
+fn parse_column(s: &str) -> Result<Column, Error> {
+    let (header, path) = s
+        .split_once(':')
+        .ok_or_else(|| Error::Message(format!("invalid column `{s}`, expected <header>:<path>")))?;
+
+    let path = path.strip_prefix('.').ok_or_else(|| {
+        Error::Message(format!("invalid column `{s}`, path has to start with `.`"))
+    })?;
+
+    if header.is_empty() || path.is_empty() {
+        return Err(Error::Message(format!(
+            "invalid column `{s}`, expected <header>:<path>"
+        )));
     }
+
+    let mut segments = vec![String::new()];
+    let mut chars = path.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => segments.last_mut().unwrap().extend(chars.next()),
+            '.' => segments.push(String::new()),
+            c => segments.last_mut().unwrap().push(c),
+        }
+    }
+
+    Ok(Column {
+        header: header.to_string(),
+        path: segments,
+    })
 }
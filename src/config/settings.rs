@@ -0,0 +1,318 @@
+use crate::config::Output;
+use crate::kubeconfig::reachability::Failure;
+use crate::kubeconfig::template::{self, NameTemplate};
+use crate::metadata::labels;
+use crate::{store, Error};
+use clap::{parser::ValueSource, ArgMatches, ValueEnum};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::time::Duration;
+use std::{fs, io, path::Path, path::PathBuf};
+
+const SETTINGS_FILE_NAME: &str = "config.toml";
+
+/// All keys that can be changed with [`Settings::set`].
+pub const KEYS: &[&str] = &[
+    "list.output",
+    "import.name-template",
+    "import.labels",
+    "import.proxy-url",
+    "import.exec-credentials",
+    "prune.timeout",
+    "prune.concurrency",
+    "prune.prune-on",
+    "shell.fzf-options",
+];
+
+/// User settings read from `config.toml` in the configuration directory.
+/// Settings only provide defaults, flags and environment variables always
+/// take precedence.
+#[derive(Debug, Default, Deserialize, Serialize)]
+#[serde(default, rename_all = "kebab-case", deny_unknown_fields)]
+pub struct Settings {
+    #[serde(skip_serializing_if = "ListSettings::is_empty")]
+    pub list: ListSettings,
+    #[serde(skip_serializing_if = "ImportSettings::is_empty")]
+    pub import: ImportSettings,
+    #[serde(skip_serializing_if = "PruneSettings::is_empty")]
+    pub prune: PruneSettings,
+    #[serde(skip_serializing_if = "ShellSettings::is_empty")]
+    pub shell: ShellSettings,
+}
+
+/// Defaults for listing kubeconfigs.
+#[derive(Debug, Default, Deserialize, Serialize)]
+#[serde(default, rename_all = "kebab-case", deny_unknown_fields)]
+pub struct ListSettings {
+    /// Output format to list kubeconfigs in.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub output: Option<String>,
+}
+
+/// Defaults for kubeconfig imports.
+#[derive(Debug, Default, Deserialize, Serialize)]
+#[serde(default, rename_all = "kebab-case", deny_unknown_fields)]
+pub struct ImportSettings {
+    /// Template to name imported kubeconfigs by if no name is passed.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name_template: Option<String>,
+    /// Labels added to all imported kubeconfigs.
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub labels: BTreeMap<String, String>,
+    /// Proxy URL configured for all imported kubeconfigs.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub proxy_url: Option<String>,
+    /// Whether to move user credentials of imported kubeconfigs to the
+    /// credential store.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub exec_credentials: Option<bool>,
+}
+
+/// Defaults for pruning kubeconfigs.
+#[derive(Debug, Default, Deserialize, Serialize)]
+#[serde(default, rename_all = "kebab-case", deny_unknown_fields)]
+pub struct PruneSettings {
+    /// How long to wait for a Kubernetes API server to respond, e.g. `10s`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub timeout: Option<String>,
+    /// How many Kubernetes API servers to check at the same time.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub concurrency: Option<usize>,
+    /// Failures of reachability checks that kubeconfigs are pruned for.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub prune_on: Option<Vec<String>>,
+}
+
+/// Defaults for shell integration.
+#[derive(Debug, Default, Deserialize, Serialize)]
+#[serde(default, rename_all = "kebab-case", deny_unknown_fields)]
+pub struct ShellSettings {
+    /// Options passed to fzf by the shell magic.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fzf_options: Option<String>,
+}
+
+impl ListSettings {
+    fn is_empty(&self) -> bool {
+        self.output.is_none()
+    }
+
+    /// Get the configured output format, if any.
+    pub fn output(&self) -> Result<Option<Output>, Error> {
+        self.output
+            .as_deref()
+            .map(|output| {
+                output
+                    .parse::<Output>()
+                    .map_err(|_| Error::Message(format!("invalid list.output setting: {output}")))
+            })
+            .transpose()
+    }
+}
+
+impl ImportSettings {
+    fn is_empty(&self) -> bool {
+        self.name_template.is_none()
+            && self.labels.is_empty()
+            && self.proxy_url.is_none()
+            && self.exec_credentials.is_none()
+    }
+
+    /// Get the configured name template, if any.
+    pub fn name_template(&self) -> Result<Option<NameTemplate>, Error> {
+        self.name_template
+            .as_deref()
+            .map(template::parse)
+            .transpose()
+            .map_err(|err| Error::Message(format!("invalid import.name-template setting: {err}")))
+    }
+
+    /// Get the configured default labels.
+    pub fn labels(&self) -> Result<Vec<labels::Label>, Error> {
+        self.labels
+            .iter()
+            .map(|(key, value)| {
+                labels::parse(&format!("{key}={value}"))
+                    .map_err(|err| Error::Message(format!("invalid import.labels setting: {err}")))
+            })
+            .collect()
+    }
+}
+
+impl PruneSettings {
+    fn is_empty(&self) -> bool {
+        self.timeout.is_none() && self.concurrency.is_none() && self.prune_on.is_none()
+    }
+
+    /// Get the configured timeout, if any.
+    pub fn timeout(&self) -> Result<Option<Duration>, Error> {
+        self.timeout
+            .as_deref()
+            .map(|timeout| {
+                humantime::parse_duration(timeout)
+                    .map_err(|err| Error::Message(format!("invalid prune.timeout setting: {err}")))
+            })
+            .transpose()
+    }
+
+    /// Get the configured failures to prune for, if any.
+    pub fn prune_on(&self) -> Result<Option<Vec<Failure>>, Error> {
+        self.prune_on
+            .as_ref()
+            .map(|failures| {
+                failures
+                    .iter()
+                    .map(|failure| {
+                        Failure::from_str(failure, false).map_err(|_| {
+                            Error::Message(format!("invalid prune.prune-on setting: {failure}"))
+                        })
+                    })
+                    .collect()
+            })
+            .transpose()
+    }
+}
+
+impl ShellSettings {
+    fn is_empty(&self) -> bool {
+        self.fzf_options.is_none()
+    }
+}
+
+impl Settings {
+    /// Load settings from the configuration directory. Returns default
+    /// settings if no settings file exists.
+    pub fn load(config_dir: &Path) -> Result<Settings, Error> {
+        let path = file_path(config_dir);
+
+        let content = match fs::read_to_string(&path) {
+            Ok(content) => content,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => {
+                log::debug!("no settings found at {}, using defaults", path.display());
+                return Ok(Settings::default());
+            }
+            Err(err) => return Err(Error::IO(err)),
+        };
+
+        log::debug!("loading settings from {}", path.display());
+        toml::from_str(&content)
+            .map_err(|err| Error::Message(format!("failed to parse {}: {err}", path.display())))
+    }
+
+    pub fn write(&self, config_dir: &Path) -> Result<(), Error> {
+        store::write(&file_path(config_dir), self.to_string().as_bytes())
+    }
+
+    /// Set a single setting, identified by its key (e.g. `list.output`).
+    /// The value is validated before it is set.
+    pub fn set(&mut self, key: &str, value: &str) -> Result<(), Error> {
+        let value = value.to_string();
+
+        match key {
+            "list.output" => {
+                self.list.output = Some(value);
+                self.list.output()?;
+            }
+            "import.name-template" => {
+                self.import.name_template = Some(value);
+                self.import.name_template()?;
+            }
+            "import.labels" => {
+                self.import.labels = labels::to_map(
+                    &value
+                        .split(',')
+                        .filter(|label| !label.is_empty())
+                        .map(labels::parse)
+                        .collect::<Result<Vec<labels::Label>, Error>>()?,
+                );
+            }
+            "import.proxy-url" => self.import.proxy_url = Some(value),
+            "import.exec-credentials" => {
+                self.import.exec_credentials = Some(value.parse::<bool>().map_err(|_| {
+                    Error::Message(format!(
+                        "invalid import.exec-credentials setting: {value} is not a boolean"
+                    ))
+                })?);
+            }
+            "prune.timeout" => {
+                self.prune.timeout = Some(value);
+                self.prune.timeout()?;
+            }
+            "prune.concurrency" => {
+                self.prune.concurrency = Some(
+                    value
+                        .parse::<usize>()
+                        .ok()
+                        .filter(|concurrency| *concurrency > 0)
+                        .ok_or_else(|| {
+                            Error::Message(format!(
+                                "invalid prune.concurrency setting: {value} is not a positive number"
+                            ))
+                        })?,
+                );
+            }
+            "prune.prune-on" => {
+                self.prune.prune_on = Some(
+                    value
+                        .split(',')
+                        .filter(|failure| !failure.is_empty())
+                        .map(str::to_string)
+                        .collect(),
+                );
+                self.prune.prune_on()?;
+            }
+            "shell.fzf-options" => self.shell.fzf_options = Some(value),
+            _ => {
+                return Err(Error::Message(format!(
+                    "unknown setting {key}, supported settings are: {}",
+                    KEYS.join(", ")
+                )))
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl std::fmt::Display for Settings {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{}", toml::to_string(self).map_err(|_| std::fmt::Error)?)
+    }
+}
+
+pub fn file_path(config_dir: &Path) -> PathBuf {
+    config_dir.join(SETTINGS_FILE_NAME)
+}
+
+/// Get the value of an argument, falling back to the setting if the argument
+/// was neither passed on the command line nor set via environment variable.
+/// This gives precedence of flag > environment variable > settings > default.
+pub fn resolve<T>(matches: &ArgMatches, id: &str, setting: Option<T>) -> Option<T>
+where
+    T: Clone + Send + Sync + 'static,
+{
+    match matches.value_source(id) {
+        Some(ValueSource::CommandLine) | Some(ValueSource::EnvVariable) => {
+            matches.get_one::<T>(id).cloned()
+        }
+        _ => setting.or_else(|| matches.get_one::<T>(id).cloned()),
+    }
+}
+
+/// Like [`resolve`], for arguments that take multiple values.
+pub fn resolve_many<T>(matches: &ArgMatches, id: &str, setting: Option<Vec<T>>) -> Vec<T>
+where
+    T: Clone + Send + Sync + 'static,
+{
+    let values = || {
+        matches
+            .get_many::<T>(id)
+            .map(|values| values.cloned().collect())
+            .unwrap_or_default()
+    };
+
+    match matches.value_source(id) {
+        Some(ValueSource::CommandLine) | Some(ValueSource::EnvVariable) => values(),
+        _ => setting.unwrap_or_else(values),
+    }
+}
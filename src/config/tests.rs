@@ -7,3 +7,36 @@ fn test_get_config_dir_with_xdg_config_home() -> Result<()> {
     assert_eq!("/xdg/config/home/kbs", get_config_dir()?.to_str().unwrap());
     Ok(())
 }
+
+#[test]
+fn test_parse_output() -> Result<()> {
+    assert_eq!(Output::Wide, "wide".parse::<Output>()?);
+    assert_eq!(
+        Output::CustomColumns(vec![
+            Column {
+                header: "NAME".to_string(),
+                path: vec!["name".to_string()],
+            },
+            Column {
+                header: "APP".to_string(),
+                path: vec!["labels".to_string(), "app.kubernetes.io/name".to_string()],
+            },
+        ]),
+        r"custom-columns=NAME:.name,APP:.labels.app\.kubernetes\.io/name".parse::<Output>()?
+    );
+
+    for output in [
+        "unknown",
+        "custom-columns=",
+        "custom-columns=NAME",
+        "custom-columns=NAME:name",
+        "custom-columns=:.name",
+    ] {
+        assert!(
+            output.parse::<Output>().is_err(),
+            "{output} should be invalid"
+        );
+    }
+
+    Ok(())
+}
@@ -9,7 +9,11 @@ pub enum Error {
     IO(std::io::Error),
     YAML(serde_yaml::Error),
     JSON(serde_json::Error),
+    TOML(toml::de::Error),
     URLParse(url::ParseError),
+    HTTP(Box<ureq::Error>),
+    Kube(Box<kube::Error>),
+    Git(git2::Error),
 }
 
 impl std::error::Error for Error {}
@@ -21,7 +25,11 @@ impl std::fmt::Display for Error {
             Error::IO(err) => write!(f, "IO error encountered: {err}"),
             Error::YAML(err) => write!(f, "YAML (de-)serialize error: {err}"),
             Error::JSON(err) => write!(f, "JSON (de-)serialize error: {err}"),
+            Error::TOML(err) => write!(f, "TOML deserialize error: {err}"),
             Error::URLParse(err) => write!(f, "failed to parse URL: {err}"),
+            Error::HTTP(err) => write!(f, "HTTP request failed: {err}"),
+            Error::Kube(err) => write!(f, "Kubernetes API error: {err}"),
+            Error::Git(err) => write!(f, "git error: {err}"),
         }
     }
 }
@@ -44,8 +52,32 @@ impl From<serde_json::Error> for Error {
     }
 }
 
+impl From<toml::de::Error> for Error {
+    fn from(err: toml::de::Error) -> Error {
+        Error::TOML(err)
+    }
+}
+
 impl From<url::ParseError> for Error {
     fn from(err: url::ParseError) -> Error {
         Error::URLParse(err)
     }
 }
+
+impl From<ureq::Error> for Error {
+    fn from(err: ureq::Error) -> Error {
+        Error::HTTP(Box::new(err))
+    }
+}
+
+impl From<kube::Error> for Error {
+    fn from(err: kube::Error) -> Error {
+        Error::Kube(Box::new(err))
+    }
+}
+
+impl From<git2::Error> for Error {
+    fn from(err: git2::Error) -> Error {
+        Error::Git(err)
+    }
+}
@@ -0,0 +1,16 @@
+use crate::Error;
+use std::process::Command;
+
+pub fn fetch(command: &str) -> Result<Vec<u8>, Error> {
+    let output = Command::new("sh").arg("-c").arg(command).output()?;
+
+    if !output.status.success() {
+        return Err(Error::Message(format!(
+            "command `{command}` failed ({}): {}",
+            output.status,
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+
+    Ok(output.stdout)
+}
@@ -0,0 +1,12 @@
+use crate::Error;
+use std::io::Read;
+use url::Url;
+
+pub fn fetch(url: &Url) -> Result<Vec<u8>, Error> {
+    let response = ureq::get(url.as_str()).call()?;
+
+    let mut buf: Vec<u8> = vec![];
+    response.into_reader().read_to_end(&mut buf)?;
+
+    Ok(buf)
+}
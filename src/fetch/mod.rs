@@ -0,0 +1,127 @@
+use crate::Error;
+use serde::{Deserialize, Serialize};
+use std::{
+    fs,
+    io::{stdin, Read},
+    path::{Path, PathBuf},
+};
+use url::Url;
+
+mod command;
+mod http;
+pub mod secret;
+mod ssh;
+
+const COMMAND_PREFIX: &str = "cmd:";
+
+/// A location that a kubeconfig can be (re-)fetched from.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum Source {
+    /// Read the kubeconfig from a local file.
+    File(PathBuf),
+    /// Read the kubeconfig from stdin. This source cannot be fetched again.
+    Stdin,
+    /// Download the kubeconfig via a HTTP(S) GET request.
+    Http(Url),
+    /// Read the kubeconfig from a remote system via the local `ssh` client.
+    Ssh(ssh::Location),
+    /// Run a local command and read the kubeconfig from its stdout.
+    Command(String),
+    /// Read the kubeconfig from a Kubernetes Secret.
+    Secret(secret::Reference),
+}
+
+impl Source {
+    /// Retrieve the raw kubeconfig from the source.
+    pub fn fetch(&self) -> Result<Vec<u8>, Error> {
+        log::debug!("fetching kubeconfig from {self}");
+
+        match self {
+            Source::File(path) => Ok(fs::read(path)?),
+            Source::Stdin => {
+                let mut buf: Vec<u8> = vec![];
+                stdin().lock().read_to_end(&mut buf)?;
+                Ok(buf)
+            }
+            Source::Http(url) => http::fetch(url),
+            Source::Ssh(location) => ssh::fetch(location),
+            Source::Command(command) => command::fetch(command),
+            Source::Secret(reference) => secret::fetch(reference),
+        }
+    }
+}
+
+impl std::fmt::Display for Source {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Source::File(path) => write!(f, "{}", path.display()),
+            Source::Stdin => write!(f, "stdin"),
+            Source::Http(url) => write!(f, "{url}"),
+            Source::Ssh(location) => write!(f, "{location}"),
+            Source::Command(command) => write!(f, "{COMMAND_PREFIX}{command}"),
+            Source::Secret(reference) => write!(f, "{reference}"),
+        }
+    }
+}
+
+/// Get the source for a local path, with '-' referring to stdin.
+pub fn from_path(path: &Path) -> Source {
+    if path.to_str().is_some_and(|x| x == "-") {
+        return Source::Stdin;
+    }
+
+    // record absolute paths so the source stays valid independent
+    // of the working directory.
+    Source::File(fs::canonicalize(path).unwrap_or(path.to_path_buf()))
+}
+
+/// Parse a source from string. Supported are http(s):// and ssh:// (or sftp://)
+/// URLs as well as `cmd:<command>` for local commands.
+pub fn parse(s: &str) -> Result<Source, Error> {
+    if let Some(command) = s.strip_prefix(COMMAND_PREFIX) {
+        if command.trim().is_empty() {
+            return Err(Error::Message(format!("no command found in `{s}`")));
+        }
+
+        return Ok(Source::Command(command.to_string()));
+    }
+
+    let url = Url::parse(s)?;
+
+    match url.scheme() {
+        "http" | "https" => Ok(Source::Http(url)),
+        "ssh" | "sftp" => Ok(Source::Ssh(ssh::Location::try_from(&url)?)),
+        scheme => Err(Error::Message(format!(
+            "unsupported scheme `{scheme}` in source `{s}`"
+        ))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        assert!(matches!(
+            parse("https://example.com/kubeconfig"),
+            Ok(Source::Http(_))
+        ));
+        assert!(matches!(
+            parse("ssh://root@bastion:2222/etc/kubernetes/admin.conf"),
+            Ok(Source::Ssh(_))
+        ));
+        assert_eq!(
+            parse("cmd:cat admin.conf").unwrap(),
+            Source::Command("cat admin.conf".to_string())
+        );
+    }
+
+    #[test]
+    fn test_parse_invalid() {
+        for source in &["ftp://example.com/kubeconfig", "cmd:", "/tmp/kubeconfig"] {
+            assert!(parse(source).is_err(), "{source} should not be valid");
+        }
+    }
+}
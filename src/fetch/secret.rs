@@ -0,0 +1,213 @@
+use crate::Error;
+use futures::executor;
+use k8s_openapi::api::core::v1::Secret;
+use kube::{api::ListParams, Api, Client};
+use serde::{Deserialize, Serialize};
+
+/// Key in the Secret data that holds the kubeconfig, following the
+/// Cluster API convention for `<cluster>-kubeconfig` Secrets.
+pub const DEFAULT_KEY: &str = "value";
+
+/// Reference to a kubeconfig stored in a Kubernetes Secret.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Reference {
+    pub namespace: String,
+    pub name: String,
+    pub key: String,
+}
+
+impl std::fmt::Display for Reference {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(
+            f,
+            "secret {}/{} (key '{}')",
+            self.namespace, self.name, self.key
+        )
+    }
+}
+
+/// Parse a Secret reference in the form of namespace/name.
+pub fn parse(s: &str) -> Result<Reference, Error> {
+    match s.split_once('/') {
+        Some((namespace, name))
+            if !namespace.is_empty() && !name.is_empty() && !name.contains('/') =>
+        {
+            Ok(Reference {
+                namespace: namespace.to_string(),
+                name: name.to_string(),
+                key: DEFAULT_KEY.to_string(),
+            })
+        }
+        _ => Err(Error::Message(format!(
+            "invalid secret reference `{s}`, expected <namespace>/<name>"
+        ))),
+    }
+}
+
+/// Read the kubeconfig from a Secret, using the cluster configured
+/// in the current environment (e.g. via KUBECONFIG).
+pub fn fetch(reference: &Reference) -> Result<Vec<u8>, Error> {
+    executor::block_on(async {
+        let client = Client::try_default().await?;
+        get(client, reference).await
+    })
+}
+
+pub async fn get(client: Client, reference: &Reference) -> Result<Vec<u8>, Error> {
+    let secrets: Api<Secret> = Api::namespaced(client, &reference.namespace);
+    let secret = secrets.get(&reference.name).await?;
+
+    data(&secret, &reference.key).ok_or_else(|| {
+        Error::Message(format!(
+            "{reference} does not contain key '{}'",
+            reference.key
+        ))
+    })
+}
+
+/// List all Secrets matching a label selector that contain a kubeconfig
+/// under the given key. Secrets without the key are skipped.
+pub async fn list(
+    client: Client,
+    namespace: Option<&str>,
+    selector: &str,
+    key: &str,
+) -> Result<Vec<(Reference, Vec<u8>)>, Error> {
+    let secrets: Api<Secret> = match namespace {
+        Some(namespace) => Api::namespaced(client, namespace),
+        None => Api::all(client),
+    };
+
+    let list = secrets
+        .list(&ListParams::default().labels(selector))
+        .await?;
+
+    let mut kubeconfigs = vec![];
+    for secret in list.items.iter() {
+        let reference = Reference {
+            namespace: secret.metadata.namespace.clone().unwrap_or_default(),
+            name: secret.metadata.name.clone().unwrap_or_default(),
+            key: key.to_string(),
+        };
+
+        match data(secret, key) {
+            Some(data) => kubeconfigs.push((reference, data)),
+            None => log::debug!("skipping {reference} as it does not contain key '{key}'"),
+        }
+    }
+
+    Ok(kubeconfigs)
+}
+
+fn data(secret: &Secret, key: &str) -> Option<Vec<u8>> {
+    secret
+        .data
+        .as_ref()
+        .and_then(|data| data.get(key))
+        .map(|value| value.0.clone())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use http::{Request, Response};
+    use k8s_openapi::ByteString;
+    use kube::client::Body;
+    use std::collections::BTreeMap;
+
+    fn secret(namespace: &str, name: &str, key: &str) -> Secret {
+        let mut secret = Secret::default();
+        secret.metadata.namespace = Some(namespace.to_string());
+        secret.metadata.name = Some(name.to_string());
+        secret.data = Some(BTreeMap::from([(
+            key.to_string(),
+            ByteString(b"apiVersion: v1".to_vec()),
+        )]));
+        secret
+    }
+
+    fn mock_client(
+        expected_uri: &'static str,
+        response: serde_json::Value,
+    ) -> (Client, tokio::task::JoinHandle<()>) {
+        let (service, mut handle) = tower_test::mock::pair::<Request<Body>, Response<Body>>();
+
+        let server = tokio::spawn(async move {
+            let (request, send) = handle.next_request().await.expect("no request received");
+            assert_eq!(request.uri().to_string(), expected_uri);
+            send.send_response(
+                Response::builder()
+                    .body(Body::from(serde_json::to_vec(&response).unwrap()))
+                    .unwrap(),
+            );
+        });
+
+        (Client::new(service, "default"), server)
+    }
+
+    #[test]
+    fn test_parse() {
+        let reference = parse("capi-system/prod-kubeconfig").unwrap();
+        assert_eq!(reference.namespace, "capi-system");
+        assert_eq!(reference.name, "prod-kubeconfig");
+        assert_eq!(reference.key, DEFAULT_KEY);
+
+        for s in &["prod-kubeconfig", "/prod-kubeconfig", "ns/", "a/b/c"] {
+            assert!(parse(s).is_err(), "{s} should not be valid");
+        }
+    }
+
+    #[tokio::test]
+    async fn test_get() {
+        let (client, server) = mock_client(
+            "/api/v1/namespaces/capi-system/secrets/prod-kubeconfig",
+            serde_json::to_value(secret("capi-system", "prod-kubeconfig", "value")).unwrap(),
+        );
+
+        let data = get(client, &parse("capi-system/prod-kubeconfig").unwrap())
+            .await
+            .unwrap();
+        assert_eq!(data, b"apiVersion: v1");
+
+        server.await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_get_missing_key() {
+        let (client, server) = mock_client(
+            "/api/v1/namespaces/capi-system/secrets/prod-kubeconfig",
+            serde_json::to_value(secret("capi-system", "prod-kubeconfig", "other")).unwrap(),
+        );
+
+        assert!(get(client, &parse("capi-system/prod-kubeconfig").unwrap())
+            .await
+            .is_err());
+
+        server.await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_list() {
+        let (client, server) = mock_client(
+            "/api/v1/secrets?&labelSelector=cluster.x-k8s.io%2Fcluster-name",
+            serde_json::json!({
+                "apiVersion": "v1",
+                "kind": "SecretList",
+                "metadata": {},
+                "items": [
+                    secret("ns-a", "a-kubeconfig", "value"),
+                    secret("ns-b", "b-kubeconfig", "other"),
+                ],
+            }),
+        );
+
+        let kubeconfigs = list(client, None, "cluster.x-k8s.io/cluster-name", DEFAULT_KEY)
+            .await
+            .unwrap();
+
+        assert_eq!(kubeconfigs.len(), 1);
+        assert_eq!(kubeconfigs[0].0, parse("ns-a/a-kubeconfig").unwrap());
+
+        server.await.unwrap();
+    }
+}
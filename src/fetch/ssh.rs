@@ -0,0 +1,134 @@
+use crate::Error;
+use serde::{Deserialize, Serialize};
+use std::process::Command;
+use url::Url;
+
+/// A file on a remote system reachable via SSH.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Location {
+    pub user: Option<String>,
+    pub host: String,
+    pub port: Option<u16>,
+    pub path: String,
+}
+
+impl TryFrom<&Url> for Location {
+    type Error = Error;
+
+    fn try_from(url: &Url) -> Result<Self, Self::Error> {
+        let host = url
+            .host_str()
+            .ok_or_else(|| Error::Message(format!("no host found in `{url}`")))?;
+
+        // paths relative to the remote home directory are passed as
+        // ssh://host/~/path, so we need to drop the leading slash.
+        let path = match url.path().strip_prefix("/~") {
+            Some(path) => format!("~{path}"),
+            None => url.path().to_string(),
+        };
+
+        if path.is_empty() || path == "/" {
+            return Err(Error::Message(format!("no file path found in `{url}`")));
+        }
+
+        Ok(Location {
+            user: match url.username() {
+                "" => None,
+                user => Some(user.to_string()),
+            },
+            host: host.to_string(),
+            port: url.port(),
+            path,
+        })
+    }
+}
+
+impl std::fmt::Display for Location {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "ssh://")?;
+        if let Some(user) = &self.user {
+            write!(f, "{user}@")?;
+        }
+        write!(f, "{}", self.host)?;
+        if let Some(port) = self.port {
+            write!(f, ":{port}")?;
+        }
+
+        match self.path.starts_with('~') {
+            true => write!(f, "/{}", self.path),
+            false => write!(f, "{}", self.path),
+        }
+    }
+}
+
+pub fn fetch(location: &Location) -> Result<Vec<u8>, Error> {
+    let mut command = Command::new("ssh");
+    command.args(["-o", "BatchMode=yes"]);
+
+    if let Some(port) = location.port {
+        command.args(["-p", &port.to_string()]);
+    }
+
+    let destination = match &location.user {
+        Some(user) => format!("{user}@{}", location.host),
+        None => location.host.clone(),
+    };
+
+    command
+        .arg(destination)
+        .arg("--")
+        .arg(format!("cat {}", quote_path(&location.path)));
+
+    let output = command.output()?;
+
+    if !output.status.success() {
+        return Err(Error::Message(format!(
+            "failed to read {} via ssh ({}): {}",
+            location,
+            output.status,
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+
+    Ok(output.stdout)
+}
+
+// Quote a path for the remote shell, leaving a leading '~/' unquoted so it
+// still expands to the remote home directory.
+fn quote_path(path: &str) -> String {
+    let (prefix, path) = match path.strip_prefix("~/") {
+        Some(rest) => ("~/", rest),
+        None => ("", path),
+    };
+
+    format!("{prefix}'{}'", path.replace('\'', r"'\''"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_location_from_url() {
+        let url = Url::parse("sftp://admin@bastion.example.com:2222/~/.kube/config").unwrap();
+        let location = Location::try_from(&url).unwrap();
+
+        assert_eq!(location.user, Some("admin".to_string()));
+        assert_eq!(location.host, "bastion.example.com");
+        assert_eq!(location.port, Some(2222));
+        assert_eq!(location.path, "~/.kube/config");
+        assert_eq!(
+            location.to_string(),
+            "ssh://admin@bastion.example.com:2222/~/.kube/config"
+        );
+    }
+
+    #[test]
+    fn test_quote_path() {
+        assert_eq!(
+            quote_path("/etc/kubernetes/admin.conf"),
+            "'/etc/kubernetes/admin.conf'"
+        );
+        assert_eq!(quote_path("~/it's here"), r"~/'it'\''s here'");
+    }
+}
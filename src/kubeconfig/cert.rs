@@ -0,0 +1,231 @@
+use crate::metadata::{Expiry, Timestamp};
+use crate::Error;
+use base64::{engine::general_purpose::STANDARD, Engine};
+use kube::config::Kubeconfig;
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::time::{Duration, UNIX_EPOCH};
+use x509_parser::{certificate::X509Certificate, pem::Pem};
+
+/// Client certificate of a user in a kubeconfig.
+pub struct ClientCert {
+    pub subject: String,
+    pub not_after: Timestamp,
+}
+
+/// Get the earliest expiry (notAfter) of all client certificates and of all
+/// certificate authorities in a kubeconfig, as well as the earliest expiry
+/// of all tokens. Certificates that cannot be
+/// parsed are skipped with a warning, they should not prevent working
+/// with a kubeconfig.
+pub fn get_expiry(kubeconfig: &Kubeconfig) -> Expiry {
+    let client_cert = get_client_certs(kubeconfig)
+        .map(|certs| certs.iter().map(|cert| cert.not_after).min())
+        .unwrap_or_else(|err| {
+            log::warn!("failed to read client certificate expiry: {err}");
+            None
+        });
+
+    let ca = get_ca_expiry(kubeconfig).unwrap_or_else(|err| {
+        log::warn!("failed to read certificate authority expiry: {err}");
+        None
+    });
+
+    Expiry {
+        client_cert,
+        ca,
+        token: super::get_token_expiry(kubeconfig),
+    }
+}
+
+/// Get the client certificates of all users in a kubeconfig.
+pub fn get_client_certs(kubeconfig: &Kubeconfig) -> Result<Vec<ClientCert>, Error> {
+    let mut certs = vec![];
+
+    for auth_info in kubeconfig
+        .auth_infos
+        .iter()
+        .filter_map(|auth_info| auth_info.auth_info.as_ref())
+    {
+        let Some(pem) = read_pem(
+            &auth_info.client_certificate_data,
+            &auth_info.client_certificate,
+        )?
+        else {
+            continue;
+        };
+
+        let cert = pem
+            .parse_x509()
+            .map_err(|err| Error::Message(format!("failed to parse client certificate: {err}")))?;
+
+        certs.push(ClientCert {
+            subject: cert.subject().to_string(),
+            not_after: not_after(&cert),
+        });
+    }
+
+    Ok(certs)
+}
+
+fn get_ca_expiry(kubeconfig: &Kubeconfig) -> Result<Option<Timestamp>, Error> {
+    let mut expiry: Option<Timestamp> = None;
+
+    for cluster in kubeconfig
+        .clusters
+        .iter()
+        .filter_map(|cluster| cluster.cluster.as_ref())
+    {
+        let Some(pem) = read_pem(
+            &cluster.certificate_authority_data,
+            &cluster.certificate_authority,
+        )?
+        else {
+            continue;
+        };
+
+        let cert = pem.parse_x509().map_err(|err| {
+            Error::Message(format!("failed to parse certificate authority: {err}"))
+        })?;
+
+        let not_after = not_after(&cert);
+        expiry = Some(expiry.map_or(not_after, |expiry| expiry.min(not_after)));
+    }
+
+    Ok(expiry)
+}
+
+fn not_after(cert: &X509Certificate) -> Timestamp {
+    let seconds = cert.validity().not_after.timestamp();
+    Timestamp(UNIX_EPOCH + Duration::from_secs(seconds.try_into().unwrap_or_default()))
+}
+
+/// Get the SHA-256 fingerprints of the certificate authorities of all
+/// clusters in a kubeconfig, formatted like `openssl x509 -fingerprint`.
+pub fn get_ca_fingerprints(kubeconfig: &Kubeconfig) -> Result<Vec<String>, Error> {
+    let mut fingerprints = vec![];
+
+    for cluster in kubeconfig
+        .clusters
+        .iter()
+        .filter_map(|cluster| cluster.cluster.as_ref())
+    {
+        let Some(pem) = read_pem(
+            &cluster.certificate_authority_data,
+            &cluster.certificate_authority,
+        )?
+        else {
+            continue;
+        };
+
+        fingerprints.push(
+            Sha256::digest(&pem.contents)
+                .iter()
+                .map(|byte| format!("{byte:02X}"))
+                .collect::<Vec<String>>()
+                .join(":"),
+        );
+    }
+
+    Ok(fingerprints)
+}
+
+// Read the first certificate from base64-encoded PEM data or a PEM file,
+// preferring the data. For client certificates, this is the certificate
+// itself (potentially followed by intermediate certificates). Empty data
+// is treated as not set.
+fn read_pem(data: &Option<String>, file: &Option<String>) -> Result<Option<Pem>, Error> {
+    let pem = match (data, file) {
+        (Some(data), _) => STANDARD
+            .decode(data.trim())
+            .map_err(|err| Error::Message(format!("failed to decode certificate data: {err}")))?,
+        (None, Some(file)) => fs::read(file)?,
+        (None, None) => return Ok(None),
+    };
+
+    if pem.is_empty() {
+        return Ok(None);
+    }
+
+    let pem = Pem::iter_from_buffer(&pem)
+        .next()
+        .ok_or_else(|| Error::Message("no certificate found in PEM data".to_string()))?
+        .map_err(|err| Error::Message(format!("failed to read PEM data: {err}")))?;
+
+    Ok(Some(pem))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_get_expiry() {
+        let mut kubeconfig: Kubeconfig =
+            serde_yaml::from_str(include_str!("../../tests/files/test.kubeconfig")).unwrap();
+        let expiry = get_expiry(&kubeconfig);
+        assert_eq!(
+            "2024-01-27T21:31:24Z",
+            expiry.client_cert.unwrap().to_string()
+        );
+        assert!(expiry.ca.is_none());
+
+        // the client certificate is self-signed, so it can act as CA too.
+        let cert = kubeconfig.auth_infos[0]
+            .auth_info
+            .as_ref()
+            .unwrap()
+            .client_certificate_data
+            .clone();
+        kubeconfig.clusters[0]
+            .cluster
+            .as_mut()
+            .unwrap()
+            .certificate_authority_data = cert;
+        assert_eq!(
+            "2024-01-27T21:31:24Z",
+            get_expiry(&kubeconfig).ca.unwrap().to_string()
+        );
+
+        // empty certificate data is ignored.
+        let kubeconfig: Kubeconfig =
+            serde_yaml::from_str(include_str!("../../tests/files/localhost.kubeconfig")).unwrap();
+        assert_eq!(Expiry::default(), get_expiry(&kubeconfig));
+    }
+
+    #[test]
+    fn test_get_client_certs() {
+        let kubeconfig: Kubeconfig =
+            serde_yaml::from_str(include_str!("../../tests/files/test.kubeconfig")).unwrap();
+        let certs = get_client_certs(&kubeconfig).unwrap();
+        assert_eq!(1, certs.len());
+        assert!(certs[0].subject.contains("CN=CommonNameOrHostname"));
+        assert!(get_ca_fingerprints(&kubeconfig).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_get_ca_fingerprints() {
+        let mut kubeconfig: Kubeconfig =
+            serde_yaml::from_str(include_str!("../../tests/files/test.kubeconfig")).unwrap();
+
+        // the client certificate is self-signed, so it can act as CA too.
+        let cert = kubeconfig.auth_infos[0]
+            .auth_info
+            .as_ref()
+            .unwrap()
+            .client_certificate_data
+            .clone();
+        kubeconfig.clusters[0]
+            .cluster
+            .as_mut()
+            .unwrap()
+            .certificate_authority_data = cert;
+
+        let fingerprints = get_ca_fingerprints(&kubeconfig).unwrap();
+        assert_eq!(1, fingerprints.len());
+        assert_eq!(32 * 3 - 1, fingerprints[0].len());
+        assert!(fingerprints[0]
+            .split(':')
+            .all(|byte| byte.len() == 2 && byte.chars().all(|c| c.is_ascii_hexdigit())));
+    }
+}
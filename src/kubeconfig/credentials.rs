@@ -0,0 +1,284 @@
+use crate::{store, Error};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use kube::config::{ExecConfig, ExecInteractiveMode, Kubeconfig};
+use secrecy::{ExposeSecret, SecretString};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::os::unix::fs::PermissionsExt;
+use std::path::{Path, PathBuf};
+use std::{fs, io};
+
+/// Directory in the data store holding credentials moved out of kubeconfigs.
+const DIR: &str = "credentials";
+
+const EXEC_API_VERSION: &str = "client.authentication.k8s.io/v1";
+
+/// Credentials of a user, in the format of the `status` of an
+/// `ExecCredential`. Client certificate and key are PEM-encoded.
+#[derive(Debug, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct Credential {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub token: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub client_certificate_data: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub client_key_data: Option<String>,
+}
+
+/// Move the credentials of all users out of a kubeconfig into the
+/// credential store and replace them with an exec stanza that calls
+/// `kbs credential`, so the kubeconfig itself holds no secrets. Users
+/// that authenticate with exec plugins, auth providers or basic auth are
+/// kept as they are, as exec plugins cannot return passwords.
+pub fn extract(
+    config_dir: &Path,
+    kubeconfig: &Kubeconfig,
+    name: &str,
+) -> Result<Kubeconfig, Error> {
+    let mut kubeconfig = kubeconfig.clone();
+    let mut credentials: BTreeMap<String, Credential> = BTreeMap::new();
+
+    for named in kubeconfig.auth_infos.iter_mut() {
+        let Some(auth_info) = named.auth_info.as_mut() else {
+            continue;
+        };
+
+        if auth_info.password.is_some() {
+            log::warn!(
+                "user '{}' authenticates with a password, which cannot be moved to the credential store and stays in the kubeconfig",
+                named.name
+            );
+        }
+
+        if auth_info.exec.is_some() || auth_info.auth_provider.is_some() {
+            continue;
+        }
+
+        let credential = Credential {
+            token: auth_info
+                .token
+                .as_ref()
+                .map(|token| token.expose_secret().to_string()),
+            client_certificate_data: decode(&auth_info.client_certificate_data)?,
+            client_key_data: decode(
+                &auth_info
+                    .client_key_data
+                    .as_ref()
+                    .map(|key| key.expose_secret().to_string()),
+            )?,
+        };
+
+        // kubectl requires both client certificate and key from exec plugins.
+        if credential.client_certificate_data.is_some() != credential.client_key_data.is_some() {
+            log::warn!(
+                "user '{}' has a client certificate or key, but not both, keeping its credentials",
+                named.name
+            );
+            continue;
+        }
+
+        if credential == Credential::default() {
+            continue;
+        }
+
+        auth_info.token = None;
+        auth_info.client_certificate_data = None;
+        auth_info.client_key_data = None;
+        auth_info.exec = Some(exec_config(config_dir, name, &named.name)?);
+
+        credentials.insert(named.name.clone(), credential);
+    }
+
+    if credentials.is_empty() {
+        log::warn!("no credentials found in kubeconfig {name} to move to the credential store");
+        return Ok(kubeconfig);
+    }
+
+    create_dir(config_dir)?;
+    store::write_private(
+        config_dir,
+        &path(config_dir, name),
+        &serde_json::to_vec(&credentials)?,
+    )?;
+
+    Ok(kubeconfig)
+}
+
+/// Put the credentials of all users calling `kbs credential` back into a
+/// kubeconfig, e.g. to pass it on to another data store.
+pub fn inline(config_dir: &Path, kubeconfig: &Kubeconfig, name: &str) -> Result<Kubeconfig, Error> {
+    let mut kubeconfig = kubeconfig.clone();
+
+    if !exists(config_dir, name) {
+        return Ok(kubeconfig);
+    }
+
+    for named in kubeconfig.auth_infos.iter_mut() {
+        let Some(auth_info) = named.auth_info.as_mut() else {
+            continue;
+        };
+
+        if !auth_info
+            .exec
+            .as_ref()
+            .is_some_and(|exec| calls_credential(exec, name))
+        {
+            continue;
+        }
+
+        let credential = get(config_dir, name, &named.name)?;
+
+        auth_info.exec = None;
+        auth_info.token = credential.token.map(SecretString::new);
+        auth_info.client_certificate_data = credential
+            .client_certificate_data
+            .map(|pem| STANDARD.encode(pem));
+        auth_info.client_key_data = credential
+            .client_key_data
+            .map(|pem| SecretString::new(STANDARD.encode(pem)));
+    }
+
+    Ok(kubeconfig)
+}
+
+/// Get the credentials of a user of a kubeconfig from the credential store.
+pub fn get(config_dir: &Path, name: &str, user: &str) -> Result<Credential, Error> {
+    let data =
+        store::read_private(config_dir, &path(config_dir, name)).map_err(|err| match err {
+            Error::IO(err) if err.kind() == io::ErrorKind::NotFound => {
+                Error::Message(format!("no credentials stored for kubeconfig {name}"))
+            }
+            err => err,
+        })?;
+
+    let mut credentials: BTreeMap<String, Credential> = serde_json::from_slice(&data)?;
+    credentials.remove(user).ok_or_else(|| {
+        Error::Message(format!(
+            "no credentials stored for user '{user}' of kubeconfig {name}"
+        ))
+    })
+}
+
+/// Get the paths of all files in the credential store.
+pub fn files(config_dir: &Path) -> Result<Vec<PathBuf>, Error> {
+    let mut files = match fs::read_dir(config_dir.join(DIR)) {
+        Ok(entries) => entries
+            .map(|entry| entry.map(|entry| entry.path()))
+            .collect::<Result<Vec<PathBuf>, io::Error>>()?,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => vec![],
+        Err(err) => return Err(Error::IO(err)),
+    };
+
+    files.retain(|file| {
+        file.extension()
+            .is_some_and(|extension| extension == "json")
+    });
+    files.sort();
+
+    Ok(files)
+}
+
+/// Check if credentials of a kubeconfig are in the credential store.
+pub fn exists(config_dir: &Path, name: &str) -> bool {
+    path(config_dir, name).is_file()
+}
+
+/// Remove the credentials of a kubeconfig from the credential store, if any.
+pub fn remove(config_dir: &Path, name: &str) -> Result<(), Error> {
+    match fs::remove_file(path(config_dir, name)) {
+        Err(err) if err.kind() != io::ErrorKind::NotFound => Err(Error::IO(err)),
+        _ => Ok(()),
+    }
+}
+
+/// Move the credentials of a kubeconfig that has been renamed from source to
+/// destination and update its exec stanzas accordingly.
+pub fn r#move(config_dir: &Path, source: &str, destination: &str) -> Result<(), Error> {
+    if !exists(config_dir, source) {
+        return Ok(());
+    }
+
+    fs::rename(path(config_dir, source), path(config_dir, destination))?;
+
+    let (kubeconfig_path, mut kubeconfig) = super::get(config_dir, destination)?;
+    for named in kubeconfig.auth_infos.iter_mut() {
+        let Some(auth_info) = named.auth_info.as_mut() else {
+            continue;
+        };
+
+        if auth_info
+            .exec
+            .as_ref()
+            .is_some_and(|exec| calls_credential(exec, source))
+        {
+            auth_info.exec = Some(exec_config(config_dir, destination, &named.name)?);
+        }
+    }
+
+    store::write_private(
+        config_dir,
+        &kubeconfig_path,
+        serde_yaml::to_string(&kubeconfig)?.as_bytes(),
+    )
+}
+
+pub(super) fn path(config_dir: &Path, name: &str) -> PathBuf {
+    config_dir.join(DIR).join(format!("{name}.json"))
+}
+
+pub(super) fn create_dir(config_dir: &Path) -> Result<(), Error> {
+    let dir = config_dir.join(DIR);
+    fs::create_dir_all(&dir)?;
+    fs::set_permissions(&dir, fs::Permissions::from_mode(0o700))?;
+
+    Ok(())
+}
+
+// The data store is passed explicitly, so the plugin works no matter which
+// environment kubectl is run in.
+fn exec_config(config_dir: &Path, name: &str, user: &str) -> Result<ExecConfig, Error> {
+    let config_dir = fs::canonicalize(config_dir)?;
+
+    Ok(ExecConfig {
+        api_version: Some(EXEC_API_VERSION.to_string()),
+        command: Some("kbs".to_string()),
+        args: Some(vec![
+            "--config-dir".to_string(),
+            config_dir.to_string_lossy().to_string(),
+            "credential".to_string(),
+            name.to_string(),
+            "--user".to_string(),
+            user.to_string(),
+        ]),
+        env: None,
+        drop_env: None,
+        // decrypting credentials might require a passphrase.
+        interactive_mode: Some(ExecInteractiveMode::IfAvailable),
+        provide_cluster_info: false,
+        cluster: None,
+    })
+}
+
+// Check if an exec stanza calls `kbs credential` for the given kubeconfig.
+fn calls_credential(exec: &ExecConfig, name: &str) -> bool {
+    exec.command.as_deref() == Some("kbs")
+        && exec.args.as_ref().is_some_and(|args| {
+            args.windows(2)
+                .any(|pair| pair[0] == "credential" && pair[1] == name)
+        })
+}
+
+// Decode base64-encoded data from a kubeconfig into a PEM string.
+fn decode(data: &Option<String>) -> Result<Option<String>, Error> {
+    data.as_ref()
+        .filter(|data| !data.trim().is_empty())
+        .map(|data| {
+            let pem = STANDARD.decode(data.trim()).map_err(|err| {
+                Error::Message(format!("failed to decode credential data: {err}"))
+            })?;
+            String::from_utf8(pem)
+                .map_err(|_| Error::Message("credential data is not PEM-encoded".to_string()))
+        })
+        .transpose()
+}
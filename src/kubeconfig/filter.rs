@@ -0,0 +1,219 @@
+use super::{get_auth, get_servers, Auth};
+use crate::Error;
+use kube::config::Kubeconfig;
+use regex::Regex;
+use std::collections::btree_map::BTreeMap;
+
+/// Filter on a field of the kubeconfig content, e.g. `server=~\.eks\.amazonaws\.com$`.
+///
+/// A kubeconfig can contain several clusters and users. Positive filters
+/// (`=`, `=~`) match if any value of the field matches, negative filters
+/// (`!=`, `!~`) match if no value of the field matches.
+#[derive(Clone, Debug)]
+pub struct FieldFilter {
+    field: Field,
+    matcher: Matcher,
+    negate: bool,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Field {
+    /// Server URLs of all clusters.
+    Server,
+    /// Names of all users.
+    User,
+    /// Authentication methods of all users.
+    Auth,
+    /// Names of all contexts.
+    Context,
+    /// Names of all clusters.
+    Cluster,
+}
+
+#[derive(Clone, Debug)]
+enum Matcher {
+    Equal(String),
+    Regex(Regex),
+}
+
+/// Parse a field filter from string. Supports `<field>=<value>` (or `==`),
+/// `<field>!=<value>`, `<field>=~<regex>` and `<field>!~<regex>`.
+pub fn parse(s: &str) -> Result<FieldFilter, Error> {
+    let invalid = |msg: String| Error::Message(format!("invalid field filter `{s}`: {msg}"));
+
+    let end = s
+        .find(|c: char| !c.is_ascii_alphabetic())
+        .unwrap_or(s.len());
+    let (name, rest) = s.split_at(end);
+
+    let field = match name {
+        "server" => Field::Server,
+        "user" => Field::User,
+        "auth" => Field::Auth,
+        "context" => Field::Context,
+        "cluster" => Field::Cluster,
+        "" => return Err(invalid("expected field".to_string())),
+        other => {
+            return Err(invalid(format!(
+            "unknown field `{other}`, supported fields are server, user, auth, context and cluster"
+        )))
+        }
+    };
+
+    let (operator, value) = ["=~", "!~", "!=", "==", "="]
+        .iter()
+        .find_map(|operator| rest.strip_prefix(operator).map(|value| (*operator, value)))
+        .ok_or_else(|| invalid("expected one of `=`, `==`, `!=`, `=~` or `!~`".to_string()))?;
+
+    let matcher = match operator {
+        "=~" | "!~" => Matcher::Regex(
+            Regex::new(value)
+                .map_err(|err| invalid(format!("invalid regular expression: {err}")))?,
+        ),
+        _ if field == Field::Auth && !Auth::ALL.iter().any(|auth| auth.to_string() == value) => {
+            return Err(invalid(format!(
+                "unknown auth method `{value}`, supported methods are {}",
+                Auth::ALL
+                    .iter()
+                    .map(|auth| auth.to_string())
+                    .collect::<Vec<String>>()
+                    .join(", ")
+            )))
+        }
+        _ => Matcher::Equal(value.to_string()),
+    };
+
+    Ok(FieldFilter {
+        field,
+        matcher,
+        negate: operator.starts_with('!'),
+    })
+}
+
+impl FieldFilter {
+    pub fn matches(&self, kubeconfig: &Kubeconfig) -> bool {
+        let matched = self
+            .field
+            .values(kubeconfig)
+            .iter()
+            .any(|value| match &self.matcher {
+                Matcher::Equal(expected) => value == expected,
+                Matcher::Regex(regex) => regex.is_match(value),
+            });
+
+        matched != self.negate
+    }
+}
+
+impl Field {
+    fn values(&self, kubeconfig: &Kubeconfig) -> Vec<String> {
+        match self {
+            Field::Server => get_servers(kubeconfig),
+            Field::User => kubeconfig
+                .auth_infos
+                .iter()
+                .map(|auth_info| auth_info.name.clone())
+                .collect(),
+            Field::Auth => get_auth(kubeconfig)
+                .iter()
+                .map(|auth| auth.to_string())
+                .collect(),
+            Field::Context => kubeconfig
+                .contexts
+                .iter()
+                .map(|context| context.name.clone())
+                .collect(),
+            Field::Cluster => kubeconfig
+                .clusters
+                .iter()
+                .map(|cluster| cluster.name.clone())
+                .collect(),
+        }
+    }
+}
+
+/// Check if text is contained (case-insensitive) in the name, the labels
+/// or the server URLs of a kubeconfig.
+pub fn search(
+    text: &str,
+    name: &str,
+    labels: Option<&BTreeMap<String, String>>,
+    kubeconfig: &Kubeconfig,
+) -> bool {
+    let text = text.to_lowercase();
+    let contains = |value: &str| value.to_lowercase().contains(&text);
+
+    contains(name)
+        || labels.is_some_and(|labels| {
+            labels
+                .iter()
+                .any(|(key, value)| contains(&format!("{key}={value}")))
+        })
+        || get_servers(kubeconfig)
+            .iter()
+            .any(|server| contains(server))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn kubeconfig() -> Kubeconfig {
+        serde_yaml::from_str(include_str!("../../tests/files/mixed.kubeconfig")).unwrap()
+    }
+
+    fn matches(filter: &str) -> bool {
+        parse(filter).unwrap().matches(&kubeconfig())
+    }
+
+    #[test]
+    fn test_parse() {
+        for filter in &[
+            "server=https://localhost:6443",
+            "server==https://localhost:6443",
+            "user!=admin",
+            r"server=~\.embik\.me(:\d+)?$",
+            "auth!~exec|token",
+            "auth=cert",
+        ] {
+            assert!(parse(filter).is_ok(), "{filter} should be valid");
+        }
+
+        for filter in &[
+            "",
+            "=admin",
+            "unknown=admin",
+            "user",
+            "user~admin",
+            "server=~(",
+            "auth=password",
+        ] {
+            assert!(parse(filter).is_err(), "{filter} should be invalid");
+        }
+    }
+
+    #[test]
+    fn test_matches() {
+        assert!(matches("server=https://localhost:6443"));
+        assert!(matches(r"server=~\.embik\.me:6443$"));
+        assert!(!matches("server!=https://localhost:6443"));
+        assert!(matches(r"server!~\.eks\.amazonaws\.com$"));
+        assert!(matches("user=kubernetes-admin"));
+        assert!(matches("auth=cert"));
+        assert!(!matches("auth=exec"));
+        assert!(matches("auth!~exec|token"));
+        assert!(matches("context=kubernetes-admin@localhost"));
+        assert!(matches("cluster=~^local"));
+    }
+
+    #[test]
+    fn test_search() {
+        let kubeconfig = kubeconfig();
+        let labels = BTreeMap::from([("env".to_string(), "prod".to_string())]);
+
+        assert!(search("mixed", "mixed", None, &kubeconfig));
+        assert!(search("ENV=PROD", "mixed", Some(&labels), &kubeconfig));
+        assert!(search("localhost:6443", "mixed", None, &kubeconfig));
+        assert!(!search("staging", "mixed", Some(&labels), &kubeconfig));
+    }
+}
@@ -1,84 +1,207 @@
-use kube::config::NamedCluster;
+use kube::config::{Kubeconfig, NamedCluster};
+
+use super::credentials;
+use super::template::{NameTemplate, Values};
+use crate::{kubeconfig, store, Error};
+use clap::builder::PossibleValue;
+use std::io::{self, BufRead, IsTerminal, Write};
+use std::path::Path;
+
+/// What to do when a kubeconfig with the same name already exists in the data store.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum OnConflict {
+    Error,
+    Overwrite,
+    Rename,
+    Skip,
+    Prompt,
+}
+
+impl clap::ValueEnum for OnConflict {
+    fn value_variants<'a>() -> &'a [Self] {
+        &[
+            OnConflict::Error,
+            OnConflict::Overwrite,
+            OnConflict::Rename,
+            OnConflict::Skip,
+            OnConflict::Prompt,
+        ]
+    }
+
+    fn to_possible_value<'a>(&self) -> Option<PossibleValue> {
+        Some(match self {
+            OnConflict::Error => PossibleValue::new("error"),
+            OnConflict::Overwrite => PossibleValue::new("overwrite"),
+            OnConflict::Rename => PossibleValue::new("rename"),
+            OnConflict::Skip => PossibleValue::new("skip"),
+            OnConflict::Prompt => PossibleValue::new("prompt"),
+        })
+    }
+}
+
+impl From<OnConflict> for clap::builder::OsStr {
+    fn from(value: OnConflict) -> clap::builder::OsStr {
+        match value {
+            OnConflict::Error => "error".into(),
+            OnConflict::Overwrite => "overwrite".into(),
+            OnConflict::Rename => "rename".into(),
+            OnConflict::Skip => "skip".into(),
+            OnConflict::Prompt => "prompt".into(),
+        }
+    }
+}
 
-use crate::{kubeconfig, Error};
-use std::fs::{self};
-use std::os::unix::fs::PermissionsExt;
-use std::{
-    fs::File,
-    io::{stdin, BufReader, BufWriter},
-    path::Path,
-};
+/// How to name an imported kubeconfig. An explicit name takes precedence
+/// over the name template.
+pub struct Naming<'a> {
+    pub name: Option<&'a String>,
+    pub template: &'a NameTemplate,
+    /// Path the kubeconfig was read from, if it was read from a file.
+    pub path: Option<&'a Path>,
+    pub use_short: bool,
+}
 
+/// Import a kubeconfig into the data store. Returns the name it was stored
+/// under, or `None` if it was skipped because of a name conflict.
 pub fn import(
     config_dir: &Path,
-    kubeconfig_path: &Path,
-    name: Option<&String>,
-    use_short: bool,
+    kubeconfig: &Kubeconfig,
+    naming: &Naming,
     proxy: Option<&String>,
-) -> Result<String, Error> {
-    log::debug!(
-        "trying to import {}",
-        kubeconfig_path.to_str().unwrap_or_default()
-    );
-
-    let mut kubeconfig = match kubeconfig_path.to_str().is_some_and(|x| x == "-") {
-        false => kubeconfig::get_from_file(kubeconfig_path)?,
-        true => {
-            let reader = BufReader::new(stdin().lock());
-            match serde_yaml::from_reader(reader) {
-                Ok(kubeconfig) => kubeconfig,
-                Err(err) => return Err(Error::YAML(err)),
-            }
+    exec_credentials: bool,
+    on_conflict: OnConflict,
+) -> Result<Option<String>, Error> {
+    // use the name passed via command line flag; if it's not set,
+    // render the name template (which defaults to the server hostname).
+    let name: String = match naming.name {
+        Some(str) => str.clone(),
+        None => {
+            log::debug!("no name passed via flag, rendering name template");
+            naming.template.render(&Values {
+                path: naming.path,
+                kubeconfig,
+                use_short: naming.use_short,
+            })?
         }
     };
 
-    if let Some(proxy_url) = proxy {
-        let clusters = kubeconfig
-            .clusters
-            .iter()
-            .map(|cluster| set_proxy(cluster, proxy_url))
-            .collect::<Vec<NamedCluster>>();
-
-        kubeconfig.clusters = clusters;
-    }
+    log::debug!("using {} as name for kubeconfig file and context", name);
 
-    // read the name from the command line flag; if it's not set,
-    // extract the hostname and use that as name.
-    let name: String = match name {
-        Some(str) => str.clone(),
-        None => {
-            log::debug!("no name passed via flag, reading it from kubeconfig server URL");
-            let host = kubeconfig::get_hostname(&kubeconfig)?;
+    let target_path = kubeconfig::get_path(config_dir, &name);
 
-            match use_short {
-                true => host.split_once('.').unwrap_or((&host, "")).0.to_string(),
-                false => host.to_string(),
+    let name = match target_path.exists() {
+        false => name,
+        true => {
+            let on_conflict = match on_conflict {
+                OnConflict::Prompt => prompt(&name)?,
+                on_conflict => on_conflict,
+            };
+
+            match on_conflict {
+                OnConflict::Overwrite => {
+                    log::debug!("overwriting existing kubeconfig {}", name);
+                    name
+                }
+                OnConflict::Rename => {
+                    let new_name = free_name(config_dir, &name);
+                    log::info!("kubeconfig {name} already exists, importing as {new_name}");
+                    new_name
+                }
+                OnConflict::Skip => {
+                    log::info!("kubeconfig {name} already exists, skipping");
+                    return Ok(None);
+                }
+                _ => {
+                    return Err(Error::Message(format!(
+                        "kubeconfig {} already exists at {}",
+                        name,
+                        target_path.display()
+                    )))
+                }
             }
         }
     };
 
-    log::debug!("using {} as name for kubeconfig file and context", name);
+    save(config_dir, kubeconfig, &name, proxy, exec_credentials)?;
 
-    let target_path = kubeconfig::get_path(config_dir, &name);
+    Ok(Some(name))
+}
 
-    // TODO: prompt the user for confirmation to override instead of
-    // throwing an error.
-    if target_path.exists() {
+/// Find the first name with a numeric suffix (name-1, name-2, ...) that is
+/// not taken in the data store yet.
+fn free_name(config_dir: &Path, name: &str) -> String {
+    (1..)
+        .map(|i| format!("{name}-{i}"))
+        .find(|candidate| !kubeconfig::get_path(config_dir, candidate).exists())
+        .unwrap()
+}
+
+/// Ask the user how to resolve a name conflict. Only possible if both stdin and
+/// stderr are attached to a terminal.
+fn prompt(name: &str) -> Result<OnConflict, Error> {
+    if !io::stdin().is_terminal() || !io::stderr().is_terminal() {
         return Err(Error::Message(format!(
-            "kubeconfig {} already exists at {}",
-            name,
-            target_path.display()
+            "kubeconfig {name} already exists and cannot prompt for a decision without a terminal"
         )));
     }
 
-    let kubeconfig = kubeconfig::rename_context(&kubeconfig, &name)?;
+    let mut stdin = io::stdin().lock();
+
+    loop {
+        eprint!("kubeconfig {name} already exists. [o]verwrite, [r]ename, [s]kip or [a]bort? ");
+        io::stderr().flush()?;
+
+        let mut answer = String::new();
+        if stdin.read_line(&mut answer)? == 0 {
+            return Ok(OnConflict::Error);
+        }
+
+        match answer.trim().to_lowercase().as_str() {
+            "o" | "overwrite" => return Ok(OnConflict::Overwrite),
+            "r" | "rename" => return Ok(OnConflict::Rename),
+            "s" | "skip" => return Ok(OnConflict::Skip),
+            "a" | "abort" => return Ok(OnConflict::Error),
+            _ => continue,
+        }
+    }
+}
+
+/// Normalize a kubeconfig and write it to the data store under the given
+/// name, replacing any kubeconfig already stored under that name. With
+/// exec_credentials, user credentials are moved to the credential store.
+pub fn save(
+    config_dir: &Path,
+    kubeconfig: &Kubeconfig,
+    name: &str,
+    proxy: Option<&String>,
+    exec_credentials: bool,
+) -> Result<(), Error> {
+    let mut kubeconfig = match exec_credentials {
+        true => credentials::extract(config_dir, kubeconfig, name)?,
+        // credentials of a kubeconfig previously stored under this name.
+        false => {
+            credentials::remove(config_dir, name)?;
+            kubeconfig.clone()
+        }
+    };
+
+    if let Some(proxy_url) = proxy {
+        let clusters = kubeconfig
+            .clusters
+            .iter()
+            .map(|cluster| set_proxy(cluster, proxy_url))
+            .collect::<Vec<NamedCluster>>();
+
+        kubeconfig.clusters = clusters;
+    }
 
-    let file = File::create(&target_path)?;
-    file.set_permissions(fs::Permissions::from_mode(0o600))?;
-    let file = BufWriter::new(file);
-    serde_yaml::to_writer(file, &kubeconfig)?;
+    let kubeconfig = kubeconfig::rename_context(&kubeconfig, name)?;
 
-    Ok(name)
+    store::write_private(
+        config_dir,
+        &kubeconfig::get_path(config_dir, name),
+        serde_yaml::to_string(&kubeconfig)?.as_bytes(),
+    )
 }
 
 fn set_proxy(cluster: &NamedCluster, proxy: &str) -> NamedCluster {
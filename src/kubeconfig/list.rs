@@ -1,11 +1,83 @@
-use crate::metadata::{self, Metadata};
+use super::{get_auth, get_expiry, get_servers, Auth};
+use crate::metadata::{self, ConfigMetadata, Metadata, SourceRecord, Timestamp};
 use crate::Error;
+use kube::config::Kubeconfig;
+use serde::Serialize;
 use std::collections::btree_map::BTreeMap;
 use std::{fs, path::Path};
 
+/// A kubeconfig in the data store. This is also the schema of structured
+/// output (json, yaml, jsonpath and templates), so fields should only ever
+/// be added to it.
+#[derive(Debug, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
 pub struct ListEntry {
+    /// Name of the kubeconfig in the data store.
     pub name: String,
+    /// Labels set on the kubeconfig.
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub labels: Option<BTreeMap<String, String>>,
+    // server and auth are only set by `with_details`.
+    /// Server URL of the (first) cluster.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub server: Option<String>,
+    /// Authentication methods of all users.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub auth: Vec<Auth>,
+    /// Earliest expiry of all client certificates.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cert_expiry: Option<Timestamp>,
+    /// Earliest expiry of all certificate authorities.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ca_expiry: Option<Timestamp>,
+    /// Last time the kubeconfig was activated with `kbs use`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_used: Option<Timestamp>,
+    /// Where the kubeconfig was imported from.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub source: Option<SourceRecord>,
+}
+
+impl ListEntry {
+    pub fn new(name: &str, config_metadata: Option<&ConfigMetadata>) -> Self {
+        ListEntry {
+            name: name.to_string(),
+            labels: config_metadata.map(|m| m.labels.clone()),
+            last_used: config_metadata.and_then(|m| m.last_used),
+            source: config_metadata.and_then(|m| m.source.clone()),
+            cert_expiry: config_metadata
+                .and_then(|m| m.expiry)
+                .and_then(|expiry| expiry.client_cert),
+            ca_expiry: config_metadata
+                .and_then(|m| m.expiry)
+                .and_then(|expiry| expiry.ca),
+            ..Default::default()
+        }
+    }
+
+    /// Add details read from the kubeconfig content to the entry. The
+    /// certificate expiry is read from the content as well, in case it was
+    /// not recorded on import. The recorded expiry is kept if the content
+    /// holds no certificates, e.g. because they are in the credential store.
+    pub fn with_details(self, kubeconfig: &Kubeconfig) -> Self {
+        let expiry = get_expiry(kubeconfig);
+
+        ListEntry {
+            server: get_servers(kubeconfig).into_iter().next(),
+            auth: get_auth(kubeconfig),
+            cert_expiry: expiry.client_cert.or(self.cert_expiry),
+            ca_expiry: expiry.ca.or(self.ca_expiry),
+            ..self
+        }
+    }
+}
+
+/// Get a single kubeconfig from the data store, including details.
+pub fn get_entry(config_dir: &Path, metadata: &Metadata, name: &str) -> Result<ListEntry, Error> {
+    let (_, kubeconfig) = super::get(config_dir, name)
+        .map_err(|err| Error::Message(format!("failed to load kubeconfig {name}: {err}")))?;
+
+    Ok(ListEntry::new(name, metadata.get(name)).with_details(&kubeconfig))
 }
 
 pub fn list(
@@ -31,28 +103,19 @@ pub fn list(
             .to_str()
             .ok_or_else(|| Error::Message("cannot convert file path to string".to_string()))?;
 
-        let mut labels: Option<BTreeMap<String, String>> = None;
+        let config_metadata = metadata.get(name);
 
         if let Some(ref selectors) = selectors {
-            labels = match metadata.get(name) {
-                Some(m) => {
-                    if !metadata::selectors::matches(
-                        selectors,
-                        &m.labels.clone().unwrap_or_default(),
-                    ) {
-                        continue;
-                    }
-
-                    Some(m.labels.clone().unwrap_or_default())
-                }
-                None => None,
-            };
+            let labels = config_metadata
+                .map(|m| m.labels.clone())
+                .unwrap_or_default();
+
+            if !metadata::selectors::matches(selectors, &labels) {
+                continue;
+            }
         }
 
-        kubeconfigs.push(ListEntry {
-            name: name.to_string(),
-            labels,
-        });
+        kubeconfigs.push(ListEntry::new(name, config_metadata));
     }
 
     Ok(kubeconfigs)
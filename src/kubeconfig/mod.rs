@@ -1,35 +1,141 @@
-use crate::Error;
+use crate::{store, Error};
 use kube::config::Kubeconfig;
-use std::{fs::File, io, path::Path, path::PathBuf};
+use std::{io, path::Path, path::PathBuf};
 use url::Url;
 
+mod cert;
+pub mod credentials;
+pub mod filter;
 mod import;
 mod list;
-
-pub use import::import;
-pub use list::list;
+pub mod reachability;
+mod redact;
+mod split;
+pub mod template;
+mod token;
+pub mod trash;
+
+pub use cert::{get_ca_fingerprints, get_client_certs, get_expiry};
+pub use import::{import, save, Naming, OnConflict};
 pub use list::ListEntry;
+pub use list::{get_entry, list};
+pub use redact::redact;
+pub use split::{extract, split};
+pub use token::get_token_expiry;
 
 #[cfg(test)]
 mod tests;
 
-pub fn get_from_file(file: &Path) -> Result<Kubeconfig, Error> {
-    let kubeconfig_file = File::open(file)?;
-
-    let kubeconfig = match serde_yaml::from_reader::<File, Kubeconfig>(kubeconfig_file) {
-        Ok(kubeconfig) => kubeconfig,
-        Err(err) => return Err(Error::YAML(err)),
-    };
-
-    Ok(kubeconfig)
+pub fn from_bytes(bytes: &[u8]) -> Result<Kubeconfig, Error> {
+    match serde_yaml::from_slice::<Kubeconfig>(bytes) {
+        Ok(kubeconfig) => Ok(kubeconfig),
+        Err(err) => Err(Error::YAML(err)),
+    }
 }
 
 pub fn get(config_dir: &Path, name: &str) -> Result<(PathBuf, Kubeconfig), Error> {
     let kubeconfig_path = get_path(config_dir, name);
-    let kubeconfig = get_from_file(&kubeconfig_path)?;
+    let kubeconfig = from_bytes(&store::read_private(config_dir, &kubeconfig_path)?)?;
     Ok((kubeconfig_path, kubeconfig))
 }
 
+/// Get the proxy URL configured for the clusters in a kubeconfig, if any.
+pub fn get_proxy_url(kubeconfig: &Kubeconfig) -> Option<String> {
+    kubeconfig
+        .clusters
+        .iter()
+        .find_map(|cluster| cluster.cluster.as_ref()?.proxy_url.clone())
+}
+
+/// Authentication method used by a user in a kubeconfig.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Auth {
+    Exec,
+    Token,
+    Cert,
+    Basic,
+    Provider,
+    None,
+}
+
+impl Auth {
+    pub const ALL: &'static [Auth] = &[
+        Auth::Exec,
+        Auth::Token,
+        Auth::Cert,
+        Auth::Basic,
+        Auth::Provider,
+        Auth::None,
+    ];
+}
+
+impl std::fmt::Display for Auth {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let name = match self {
+            Auth::Exec => "exec",
+            Auth::Token => "token",
+            Auth::Cert => "cert",
+            Auth::Basic => "basic",
+            Auth::Provider => "provider",
+            Auth::None => "none",
+        };
+
+        write!(f, "{name}")
+    }
+}
+
+impl serde::Serialize for Auth {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.collect_str(self)
+    }
+}
+
+/// Get the authentication methods of all users in a kubeconfig.
+pub fn get_auth(kubeconfig: &Kubeconfig) -> Vec<Auth> {
+    let mut auth: Vec<Auth> = kubeconfig
+        .auth_infos
+        .iter()
+        .map(|auth_info| match auth_info.auth_info.as_ref() {
+            Some(info) if info.exec.is_some() => Auth::Exec,
+            Some(info) if info.token.is_some() || info.token_file.is_some() => Auth::Token,
+            Some(info)
+                if info.client_certificate.is_some() || info.client_certificate_data.is_some() =>
+            {
+                Auth::Cert
+            }
+            Some(info) if info.username.is_some() || info.password.is_some() => Auth::Basic,
+            Some(info) if info.auth_provider.is_some() => Auth::Provider,
+            _ => Auth::None,
+        })
+        .collect();
+
+    auth.dedup();
+    auth
+}
+
+/// Get the server URLs of all clusters in a kubeconfig.
+pub fn get_servers(kubeconfig: &Kubeconfig) -> Vec<String> {
+    kubeconfig
+        .clusters
+        .iter()
+        .filter_map(|cluster| cluster.cluster.as_ref()?.server.clone())
+        .collect()
+}
+
+/// Get the default name for a kubeconfig, which is the hostname of its
+/// server (or only the first part of it if use_short is set).
+pub fn get_name(kubeconfig: &Kubeconfig, use_short: bool) -> Result<String, Error> {
+    let host = get_hostname(kubeconfig)?;
+
+    Ok(match use_short {
+        true => host.split_once('.').unwrap_or((&host, "")).0.to_string(),
+        false => host,
+    })
+}
+
 pub fn get_hostname(kubeconfig: &Kubeconfig) -> Result<String, Error> {
     let mut urls: Vec<String> = vec![];
     for cluster in kubeconfig.clusters.iter() {
@@ -60,7 +166,7 @@ pub fn get_hostname(kubeconfig: &Kubeconfig) -> Result<String, Error> {
         )),
         1 => urls.first().ok_or(Error::Message("".to_string())).cloned(),
         _ => Err(Error::Message(
-            "kubeconfig has more than one server defined".to_string(),
+            "kubeconfig has more than one server defined, use --name or --split".to_string(),
         )),
     }
 }
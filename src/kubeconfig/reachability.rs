@@ -0,0 +1,291 @@
+use clap::ValueEnum;
+use kube::client::AuthError;
+use kube::config::Kubeconfig;
+use std::error::Error as StdError;
+use std::time::Duration;
+
+/// Result of checking whether the Kubernetes API server of a kubeconfig is
+/// reachable.
+#[derive(Debug)]
+pub enum Reachability {
+    Reachable,
+    Failed(Failure, String),
+}
+
+/// Class of failure when checking a Kubernetes API server.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, ValueEnum)]
+pub enum Failure {
+    /// The server could not be connected to, e.g. the connection was refused.
+    Network,
+    /// The server's host name could not be resolved.
+    Dns,
+    /// The server did not respond in time.
+    Timeout,
+    /// The TLS connection could not be established, e.g. the server
+    /// certificate could not be verified.
+    Tls,
+    /// The server rejected the credentials (HTTP 401).
+    Unauthorized,
+    /// The credentials are not allowed to access the server version (HTTP 403).
+    Forbidden,
+    /// The exec plugin providing credentials failed.
+    Exec,
+    /// Any other failure, e.g. an invalid kubeconfig.
+    Other,
+}
+
+impl Failure {
+    /// Network-level failures, i.e. the server itself could not be reached.
+    pub const NETWORK: &'static [Failure] = &[Failure::Network, Failure::Dns, Failure::Timeout];
+
+    pub fn is_network(&self) -> bool {
+        Failure::NETWORK.contains(self)
+    }
+}
+
+impl std::fmt::Display for Failure {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let value = self.to_possible_value().ok_or(std::fmt::Error)?;
+        write!(f, "{}", value.get_name())
+    }
+}
+
+/// Check if the Kubernetes API server of a kubeconfig is reachable by
+/// fetching its version. The timeout covers the whole check, including exec
+/// plugins and TLS handshakes, so a server that accepts connections but
+/// never responds cannot stall the check.
+pub async fn check(kubeconfig: Kubeconfig, timeout: Duration) -> Reachability {
+    match tokio::time::timeout(timeout, ping(kubeconfig, timeout)).await {
+        Ok(reachability) => reachability,
+        Err(_) => Reachability::Failed(
+            Failure::Timeout,
+            format!("timed out after {}", humantime::format_duration(timeout)),
+        ),
+    }
+}
+
+async fn ping(kubeconfig: Kubeconfig, timeout: Duration) -> Reachability {
+    let options = kube::config::KubeConfigOptions {
+        cluster: None,
+        context: None,
+        user: None,
+    };
+
+    let mut config = match kube::Config::from_custom_kubeconfig(kubeconfig, &options).await {
+        Ok(config) => config,
+        Err(err) => return Reachability::Failed(Failure::Other, err.to_string()),
+    };
+    config.connect_timeout = Some(timeout);
+    config.read_timeout = Some(timeout);
+
+    // resolve the server's host name up front, so DNS failures are known by
+    // where they happened instead of being guessed from the HTTP stack's
+    // error messages. With a proxy, the proxy resolves the host name.
+    let resolve = config
+        .proxy_url
+        .is_none()
+        .then(|| address(&config.cluster_url.to_string()))
+        .flatten();
+
+    // failing to create a client means the kubeconfig is invalid (e.g. a
+    // client key is missing) or the exec plugin failed, the server has not
+    // been contacted yet.
+    let client = match kube::client::Client::try_from(config) {
+        Ok(client) => client,
+        Err(err) => {
+            // exec plugins are run when creating the client.
+            let failure = match &err {
+                kube::Error::Auth(err) => classify_auth(err),
+                _ => Failure::Other,
+            };
+            return Reachability::Failed(failure, describe(&err));
+        }
+    };
+
+    if let Some((host, port)) = resolve {
+        if let Err(err) = tokio::net::lookup_host((host.as_str(), port)).await {
+            return Reachability::Failed(Failure::Dns, format!("failed to resolve {host}: {err}"));
+        }
+    }
+
+    match client.apiserver_version().await {
+        Ok(_) => Reachability::Reachable,
+        Err(err) => Reachability::Failed(classify(&err), describe(&err)),
+    }
+}
+
+fn classify(err: &kube::Error) -> Failure {
+    match err {
+        kube::Error::Api(response) if response.code == 401 => return Failure::Unauthorized,
+        kube::Error::Api(response) if response.code == 403 => return Failure::Forbidden,
+        kube::Error::Auth(err) => return classify_auth(err),
+        _ => {}
+    }
+
+    // errors from the HTTP stack are boxed, so the cause has to be found in
+    // the chain of sources.
+    for err in sources(err) {
+        if let Some(err) = err.downcast_ref::<AuthError>() {
+            return classify_auth(err);
+        }
+
+        if err.is::<rustls::Error>() {
+            return Failure::Tls;
+        }
+
+        if let Some(err) = err.downcast_ref::<std::io::Error>() {
+            use std::io::ErrorKind;
+
+            match err.kind() {
+                ErrorKind::TimedOut => return Failure::Timeout,
+                ErrorKind::ConnectionRefused
+                | ErrorKind::ConnectionReset
+                | ErrorKind::ConnectionAborted
+                | ErrorKind::NotConnected
+                | ErrorKind::AddrNotAvailable
+                | ErrorKind::HostUnreachable
+                | ErrorKind::NetworkUnreachable => return Failure::Network,
+                _ => {}
+            }
+        }
+    }
+
+    Failure::Other
+}
+
+// Host name and port of a server URL, with brackets removed from IPv6
+// addresses.
+fn address(url: &str) -> Option<(String, u16)> {
+    let url = url::Url::parse(url).ok()?;
+    let host = url
+        .host_str()?
+        .trim_start_matches('[')
+        .trim_end_matches(']');
+
+    Some((host.to_string(), url.port_or_known_default()?))
+}
+
+// Iterate over an error and its sources. Unlike `source()`, this includes
+// errors wrapped in `std::io::Error` (e.g. TLS errors).
+fn sources<'a>(
+    err: &'a (dyn StdError + 'static),
+) -> impl Iterator<Item = &'a (dyn StdError + 'static)> {
+    std::iter::successors(Some(err), |err: &&'a (dyn StdError + 'static)| {
+        let err: &'a (dyn StdError + 'static) = *err;
+        match err
+            .downcast_ref::<std::io::Error>()
+            .and_then(|err| err.get_ref())
+        {
+            Some(inner) => Some(inner as &(dyn StdError + 'static)),
+            None => err.source(),
+        }
+    })
+}
+
+// Describe an error with all of its sources, as the top-level errors of the
+// HTTP stack (e.g. `client error (Connect)`) are not helpful on their own.
+fn describe(err: &kube::Error) -> String {
+    let mut messages: Vec<String> = vec![];
+
+    for err in sources(err) {
+        let message = err.to_string();
+        if !messages.iter().any(|m| m.contains(&message)) {
+            messages.push(message);
+        }
+    }
+
+    messages.join(": ")
+}
+
+fn classify_auth(err: &AuthError) -> Failure {
+    match err {
+        AuthError::ExecPluginFailed
+        | AuthError::AuthExecStart(_)
+        | AuthError::AuthExecRun { .. }
+        | AuthError::AuthExecParse(_)
+        | AuthError::AuthExec(_)
+        | AuthError::MissingCommand
+        | AuthError::ExecMissingClusterInfo => Failure::Exec,
+        _ => Failure::Other,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use kube::core::ErrorResponse;
+
+    fn api_error(code: u16) -> kube::Error {
+        kube::Error::Api(ErrorResponse {
+            status: "Failure".to_string(),
+            message: "".to_string(),
+            reason: "".to_string(),
+            code,
+        })
+    }
+
+    #[test]
+    fn test_classify() {
+        assert_eq!(Failure::Unauthorized, classify(&api_error(401)));
+        assert_eq!(Failure::Forbidden, classify(&api_error(403)));
+        assert_eq!(Failure::Other, classify(&api_error(500)));
+        assert_eq!(
+            Failure::Exec,
+            classify(&kube::Error::Auth(AuthError::AuthExec(
+                "plugin exited".to_string()
+            )))
+        );
+
+        // errors from the HTTP stack are wrapped.
+        assert_eq!(
+            Failure::Exec,
+            classify(&kube::Error::Service(Box::new(AuthError::ExecPluginFailed)))
+        );
+        assert_eq!(
+            Failure::Network,
+            classify(&kube::Error::Service(Box::new(std::io::Error::from(
+                std::io::ErrorKind::ConnectionRefused
+            ))))
+        );
+        assert_eq!(
+            Failure::Tls,
+            classify(&kube::Error::Service(Box::new(std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                rustls::Error::InvalidCertificate(rustls::CertificateError::UnknownIssuer)
+            ))))
+        );
+        assert_eq!(
+            Failure::Timeout,
+            classify(&kube::Error::Service(Box::new(std::io::Error::from(
+                std::io::ErrorKind::TimedOut
+            ))))
+        );
+    }
+
+    #[test]
+    fn test_address() {
+        assert_eq!(
+            Some(("example.com".to_string(), 443)),
+            address("https://example.com")
+        );
+        assert_eq!(
+            Some(("example.com".to_string(), 6443)),
+            address("https://example.com:6443")
+        );
+        assert_eq!(
+            Some(("example.com".to_string(), 80)),
+            address("http://example.com")
+        );
+        assert_eq!(
+            Some(("::1".to_string(), 6443)),
+            address("https://[::1]:6443")
+        );
+    }
+
+    #[test]
+    fn test_failure_display() {
+        assert_eq!("unauthorized", Failure::Unauthorized.to_string());
+        assert!(Failure::Dns.is_network());
+        assert!(!Failure::Tls.is_network());
+    }
+}
@@ -0,0 +1,196 @@
+use crate::Error;
+use kube::config::Kubeconfig;
+use serde_yaml::{Mapping, Value};
+
+const REDACTED: &str = "REDACTED";
+
+// Fields of a user that hold credentials.
+const USER_SECRETS: &[&str] = &["client-key-data", "token", "password"];
+
+// Exec plugin flags with these words in their name are likely to carry
+// credentials, e.g. `--token=...` or `--client-secret ...`.
+const SECRET_FLAGS: &[&str] = &["token", "password", "secret", "key"];
+
+/// Serialize a kubeconfig with all credentials replaced by `REDACTED`. This
+/// covers client keys, tokens and passwords of users as well as the config
+/// of auth providers and environment variables and secret-looking flags
+/// passed to exec plugins, which frequently hold tokens too.
+pub fn redact(kubeconfig: &Kubeconfig) -> Result<Value, Error> {
+    let mut value = serde_yaml::to_value(kubeconfig)?;
+
+    let users = value
+        .get_mut("users")
+        .and_then(Value::as_sequence_mut)
+        .into_iter()
+        .flatten()
+        .filter_map(|user| user.get_mut("user").and_then(Value::as_mapping_mut));
+
+    for user in users {
+        for field in USER_SECRETS {
+            if let Some(secret) = user.get_mut(*field) {
+                *secret = Value::from(REDACTED);
+            }
+        }
+
+        if let Some(config) = user
+            .get_mut("auth-provider")
+            .and_then(|provider| provider.get_mut("config"))
+            .and_then(Value::as_mapping_mut)
+        {
+            redact_values(config);
+        }
+
+        if let Some(args) = user
+            .get_mut("exec")
+            .and_then(|exec| exec.get_mut("args"))
+            .and_then(Value::as_sequence_mut)
+        {
+            redact_args(args);
+        }
+
+        let env = user
+            .get_mut("exec")
+            .and_then(|exec| exec.get_mut("env"))
+            .and_then(Value::as_sequence_mut)
+            .into_iter()
+            .flatten()
+            .filter_map(Value::as_mapping_mut);
+
+        for var in env {
+            if let Some(value) = var.get_mut("value") {
+                *value = Value::from(REDACTED);
+            }
+        }
+    }
+
+    Ok(value)
+}
+
+// Redact values of secret flags, passed either as `--flag=value` or as
+// `--flag value`.
+fn redact_args(args: &mut [Value]) {
+    let mut redact_next = false;
+
+    for arg in args.iter_mut() {
+        let Some(s) = arg.as_str() else {
+            redact_next = false;
+            continue;
+        };
+
+        if redact_next && !s.starts_with('-') {
+            *arg = Value::from(REDACTED);
+            redact_next = false;
+            continue;
+        }
+
+        let Some(flag) = s.strip_prefix('-') else {
+            redact_next = false;
+            continue;
+        };
+
+        let (flag, value) = match flag.split_once('=') {
+            Some((flag, value)) => (flag, Some(value)),
+            None => (flag, None),
+        };
+
+        let secret = SECRET_FLAGS
+            .iter()
+            .any(|word| flag.to_lowercase().contains(word));
+
+        redact_next = secret && value.is_none();
+        if secret && value.is_some() {
+            *arg = Value::from(format!("-{flag}={REDACTED}"));
+        }
+    }
+}
+
+fn redact_values(mapping: &mut Mapping) {
+    for (_, value) in mapping.iter_mut() {
+        *value = Value::from(REDACTED);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_redact() {
+        let kubeconfig: Kubeconfig = serde_yaml::from_str(
+            r#"
+apiVersion: v1
+kind: Config
+clusters:
+- name: kubernetes
+  cluster:
+    server: https://kubernetes.embik.me:6443
+    certificate-authority-data: Y2E=
+users:
+- name: cert
+  user:
+    client-certificate-data: Y2VydA==
+    client-key-data: a2V5
+- name: token
+  user:
+    token: secret-token
+- name: basic
+  user:
+    username: admin
+    password: secret-password
+- name: provider
+  user:
+    auth-provider:
+      name: oidc
+      config:
+        id-token: secret-id-token
+- name: exec
+  user:
+    exec:
+      apiVersion: client.authentication.k8s.io/v1
+      command: kubelogin
+      args:
+      - get-token
+      - --token=secret-arg
+      - --client-secret
+      - secret-next-arg
+      - --server-id
+      - server
+      env:
+      - name: TOKEN
+        value: secret-env
+"#,
+        )
+        .unwrap();
+
+        let redacted = serde_yaml::to_string(&redact(&kubeconfig).unwrap()).unwrap();
+
+        for secret in [
+            "a2V5",
+            "secret-token",
+            "secret-password",
+            "secret-id-token",
+            "secret-env",
+            "secret-arg",
+            "secret-next-arg",
+        ] {
+            assert!(!redacted.contains(secret), "{secret} should be redacted");
+        }
+
+        // non-secret data is kept.
+        for data in [
+            "Y2E=",
+            "Y2VydA==",
+            "admin",
+            "kubelogin",
+            "TOKEN",
+            "oidc",
+            "get-token",
+            "--token=REDACTED",
+            "--client-secret",
+            "--server-id",
+            "server",
+        ] {
+            assert!(redacted.contains(data), "{data} should be kept");
+        }
+    }
+}
@@ -0,0 +1,133 @@
+use super::get_name;
+use super::template::{NameTemplate, Values};
+use crate::Error;
+use kube::config::Kubeconfig;
+use std::collections::BTreeMap;
+use std::path::Path;
+
+/// Kubeconfigs split from a single kubeconfig.
+pub struct Split {
+    /// One kubeconfig per context along with the name to import it as.
+    pub parts: Vec<(String, Kubeconfig)>,
+    /// Contexts that could not be extracted along with the reason.
+    pub failed: Vec<(String, Error)>,
+}
+
+/// Break a kubeconfig into one kubeconfig per context. Each of them is named
+/// after the hostname of its cluster, unless several contexts point to the same
+/// host; those are named after their context instead. If a name template is
+/// passed, it names the parts and contexts sharing a rendered name get their
+/// context appended to it. Contexts that cannot be extracted are returned
+/// along with the reason, so callers can report them.
+pub fn split(
+    kubeconfig: &Kubeconfig,
+    template: Option<&NameTemplate>,
+    path: Option<&Path>,
+    use_short: bool,
+) -> Result<Split, Error> {
+    let mut parts = vec![];
+    let mut failed = vec![];
+
+    for context in kubeconfig.contexts.iter() {
+        match extract(kubeconfig, &context.name) {
+            Ok(part) => parts.push(part),
+            Err(err) => failed.push((context.name.clone(), err)),
+        }
+    }
+
+    if kubeconfig.contexts.is_empty() {
+        return Err(Error::Message(
+            "could not find any context in kubeconfig".to_string(),
+        ));
+    }
+
+    let names = parts
+        .iter()
+        .map(|part| match template {
+            Some(template) => template.render(&Values {
+                path,
+                kubeconfig: part,
+                use_short,
+            }),
+            None => get_name(part, use_short),
+        })
+        .collect::<Result<Vec<String>, Error>>()?;
+
+    let mut counts: BTreeMap<&str, usize> = BTreeMap::new();
+    for name in names.iter() {
+        *counts.entry(name).or_default() += 1;
+    }
+
+    let parts = parts
+        .iter()
+        .zip(names.iter())
+        .map(|(part, name)| {
+            let context = sanitize(part.current_context.as_deref().unwrap_or(name));
+            let name = match (counts[name.as_str()], template) {
+                (1, _) => name.to_string(),
+                (_, Some(_)) => format!("{name}-{context}"),
+                (_, None) => context,
+            };
+
+            (name, part.clone())
+        })
+        .collect();
+
+    Ok(Split { parts, failed })
+}
+
+/// Extract a single context from a kubeconfig, along with the cluster and
+/// user it references. The context is set as current context.
+pub fn extract(kubeconfig: &Kubeconfig, context_name: &str) -> Result<Kubeconfig, Error> {
+    let named_context = kubeconfig
+        .contexts
+        .iter()
+        .find(|context| context.name == context_name)
+        .ok_or_else(|| Error::Message(format!("could not find context {context_name}")))?;
+
+    let context = named_context
+        .context
+        .as_ref()
+        .ok_or_else(|| Error::Message(format!("context {context_name} is empty")))?;
+
+    let cluster = kubeconfig
+        .clusters
+        .iter()
+        .find(|cluster| cluster.name == context.cluster)
+        .ok_or_else(|| {
+            Error::Message(format!(
+                "context {context_name} references unknown cluster {}",
+                context.cluster
+            ))
+        })?;
+
+    let auth_info = kubeconfig
+        .auth_infos
+        .iter()
+        .find(|auth_info| auth_info.name == context.user)
+        .ok_or_else(|| {
+            Error::Message(format!(
+                "context {context_name} references unknown user {}",
+                context.user
+            ))
+        })?;
+
+    Ok(Kubeconfig {
+        clusters: vec![cluster.clone()],
+        auth_infos: vec![auth_info.clone()],
+        contexts: vec![named_context.clone()],
+        current_context: Some(context_name.to_string()),
+        ..kubeconfig.clone()
+    })
+}
+
+// context names might contain characters that are not suitable for file
+// names (e.g. ARNs for EKS clusters), so replace them.
+fn sanitize(name: &str) -> String {
+    name.chars()
+        .map(|c| match c {
+            'a'..='z' | 'A'..='Z' | '0'..='9' | '-' | '.' | '_' | '@' => c,
+            _ => '-',
+        })
+        .collect()
+}
@@ -0,0 +1,349 @@
+use super::get_name;
+use crate::Error;
+use kube::config::{Kubeconfig, NamedContext};
+use regex::Regex;
+use std::{fmt, path::Path};
+use url::Url;
+
+/// Template to name imported kubeconfigs by, e.g. `{dir}-{host}`.
+///
+/// Placeholders reference a [`Field`] and can select a single dot-separated
+/// segment of its value (`{host[1]}`) and/or extract a part of it with a
+/// regular expression (`{host:^api\.([^.]+)}`). The regular expression's first
+/// capture group is used if it has one, otherwise the whole match.
+#[derive(Clone, Debug)]
+pub struct NameTemplate {
+    parts: Vec<Part>,
+}
+
+#[derive(Clone, Debug)]
+enum Part {
+    Literal(String),
+    Placeholder(Placeholder),
+}
+
+#[derive(Clone, Debug)]
+struct Placeholder {
+    field: Field,
+    index: Option<usize>,
+    regex: Option<Regex>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Field {
+    /// Name of the directory the kubeconfig file is in.
+    Dir,
+    /// Name of the kubeconfig file without extension.
+    File,
+    /// Hostname of the server in the kubeconfig.
+    Host,
+    /// Port of the server in the kubeconfig.
+    Port,
+    /// Name of the current context in the kubeconfig.
+    Context,
+    /// Name of the cluster referenced by the current context.
+    Cluster,
+    /// Name of the user referenced by the current context.
+    User,
+}
+
+/// Values available to placeholders when rendering a [`NameTemplate`].
+pub struct Values<'a> {
+    pub path: Option<&'a Path>,
+    pub kubeconfig: &'a Kubeconfig,
+    pub use_short: bool,
+}
+
+/// Parse a name template from string.
+pub fn parse(s: &str) -> Result<NameTemplate, Error> {
+    let mut parts = vec![];
+    let mut literal = String::new();
+    let mut chars = s.char_indices();
+
+    while let Some((start, c)) = chars.next() {
+        if c != '{' {
+            literal.push(c);
+            continue;
+        }
+
+        // find the matching closing brace, regular expressions
+        // might contain braces themselves (e.g. `[a-z]{2}`).
+        let mut depth = 1;
+        let end = chars
+            .by_ref()
+            .find(|(_, c)| {
+                match c {
+                    '{' => depth += 1,
+                    '}' => depth -= 1,
+                    _ => {}
+                };
+                depth == 0
+            })
+            .map(|(end, _)| end)
+            .ok_or_else(|| Error::Message(format!("unclosed placeholder in template `{s}`")))?;
+
+        if !literal.is_empty() {
+            parts.push(Part::Literal(std::mem::take(&mut literal)));
+        }
+
+        parts.push(Part::Placeholder(
+            parse_placeholder(&s[start + 1..end])
+                .map_err(|err| Error::Message(format!("{err} in template `{s}`")))?,
+        ));
+    }
+
+    if !literal.is_empty() {
+        parts.push(Part::Literal(literal));
+    }
+
+    Ok(NameTemplate { parts })
+}
+
+fn parse_placeholder(s: &str) -> Result<Placeholder, Error> {
+    let (s, regex) = match s.split_once(':') {
+        Some((s, regex)) => (
+            s,
+            Some(Regex::new(regex).map_err(|err| {
+                Error::Message(format!("invalid regular expression `{regex}`: {err}"))
+            })?),
+        ),
+        None => (s, None),
+    };
+
+    let (name, index) = match s.strip_suffix(']').and_then(|s| s.split_once('[')) {
+        Some((name, index)) => (
+            name,
+            Some(
+                index
+                    .parse::<usize>()
+                    .map_err(|_| Error::Message(format!("invalid index `{index}`")))?,
+            ),
+        ),
+        None => (s, None),
+    };
+
+    let field = match name {
+        "dir" => Field::Dir,
+        "file" => Field::File,
+        "host" => Field::Host,
+        "port" => Field::Port,
+        "context" => Field::Context,
+        "cluster" => Field::Cluster,
+        "user" => Field::User,
+        other => return Err(Error::Message(format!("unknown placeholder `{{{other}}}`"))),
+    };
+
+    Ok(Placeholder {
+        field,
+        index,
+        regex,
+    })
+}
+
+impl Default for NameTemplate {
+    fn default() -> Self {
+        NameTemplate {
+            parts: vec![Part::Placeholder(Placeholder {
+                field: Field::Host,
+                index: None,
+                regex: None,
+            })],
+        }
+    }
+}
+
+impl NameTemplate {
+    pub fn render(&self, values: &Values) -> Result<String, Error> {
+        let mut name = String::new();
+
+        for part in self.parts.iter() {
+            match part {
+                Part::Literal(literal) => name.push_str(literal),
+                Part::Placeholder(placeholder) => name.push_str(&placeholder.render(values)?),
+            }
+        }
+
+        if name.is_empty() || name.contains('/') {
+            return Err(Error::Message(format!(
+                "template rendered invalid name `{name}`"
+            )));
+        }
+
+        Ok(name)
+    }
+}
+
+impl fmt::Display for Field {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            Field::Dir => "dir",
+            Field::File => "file",
+            Field::Host => "host",
+            Field::Port => "port",
+            Field::Context => "context",
+            Field::Cluster => "cluster",
+            Field::User => "user",
+        };
+
+        write!(f, "{{{name}}}")
+    }
+}
+
+impl Placeholder {
+    fn render(&self, values: &Values) -> Result<String, Error> {
+        let mut value = self.field.value(values)?;
+
+        if let Some(index) = self.index {
+            value = value
+                .split('.')
+                .nth(index)
+                .ok_or_else(|| {
+                    Error::Message(format!(
+                        "{} value `{value}` has no segment with index {index}",
+                        self.field
+                    ))
+                })?
+                .to_string();
+        }
+
+        if let Some(regex) = &self.regex {
+            let captures = regex.captures(&value).ok_or_else(|| {
+                Error::Message(format!(
+                    "{} value `{value}` does not match `{regex}`",
+                    self.field
+                ))
+            })?;
+
+            value = captures
+                .get(1)
+                .or_else(|| captures.get(0))
+                .map(|m| m.as_str().to_string())
+                .unwrap_or_default();
+        }
+
+        Ok(value)
+    }
+}
+
+impl Field {
+    fn value(&self, values: &Values) -> Result<String, Error> {
+        let path = || {
+            values.path.ok_or_else(|| {
+                Error::Message(format!("{self} is only available when importing files"))
+            })
+        };
+
+        let value = match self {
+            Field::Dir => path()?
+                .parent()
+                .and_then(|dir| dir.file_name())
+                .map(|dir| dir.to_string_lossy().to_string()),
+            Field::File => path()?
+                .file_stem()
+                .map(|file| file.to_string_lossy().to_string()),
+            Field::Host => Some(get_name(values.kubeconfig, values.use_short)?),
+            Field::Port => server_url(values.kubeconfig)?
+                .port_or_known_default()
+                .map(|port| port.to_string()),
+            Field::Context => values.kubeconfig.current_context.clone(),
+            Field::Cluster => current_context(values.kubeconfig)?
+                .context
+                .as_ref()
+                .map(|context| context.cluster.clone()),
+            Field::User => current_context(values.kubeconfig)?
+                .context
+                .as_ref()
+                .map(|context| context.user.clone()),
+        };
+
+        value.ok_or_else(|| Error::Message(format!("cannot determine value for {self}")))
+    }
+}
+
+fn current_context(kubeconfig: &Kubeconfig) -> Result<&NamedContext, Error> {
+    let current_context = kubeconfig
+        .current_context
+        .as_ref()
+        .ok_or_else(|| Error::Message("cannot get current context".to_string()))?;
+
+    kubeconfig
+        .contexts
+        .iter()
+        .find(|context| context.name.eq(current_context))
+        .ok_or_else(|| Error::Message(format!("could not find context {current_context}")))
+}
+
+// server URL of the cluster referenced by the current context.
+fn server_url(kubeconfig: &Kubeconfig) -> Result<Url, Error> {
+    let cluster_name = current_context(kubeconfig)?
+        .context
+        .as_ref()
+        .map(|context| context.cluster.clone());
+
+    let server = kubeconfig
+        .clusters
+        .iter()
+        .find(|cluster| Some(&cluster.name) == cluster_name.as_ref())
+        .and_then(|cluster| cluster.cluster.as_ref())
+        .and_then(|cluster| cluster.server.as_ref())
+        .ok_or_else(|| Error::Message("could not find server URL in kubeconfig".to_string()))?;
+
+    Ok(Url::parse(server)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn kubeconfig() -> Kubeconfig {
+        serde_yaml::from_str(include_str!("../../tests/files/test.kubeconfig")).unwrap()
+    }
+
+    fn render(template: &str, values: &Values) -> String {
+        parse(template).unwrap().render(values).unwrap()
+    }
+
+    #[test]
+    fn test_parse() {
+        assert!(parse("{dir}-{host}").is_ok());
+        assert!(parse("static").is_ok());
+        assert!(parse("{host[1]}").is_ok());
+        assert!(parse(r"{host:^([a-z]{2})}").is_ok());
+        assert!(parse("{dir").is_err());
+        assert!(parse("{unknown}").is_err());
+        assert!(parse("{host[a]}").is_err());
+        assert!(parse("{host:(}").is_err());
+    }
+
+    #[test]
+    fn test_render() {
+        let kubeconfig = kubeconfig();
+        let values = Values {
+            path: Some(Path::new("/home/user/clusters/prod/admin.yaml")),
+            kubeconfig: &kubeconfig,
+            use_short: false,
+        };
+
+        assert_eq!("prod-kubernetes.embik.me", render("{dir}-{host}", &values));
+        assert_eq!("admin@prod", render("{file}@{dir}", &values));
+        assert_eq!("embik-6443", render("{host[1]}-{port}", &values));
+        assert_eq!(
+            "kubernetes-admin@kubernetes",
+            render("{user}@{cluster}", &values)
+        );
+        assert_eq!("kubernetes-admin@kubernetes", render("{context}", &values));
+        assert_eq!("embik", render(r"{host:^kubernetes\.([a-z]+)}", &values));
+        assert_eq!("me", render(r"{host:[a-z]{2}$}", &values));
+        assert!(parse("{host[5]}").unwrap().render(&values).is_err());
+        assert!(parse("{host:^api}").unwrap().render(&values).is_err());
+
+        let values = Values {
+            path: None,
+            use_short: true,
+            ..values
+        };
+
+        assert_eq!("kubernetes", render("{host}", &values));
+        assert!(parse("{file}").unwrap().render(&values).is_err());
+    }
+}
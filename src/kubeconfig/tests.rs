@@ -149,7 +149,7 @@ fn test_get_hostname_multiple_clusters() -> Result<()> {
     assert_eq!("test.embik.me", get_hostname(&kubeconfig)?);
 
     let kubeconfig = kubeconfig_multiple_cluster_different_hosts();
-    assert_eq!(true, get_hostname(&kubeconfig).is_err());
+    assert!(get_hostname(&kubeconfig).is_err());
 
     Ok(())
 }
@@ -168,3 +168,120 @@ fn test_rename_context() -> Result<()> {
 
     Ok(())
 }
+
+#[test]
+fn test_split() -> Result<()> {
+    let mut kubeconfig = kubeconfig_multiple_cluster_different_hosts();
+    kubeconfig.contexts.push(NamedContext {
+        name: "other-context".to_string(),
+        context: Some(kube::config::Context {
+            cluster: "test-cluster-2".to_string(),
+            user: "user".to_string(),
+            namespace: None,
+            extensions: None,
+        }),
+    });
+
+    let parts = split(&kubeconfig, None, None, false)?.parts;
+    assert_eq!(2, parts.len());
+
+    let (name, part) = &parts[0];
+    assert_eq!("kubernetes.embik.me", name);
+    assert_eq!(Some("current-context".to_string()), part.current_context);
+    assert_eq!(1, part.clusters.len());
+    assert_eq!("test-cluster", part.clusters[0].name);
+    assert_eq!(1, part.auth_infos.len());
+
+    let (name, part) = &parts[1];
+    assert_eq!("test.embik.me", name);
+    assert_eq!("test-cluster-2", part.clusters[0].name);
+
+    let parts = split(&kubeconfig, None, None, true)?.parts;
+    assert_eq!("kubernetes", parts[0].0);
+    assert_eq!("test", parts[1].0);
+
+    Ok(())
+}
+
+#[test]
+fn test_split_same_host() -> Result<()> {
+    let mut kubeconfig = kubeconfig();
+    kubeconfig.contexts.push(NamedContext {
+        name: "arn:aws:eks:eu-central-1:123456789012:cluster/test".to_string(),
+        context: Some(kube::config::Context {
+            cluster: "test-cluster".to_string(),
+            user: "user".to_string(),
+            namespace: None,
+            extensions: None,
+        }),
+    });
+
+    // both contexts point to the same host, so they are named after their context.
+    let names = split(&kubeconfig, None, None, false)?
+        .parts
+        .into_iter()
+        .map(|(name, _)| name)
+        .collect::<Vec<String>>();
+    assert_eq!(
+        vec![
+            "current-context",
+            "arn-aws-eks-eu-central-1-123456789012-cluster-test"
+        ],
+        names
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_split_name_template() -> Result<()> {
+    let mut kubeconfig = kubeconfig();
+    kubeconfig.contexts.push(NamedContext {
+        name: "other-context".to_string(),
+        context: Some(kube::config::Context {
+            cluster: "test-cluster".to_string(),
+            user: "user".to_string(),
+            namespace: None,
+            extensions: None,
+        }),
+    });
+
+    // both contexts render to the same name, so their context is appended.
+    let host = template::parse("{host[0]}")?;
+    let names = split(&kubeconfig, Some(&host), None, false)?
+        .parts
+        .into_iter()
+        .map(|(name, _)| name)
+        .collect::<Vec<String>>();
+    assert_eq!(vec!["test-current-context", "test-other-context"], names);
+
+    let context = template::parse("{context}")?;
+    let names = split(&kubeconfig, Some(&context), None, false)?
+        .parts
+        .into_iter()
+        .map(|(name, _)| name)
+        .collect::<Vec<String>>();
+    assert_eq!(vec!["current-context", "other-context"], names);
+
+    Ok(())
+}
+
+#[test]
+fn test_extract_unknown_cluster() {
+    let mut kubeconfig = kubeconfig();
+    kubeconfig.contexts.push(NamedContext {
+        name: "broken".to_string(),
+        context: Some(kube::config::Context {
+            cluster: "does-not-exist".to_string(),
+            user: "user".to_string(),
+            namespace: None,
+            extensions: None,
+        }),
+    });
+
+    assert!(extract(&kubeconfig, "broken").is_err());
+    let split = split(&kubeconfig, None, None, false).unwrap();
+    assert_eq!(1, split.parts.len());
+    assert_eq!(1, split.failed.len());
+    assert_eq!("broken", split.failed[0].0);
+}
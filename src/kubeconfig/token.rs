@@ -0,0 +1,89 @@
+use crate::metadata::Timestamp;
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use kube::config::Kubeconfig;
+use secrecy::ExposeSecret;
+use std::time::{Duration, UNIX_EPOCH};
+
+/// Get the earliest expiry (`exp` claim) of all tokens in a kubeconfig.
+/// Only JWTs (e.g. service account tokens) carry an expiry, opaque tokens
+/// and JWTs without `exp` are skipped.
+pub fn get_token_expiry(kubeconfig: &Kubeconfig) -> Option<Timestamp> {
+    kubeconfig
+        .auth_infos
+        .iter()
+        .filter_map(|auth_info| auth_info.auth_info.as_ref())
+        .filter_map(|auth_info| auth_info.token.as_ref())
+        .filter_map(|token| jwt_expiry(token.expose_secret()))
+        .min()
+}
+
+fn jwt_expiry(token: &str) -> Option<Timestamp> {
+    let mut parts = token.trim().split('.');
+    let (Some(_), Some(payload), Some(_), None) =
+        (parts.next(), parts.next(), parts.next(), parts.next())
+    else {
+        return None;
+    };
+
+    let payload = URL_SAFE_NO_PAD.decode(payload.trim_end_matches('=')).ok()?;
+    let claims: serde_json::Value = serde_json::from_slice(&payload).ok()?;
+    let exp = claims.get("exp")?.as_u64()?;
+
+    Some(Timestamp(UNIX_EPOCH + Duration::from_secs(exp)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn jwt(claims: &str) -> String {
+        format!(
+            "{}.{}.signature",
+            URL_SAFE_NO_PAD.encode(r#"{"alg":"RS256"}"#),
+            URL_SAFE_NO_PAD.encode(claims)
+        )
+    }
+
+    #[test]
+    fn test_jwt_expiry() {
+        assert_eq!(
+            "2023-11-14T22:13:20Z",
+            jwt_expiry(&jwt(
+                r#"{"sub":"system:serviceaccount:default:kbs","exp":1700000000}"#
+            ))
+            .unwrap()
+            .to_string()
+        );
+        assert!(jwt_expiry(&jwt(r#"{"sub":"admin"}"#)).is_none());
+        assert!(jwt_expiry("opaque-token").is_none());
+        assert!(jwt_expiry("a.b.c").is_none());
+    }
+
+    #[test]
+    fn test_get_token_expiry() {
+        let kubeconfig: Kubeconfig = serde_yaml::from_str(&format!(
+            r#"
+apiVersion: v1
+kind: Config
+users:
+- name: opaque
+  user:
+    token: opaque-token
+- name: later
+  user:
+    token: {}
+- name: earlier
+  user:
+    token: {}
+"#,
+            jwt(r#"{"exp":1800000000}"#),
+            jwt(r#"{"exp":1700000000}"#)
+        ))
+        .unwrap();
+
+        assert_eq!(
+            "2023-11-14T22:13:20Z",
+            get_token_expiry(&kubeconfig).unwrap().to_string()
+        );
+    }
+}
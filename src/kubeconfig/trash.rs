@@ -0,0 +1,208 @@
+use super::credentials;
+use crate::metadata::{ConfigMetadata, Timestamp};
+use crate::{store, Error};
+use serde::{Deserialize, Serialize};
+use std::os::unix::fs::PermissionsExt;
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+use std::{fs, io};
+
+/// Directory in the data store holding removed kubeconfigs.
+const DIR: &str = ".trash";
+
+const ENTRY_FILE: &str = "entry.json";
+const KUBECONFIG_FILE: &str = "kubeconfig";
+const CREDENTIALS_FILE: &str = "credentials.json";
+
+/// A kubeconfig in the trash. Each entry is a directory in the trash
+/// holding the kubeconfig file, its credentials (if any) and this record.
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Entry {
+    pub name: String,
+    pub deleted_at: Timestamp,
+    /// Why the kubeconfig has been removed, e.g. the prune reasons.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+    /// Metadata of the kubeconfig at the time it was removed.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub metadata: Option<ConfigMetadata>,
+    #[serde(skip)]
+    path: PathBuf,
+}
+
+/// Move a kubeconfig (and its credentials) from the data store to the trash.
+/// Its metadata is kept in the trash as well, but has to be removed from the
+/// metadata store by the caller.
+pub fn put(
+    config_dir: &Path,
+    name: &str,
+    metadata: Option<&ConfigMetadata>,
+    reason: Option<String>,
+) -> Result<Entry, Error> {
+    let deleted_at = Timestamp::now();
+    let millis = deleted_at
+        .0
+        .duration_since(UNIX_EPOCH)
+        .map(|since| since.as_millis())
+        .unwrap_or_default();
+
+    let trash_dir = config_dir.join(DIR);
+    fs::create_dir_all(&trash_dir)?;
+    fs::set_permissions(&trash_dir, fs::Permissions::from_mode(0o700))?;
+
+    let entry = Entry {
+        name: name.to_string(),
+        deleted_at,
+        reason,
+        metadata: metadata.cloned(),
+        path: trash_dir.join(format!("{millis}-{name}")),
+    };
+
+    // the record is written last, so the trash only lists entries with a
+    // kubeconfig to restore.
+    fs::create_dir(&entry.path)?;
+
+    if let Err(err) = fill(config_dir, &entry) {
+        // move everything back, so the kubeconfig stays in the data store.
+        for (file, path) in sources(config_dir, name) {
+            if entry.path.join(file).exists() {
+                fs::rename(entry.path.join(file), path)?;
+            }
+        }
+        fs::remove_dir_all(&entry.path)?;
+
+        return Err(err);
+    }
+
+    Ok(entry)
+}
+
+/// List all kubeconfigs in the trash, most recently removed first.
+pub fn list(config_dir: &Path) -> Result<Vec<Entry>, Error> {
+    let dirs = match fs::read_dir(config_dir.join(DIR)) {
+        Ok(dirs) => dirs,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(vec![]),
+        Err(err) => return Err(Error::IO(err)),
+    };
+
+    let mut entries = vec![];
+
+    for dir in dirs {
+        let path = dir?.path();
+
+        let data = match fs::read(path.join(ENTRY_FILE)) {
+            Ok(data) => data,
+            Err(err) => {
+                log::warn!("skipping {} in trash: {err}", path.display());
+                continue;
+            }
+        };
+
+        match serde_json::from_slice::<Entry>(&data) {
+            Ok(entry) => entries.push(Entry { path, ..entry }),
+            Err(err) => log::warn!("skipping {} in trash: {err}", path.display()),
+        }
+    }
+
+    // entries are named after the time of removal in milliseconds, which is
+    // more precise than the recorded timestamp.
+    entries.sort_by(|a, b| (b.deleted_at, &b.path).cmp(&(a.deleted_at, &a.path)));
+
+    Ok(entries)
+}
+
+/// Move the most recently removed kubeconfig with the given name from the
+/// trash back to the data store. The returned entry holds the metadata that
+/// has to be restored by the caller.
+pub fn restore(config_dir: &Path, name: &str) -> Result<Entry, Error> {
+    let entry = list(config_dir)?
+        .into_iter()
+        .find(|entry| entry.name == name)
+        .ok_or_else(|| Error::Message(format!("no kubeconfig named {name} in trash")))?;
+
+    let kubeconfig_path = super::get_path(config_dir, name);
+    if kubeconfig_path.exists() {
+        return Err(Error::Message(format!(
+            "kubeconfig {name} exists in the data store already"
+        )));
+    }
+
+    let credentials_path = entry.path.join(CREDENTIALS_FILE);
+    if credentials_path.exists() {
+        credentials::create_dir(config_dir)?;
+        fs::rename(&credentials_path, credentials::path(config_dir, name))?;
+    }
+
+    fs::rename(entry.path.join(KUBECONFIG_FILE), &kubeconfig_path)?;
+    delete(&entry)?;
+
+    Ok(entry)
+}
+
+/// Permanently delete an entry from the trash.
+pub fn delete(entry: &Entry) -> Result<(), Error> {
+    Ok(fs::remove_dir_all(&entry.path)?)
+}
+
+// Move the files of a kubeconfig into the directory of entry and write the
+// record of it.
+fn fill(config_dir: &Path, entry: &Entry) -> Result<(), Error> {
+    for (file, path) in sources(config_dir, &entry.name) {
+        if file == KUBECONFIG_FILE || path.exists() {
+            fs::rename(path, entry.path.join(file))?;
+        }
+    }
+
+    store::write(&entry.path.join(ENTRY_FILE), &serde_json::to_vec(entry)?)
+}
+
+// Files of a kubeconfig in an entry and their paths in the data store.
+fn sources(config_dir: &Path, name: &str) -> [(&'static str, PathBuf); 2] {
+    [
+        (KUBECONFIG_FILE, super::get_path(config_dir, name)),
+        (CREDENTIALS_FILE, credentials::path(config_dir, name)),
+    ]
+}
+
+/// Get the paths of all kubeconfig and credential files in the trash.
+pub fn files(config_dir: &Path) -> Result<Vec<PathBuf>, Error> {
+    Ok(list(config_dir)?
+        .iter()
+        .flat_map(|entry| {
+            [KUBECONFIG_FILE, CREDENTIALS_FILE]
+                .into_iter()
+                .map(|file| entry.path.join(file))
+        })
+        .filter(|path| path.is_file())
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_put_failure_leaves_no_entry() {
+        let dir = tempdir().unwrap();
+
+        assert!(put(dir.path(), "missing", None, None).is_err());
+        assert_eq!(0, fs::read_dir(dir.path().join(DIR)).unwrap().count());
+    }
+
+    #[test]
+    fn test_list_skips_corrupt_entries() {
+        let dir = tempdir().unwrap();
+        fs::write(dir.path().join("test.kubeconfig"), "apiVersion: v1").unwrap();
+        put(dir.path(), "test", None, Some("removed".to_string())).unwrap();
+
+        let corrupt = dir.path().join(DIR).join("0-corrupt");
+        fs::create_dir(&corrupt).unwrap();
+        fs::write(corrupt.join(ENTRY_FILE), "{").unwrap();
+
+        let entries = list(dir.path()).unwrap();
+        assert_eq!(1, entries.len());
+        assert_eq!("test", entries[0].name);
+    }
+}
@@ -3,8 +3,10 @@ use anyhow::Result;
 mod cmd;
 mod config;
 mod error;
+mod fetch;
 mod kubeconfig;
 mod metadata;
+mod store;
 
 pub use error::Error;
 
@@ -68,34 +68,27 @@ pub fn merge(
     new_labels: &[Label],
     overwrite: bool,
 ) -> Result<BTreeMap<String, String>, Error> {
-    match &metadata.labels {
-        Some(existing_labels) => {
-            let mut merged_labels = existing_labels.clone();
-
-            for label in new_labels.iter() {
-                if let Some(new_val) = &label.value {
-                    if let Some(old_val) =
-                        merged_labels.insert(label.key.clone(), new_val.to_owned())
-                    {
-                        if !old_val.eq(new_val) && !overwrite {
-                            return Err(Error::Message(format!(
-                                "cannot set key '{}' to value '{}', is '{}' already",
-                                label.key, new_val, old_val
-                            )));
-                        }
-                    }
-                } else {
-                    // the label had value set to None, which means
-                    // we want to remove the label from the merged
-                    // map.
-                    merged_labels.remove(&label.key);
+    let mut merged_labels = metadata.labels.clone();
+
+    for label in new_labels.iter() {
+        if let Some(new_val) = &label.value {
+            if let Some(old_val) = merged_labels.insert(label.key.clone(), new_val.to_owned()) {
+                if !old_val.eq(new_val) && !overwrite {
+                    return Err(Error::Message(format!(
+                        "cannot set key '{}' to value '{}', is '{}' already",
+                        label.key, new_val, old_val
+                    )));
                 }
             }
-
-            Ok(merged_labels)
+        } else {
+            // the label had value set to None, which means
+            // we want to remove the label from the merged
+            // map.
+            merged_labels.remove(&label.key);
         }
-        None => Ok(to_map(new_labels)),
     }
+
+    Ok(merged_labels)
 }
 
 // Ensure that a given label key or value is compliant with RFC 1123
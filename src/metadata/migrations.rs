@@ -0,0 +1,135 @@
+use super::VERSION;
+use crate::Error;
+use serde_json::{Map, Value};
+
+/// A single step upgrading raw metadata from one schema version to the next.
+pub struct Migration {
+    pub from: &'static str,
+    pub to: &'static str,
+    pub description: &'static str,
+    migrate: fn(Value) -> Result<Value, Error>,
+}
+
+/// All known migrations, ordered by version. A migration's `to` version needs
+/// to match the `from` version of the next one, the last one has to end at
+/// the current [`VERSION`].
+const MIGRATIONS: &[Migration] = &[Migration {
+    from: "0.1",
+    to: "0.2",
+    description: "make labels a required (possibly empty) map",
+    migrate: v0_1_to_v0_2,
+}];
+
+/// Get the schema version of raw metadata.
+pub fn version(raw: &Value) -> Result<String, Error> {
+    raw.get("version")
+        .and_then(|version| version.as_str())
+        .map(|version| version.to_string())
+        .ok_or_else(|| Error::Message("metadata has no version".to_string()))
+}
+
+/// Get the list of migrations required to upgrade metadata from the given
+/// version to the current one. Fails for unknown versions.
+pub fn plan(version: &str) -> Result<Vec<&'static Migration>, Error> {
+    let mut steps = vec![];
+    let mut current = version;
+
+    while current != VERSION {
+        let migration = MIGRATIONS
+            .iter()
+            .find(|migration| migration.from == current)
+            .ok_or_else(|| Error::Message(format!("unknown metadata version: {version}")))?;
+
+        steps.push(migration);
+        current = migration.to;
+    }
+
+    Ok(steps)
+}
+
+/// Upgrade raw metadata step-by-step to the current schema version.
+pub fn migrate(raw: Value) -> Result<Value, Error> {
+    let mut raw = raw;
+
+    for migration in plan(&version(&raw)?)? {
+        log::debug!(
+            "migrating metadata from version {} to {}",
+            migration.from,
+            migration.to
+        );
+
+        raw = (migration.migrate)(raw)?;
+        raw["version"] = Value::String(migration.to.to_string());
+    }
+
+    Ok(raw)
+}
+
+fn kubeconfigs_mut(raw: &mut Value) -> Result<&mut Map<String, Value>, Error> {
+    raw.get_mut("kubeconfigs")
+        .and_then(|kubeconfigs| kubeconfigs.as_object_mut())
+        .ok_or_else(|| Error::Message("metadata has no kubeconfigs".to_string()))
+}
+
+// 0.1 allowed `labels` to be null or missing.
+fn v0_1_to_v0_2(mut raw: Value) -> Result<Value, Error> {
+    for (name, config) in kubeconfigs_mut(&mut raw)?.iter_mut() {
+        let config = config
+            .as_object_mut()
+            .ok_or_else(|| Error::Message(format!("invalid metadata for {name}")))?;
+
+        if config.get("labels").is_none_or(|labels| labels.is_null()) {
+            config.insert("labels".to_string(), Value::Object(Map::new()));
+        }
+    }
+
+    Ok(raw)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::metadata::Metadata;
+
+    const FIXTURE_0_1: &str = include_str!("../../tests/files/metadata/v0.1.json");
+
+    #[test]
+    fn test_migrations_are_consecutive() {
+        for pair in MIGRATIONS.windows(2) {
+            assert_eq!(pair[0].to, pair[1].from);
+        }
+
+        assert_eq!(MIGRATIONS.last().unwrap().to, VERSION);
+    }
+
+    #[test]
+    fn test_plan() {
+        assert!(plan(VERSION).unwrap().is_empty());
+        assert_eq!(plan("0.1").unwrap().len(), 1);
+        assert!(plan("0.0").is_err());
+        assert!(plan("99.0").is_err());
+    }
+
+    #[test]
+    fn test_migrate_v0_1() {
+        let raw: Value = serde_json::from_str(FIXTURE_0_1).unwrap();
+        let metadata: Metadata = serde_json::from_value(migrate(raw).unwrap()).unwrap();
+
+        assert_eq!(metadata.version, VERSION);
+        assert_eq!(metadata.kubeconfigs.len(), 3);
+        assert_eq!(
+            metadata
+                .get("kubernetes.embik.me")
+                .unwrap()
+                .labels
+                .get("owner"),
+            Some(&"embik".to_string())
+        );
+        assert!(metadata.get("localhost").unwrap().labels.is_empty());
+        assert!(metadata
+            .get("kubernetes.beckers.dev")
+            .unwrap()
+            .labels
+            .is_empty());
+    }
+}
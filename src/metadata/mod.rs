@@ -1,16 +1,21 @@
-use crate::{error, Error};
+use crate::{error, fetch, store, Error};
 use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
 use std::collections::btree_map::BTreeMap;
-use std::{fs::File, path::Path, path::PathBuf};
+use std::{fs, fs::File, path::Path, path::PathBuf};
 
 pub mod labels;
+pub mod migrations;
 pub mod selectors;
+mod time;
+
+pub use time::Timestamp;
 
 pub use selectors::Selector;
 
 pub const FILE: &str = "metadata.json";
 
-const VERSION: &str = "0.1";
+pub const VERSION: &str = "0.2";
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct Metadata {
@@ -26,37 +31,40 @@ impl Metadata {
         }
     }
 
+    /// Load metadata from file. Metadata in an older schema version is
+    /// migrated in memory, the file is only updated by [`Metadata::write`].
     pub fn from_file(file: &Path) -> Result<Metadata, Error> {
-        let metadata_file = match File::open(file) {
-            Ok(file) => file,
-            Err(err) => return Err(Error::IO(err)),
-        };
-
-        let metadata = match serde_json::from_reader::<File, Metadata>(metadata_file) {
-            Ok(metadata) => metadata,
-            Err(err) => return Err(Error::JSON(err)),
-        };
+        let raw = read_raw(file)?;
+        let version = migrations::version(&raw)?;
 
-        if metadata.version != VERSION {
-            return Err(Error::Message(format!(
-                "unknown metadata version: {}",
-                metadata.version
-            )));
+        if version == VERSION {
+            return Ok(serde_json::from_value::<Metadata>(raw)?);
         }
 
-        Ok(metadata)
+        Ok(serde_json::from_value::<Metadata>(migrations::migrate(
+            raw,
+        )?)?)
     }
 
+    /// Write metadata to file. If the file holds metadata in an older schema
+    /// version, the original is backed up before it is overwritten.
     pub fn write(&self, file: &Path) -> Result<(), Error> {
-        let metadata_file = match File::create(file) {
-            Ok(file) => file,
-            Err(_) => std::fs::OpenOptions::new().write(true).open(file)?,
+        let data = match serde_json::to_vec::<Metadata>(self) {
+            Ok(data) => data,
+            Err(err) => return Err(Error::JSON(err)),
         };
 
-        match serde_json::to_writer::<File, Metadata>(metadata_file, self) {
-            Ok(_) => Ok(()),
-            Err(err) => Err(Error::JSON(err)),
+        let backup = backup(file)?;
+        store::write(file, &data)?;
+
+        if let Some((version, backup_path)) = backup {
+            log::info!(
+                "migrated metadata from version {version} to {VERSION}, original saved to {}",
+                backup_path.display()
+            );
         }
+
+        Ok(())
     }
 
     pub fn get(&self, name: &str) -> Option<&ConfigMetadata> {
@@ -70,6 +78,49 @@ impl Metadata {
         self
     }
 
+    /// Record a freshly imported kubeconfig. If the name is known already
+    /// (because an existing kubeconfig was overwritten), its labels are kept
+    /// and the new labels are merged into them.
+    pub fn set_imported(
+        self,
+        name: String,
+        new_labels: &[labels::Label],
+        source: SourceRecord,
+        expiry: Expiry,
+    ) -> Result<Self, Error> {
+        let (labels, last_used) = match self.get(&name) {
+            Some(existing) => (
+                labels::merge(existing, new_labels, true)?,
+                existing.last_used,
+            ),
+            None => (labels::to_map(new_labels), None),
+        };
+
+        Ok(self.set(
+            name,
+            ConfigMetadata {
+                labels,
+                source: Some(source),
+                last_used,
+                expiry: Some(expiry),
+            },
+        ))
+    }
+
+    /// Record that a kubeconfig has been used just now.
+    pub fn set_used(mut self, name: &str) -> Self {
+        let map = &mut self.kubeconfigs;
+        map.entry(name.to_string())
+            .or_insert_with(|| ConfigMetadata {
+                labels: BTreeMap::new(),
+                source: None,
+                last_used: None,
+                expiry: None,
+            })
+            .last_used = Some(Timestamp::now());
+        self
+    }
+
     pub fn remove(mut self, name: &str) -> Self {
         let map = &mut self.kubeconfigs;
         map.remove(name);
@@ -93,11 +144,109 @@ impl Metadata {
     }
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Debug, Default, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
 pub struct ConfigMetadata {
-    pub labels: Option<BTreeMap<String, String>>,
+    #[serde(default)]
+    pub labels: BTreeMap<String, String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source: Option<SourceRecord>,
+    /// Last time the kubeconfig was activated with `kbs use`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_used: Option<Timestamp>,
+    /// Expiry of the certificates in the kubeconfig, recorded on import.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expiry: Option<Expiry>,
+}
+
+/// Expiry of the certificates (notAfter) and tokens (`exp`) in a kubeconfig.
+#[derive(Debug, Default, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct Expiry {
+    /// Earliest expiry of all client certificates.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub client_cert: Option<Timestamp>,
+    /// Earliest expiry of all certificate authorities.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ca: Option<Timestamp>,
+    /// Earliest expiry of all tokens that are JWTs.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub token: Option<Timestamp>,
+}
+
+/// Provenance of an imported kubeconfig, used to re-import it later on.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct SourceRecord {
+    pub origin: fetch::Source,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub imported_at: Option<Timestamp>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hash: Option<String>,
+    /// Context the kubeconfig was split from if the source holds multiple contexts.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub context: Option<String>,
+}
+
+impl SourceRecord {
+    /// Create a record for a kubeconfig imported right now from source
+    /// with the given (raw) content.
+    pub fn new(origin: fetch::Source, content: &[u8]) -> SourceRecord {
+        SourceRecord {
+            origin,
+            imported_at: Some(Timestamp::now()),
+            hash: Some(hash(content)),
+            context: None,
+        }
+    }
+
+    /// Mark the record as split from the given context of the source.
+    pub fn split_from(mut self, context: String) -> SourceRecord {
+        self.context = Some(context);
+        self
+    }
+}
+
+/// Calculate the content hash recorded for an imported kubeconfig.
+pub fn hash(content: &[u8]) -> String {
+    format!("sha256:{:x}", Sha256::digest(content))
 }
 
 pub fn file_path(config_dir: &Path) -> PathBuf {
     config_dir.join(FILE)
 }
+
+/// Read metadata from file without interpreting it in any schema version.
+pub fn read_raw(file: &Path) -> Result<serde_json::Value, Error> {
+    let metadata_file = File::open(file)?;
+
+    match serde_json::from_reader::<File, serde_json::Value>(metadata_file) {
+        Ok(raw) => Ok(raw),
+        Err(err) => Err(Error::JSON(err)),
+    }
+}
+
+// Back up the metadata file if it is in an older schema version. Files that
+// cannot be read or have no version are not backed up, loading them has
+// failed before already.
+fn backup(file: &Path) -> Result<Option<(String, PathBuf)>, Error> {
+    let Ok(version) = read_raw(file).and_then(|raw| migrations::version(&raw)) else {
+        return Ok(None);
+    };
+
+    if version == VERSION {
+        return Ok(None);
+    }
+
+    let backup_path = backup_path(file, &version);
+    store::write(&backup_path, &fs::read(file)?)?;
+
+    Ok(Some((version, backup_path)))
+}
+
+/// Path the metadata file is backed up to before migrating it from the given version.
+pub fn backup_path(file: &Path, version: &str) -> PathBuf {
+    let mut path = file.as_os_str().to_owned();
+    path.push(format!(".v{version}.bak"));
+    PathBuf::from(path)
+}
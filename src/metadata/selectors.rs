@@ -2,37 +2,44 @@ use super::labels;
 use crate::Error;
 use clap::ArgMatches;
 use std::collections::btree_map::BTreeMap;
+use std::iter::Peekable;
+use std::vec::IntoIter;
 
-#[derive(Clone, PartialEq)]
+#[derive(Clone, Debug, PartialEq)]
 pub enum Operation {
     Equal,
     NotEqual,
+    In,
+    NotIn,
+    Exists,
+    DoesNotExist,
 }
 
-#[derive(Clone)]
+#[derive(Clone, Debug, PartialEq)]
 pub struct Selector {
     pub key: String,
-    pub value: String,
+    pub values: Vec<String>,
     pub op: Operation,
 }
 
-pub fn matches(selectors: &[Selector], labels: &BTreeMap<String, String>) -> bool {
-    for selector in selectors.iter() {
-        let opt = labels.get(&selector.key);
-        if let Some(value) = opt {
-            if (selector.op == Operation::Equal && &selector.value != value)
-                || (selector.op == Operation::NotEqual && &selector.value == value)
-            {
-                return false;
+impl Selector {
+    fn matches(&self, labels: &BTreeMap<String, String>) -> bool {
+        let value = labels.get(&self.key);
+
+        match self.op {
+            Operation::Equal | Operation::In => value.is_some_and(|v| self.values.contains(v)),
+            Operation::NotEqual | Operation::NotIn => {
+                value.is_none_or(|v| !self.values.contains(v))
             }
-        } else if selector.op == Operation::Equal {
-            // if the label doesn't exist, we only have to return "false"
-            // if the operation was an equal check. For NotEqual we're good.
-            return false;
+            Operation::Exists => value.is_some(),
+            Operation::DoesNotExist => value.is_none(),
         }
     }
+}
 
-    true
+/// Check if labels match all selectors.
+pub fn matches(selectors: &[Selector], labels: &BTreeMap<String, String>) -> bool {
+    selectors.iter().all(|selector| selector.matches(labels))
 }
 
 pub fn from_args(matches: &ArgMatches, id: &str) -> Result<Vec<Selector>, Error> {
@@ -40,45 +47,172 @@ pub fn from_args(matches: &ArgMatches, id: &str) -> Result<Vec<Selector>, Error>
         return Ok(vec![]);
     }
 
-    let labels = matches
-        .get_many::<Selector>(id)
-        .ok_or_else(|| Error::Message("failed to parse labels from argument".to_string()))?
-        .map(|l| l.to_owned())
+    let selectors = matches
+        .get_many::<Vec<Selector>>(id)
+        .ok_or_else(|| Error::Message("failed to parse selectors from argument".to_string()))?
+        .flatten()
+        .cloned()
         .collect::<Vec<Selector>>();
 
-    Ok(labels)
+    Ok(selectors)
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Token {
+    Identifier(String),
+    Comma,
+    OpenParen,
+    CloseParen,
+    Bang,
+    Equal,
+    NotEqual,
 }
 
-// Parse a single selector from string
-pub fn parse(s: &str) -> Result<Selector, Error> {
-    let pos = s
-        .find('=')
-        .ok_or_else(|| Error::Message(format!("invalid selector: no `=` found in `{s}`")))?;
-    let mut key_end = pos;
-    let mut op = Operation::Equal;
-
-    // check if '!' is in the string and right before the '='
-    if let Some(pos_bang) = s.find('!') {
-        if pos_bang == pos - 1 {
-            op = Operation::NotEqual;
-            key_end = pos - 1;
+// Split a selector string into tokens. Keywords (`in`, `notin`) are returned
+// as identifiers, the parser decides from their position if they are keywords.
+fn tokenize(s: &str) -> Result<Vec<Token>, Error> {
+    let mut tokens = vec![];
+    let mut chars = s.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            c if c.is_whitespace() => {}
+            ',' => tokens.push(Token::Comma),
+            '(' => tokens.push(Token::OpenParen),
+            ')' => tokens.push(Token::CloseParen),
+            '!' if chars.peek() == Some(&'=') => {
+                chars.next();
+                tokens.push(Token::NotEqual);
+            }
+            '!' => tokens.push(Token::Bang),
+            '=' => {
+                // `==` is a synonym for `=`.
+                if chars.peek() == Some(&'=') {
+                    chars.next();
+                }
+                tokens.push(Token::Equal);
+            }
+            c => {
+                let mut identifier = c.to_string();
+                while let Some(&c) = chars.peek() {
+                    if c.is_whitespace() || ",()!=".contains(c) {
+                        break;
+                    }
+                    identifier.push(c);
+                    chars.next();
+                }
+                tokens.push(Token::Identifier(identifier));
+            }
         }
     }
 
-    let key = &s[..key_end];
-    let value = &s[pos + 1..];
+    Ok(tokens)
+}
+
+/// Parse a selector (label query) from string. Supports the Kubernetes label
+/// selector syntax, i.e. a comma-separated list of requirements:
+///
+/// - `key=value`, `key==value` and `key!=value`
+/// - `key in (value1,value2)` and `key notin (value1,value2)`
+/// - `key` (label exists) and `!key` (label does not exist)
+pub fn parse(s: &str) -> Result<Vec<Selector>, Error> {
+    let mut tokens = tokenize(s)?.into_iter().peekable();
+    let mut selectors = vec![];
+
+    loop {
+        selectors.push(parse_requirement(&mut tokens, s)?);
 
-    if !labels::is_valid_label_key(key) || !labels::is_valid_label_value(value) {
-        return Err(Error::Message(
-            "key or value are not valid RFC 1123 dns-style".to_string(),
-        ));
+        match tokens.next() {
+            None => break,
+            Some(Token::Comma) => continue,
+            Some(token) => {
+                return Err(Error::Message(format!(
+                    "invalid selector `{s}`: expected `,` but found {token:?}"
+                )))
+            }
+        }
     }
 
-    Ok(Selector {
-        key: key.to_string(),
-        value: value.to_string(),
-        op,
-    })
+    Ok(selectors)
+}
+
+fn parse_requirement(tokens: &mut Peekable<IntoIter<Token>>, s: &str) -> Result<Selector, Error> {
+    let invalid = |msg: &str| Error::Message(format!("invalid selector `{s}`: {msg}"));
+
+    if tokens.next_if_eq(&Token::Bang).is_some() {
+        let key = parse_key(tokens, s)?;
+        return Ok(Selector {
+            key,
+            values: vec![],
+            op: Operation::DoesNotExist,
+        });
+    }
+
+    let key = parse_key(tokens, s)?;
+
+    let op = match tokens.peek() {
+        None | Some(Token::Comma) => {
+            return Ok(Selector {
+                key,
+                values: vec![],
+                op: Operation::Exists,
+            })
+        }
+        Some(Token::Equal) => Operation::Equal,
+        Some(Token::NotEqual) => Operation::NotEqual,
+        Some(Token::Identifier(keyword)) if keyword == "in" => Operation::In,
+        Some(Token::Identifier(keyword)) if keyword == "notin" => Operation::NotIn,
+        Some(token) => return Err(invalid(&format!("unexpected {token:?} after key `{key}`"))),
+    };
+    tokens.next();
+
+    let values = match op {
+        Operation::In | Operation::NotIn => {
+            if tokens.next() != Some(Token::OpenParen) {
+                return Err(invalid("expected `(` after set operator"));
+            }
+
+            let mut values = vec![];
+            loop {
+                values.push(parse_value(tokens, s)?);
+
+                match tokens.next() {
+                    Some(Token::Comma) => continue,
+                    Some(Token::CloseParen) => break,
+                    _ => return Err(invalid("expected `,` or `)` in value set")),
+                }
+            }
+
+            values
+        }
+        _ => vec![parse_value(tokens, s)?],
+    };
+
+    Ok(Selector { key, values, op })
+}
+
+fn parse_key(tokens: &mut Peekable<IntoIter<Token>>, s: &str) -> Result<String, Error> {
+    match tokens.next() {
+        Some(Token::Identifier(key)) if labels::is_valid_label_key(&key) => Ok(key),
+        Some(Token::Identifier(key)) => Err(Error::Message(format!(
+            "invalid selector `{s}`: key `{key}` is not valid RFC 1123 dns-style"
+        ))),
+        _ => Err(Error::Message(format!(
+            "invalid selector `{s}`: expected key"
+        ))),
+    }
+}
+
+fn parse_value(tokens: &mut Peekable<IntoIter<Token>>, s: &str) -> Result<String, Error> {
+    match tokens.next() {
+        Some(Token::Identifier(value)) if labels::is_valid_label_value(&value) => Ok(value),
+        Some(Token::Identifier(value)) => Err(Error::Message(format!(
+            "invalid selector `{s}`: value `{value}` is not valid RFC 1123 dns-style"
+        ))),
+        _ => Err(Error::Message(format!(
+            "invalid selector `{s}`: expected value"
+        ))),
+    }
 }
 
 #[cfg(test)]
@@ -87,12 +221,59 @@ mod tests {
     use crate::metadata;
     use crate::metadata::labels::Label;
 
+    fn selector(key: &str, values: &[&str], op: Operation) -> Selector {
+        Selector {
+            key: key.to_string(),
+            values: values.iter().map(|v| v.to_string()).collect(),
+            op,
+        }
+    }
+
     #[test]
     fn test_parse() {
-        for selector in &["test=test", "test!=test.com", "test=test.com"] {
+        for selector in &["test=test", "test!=test.com", "test=test.com", "test==test"] {
             let s = parse(selector);
             assert!(s.is_ok());
-            assert_eq!(s.unwrap().key, "test");
+            assert_eq!(s.unwrap()[0].key, "test");
+        }
+    }
+
+    #[test]
+    fn test_parse_set_based() {
+        assert_eq!(
+            parse("env in (prod, staging),team,!legacy,tier notin (dev),owner!=embik").unwrap(),
+            vec![
+                selector("env", &["prod", "staging"], Operation::In),
+                selector("team", &[], Operation::Exists),
+                selector("legacy", &[], Operation::DoesNotExist),
+                selector("tier", &["dev"], Operation::NotIn),
+                selector("owner", &["embik"], Operation::NotEqual),
+            ]
+        );
+
+        // keywords are valid keys.
+        assert_eq!(
+            parse("in in (in)").unwrap(),
+            vec![selector("in", &["in"], Operation::In)]
+        );
+    }
+
+    #[test]
+    fn test_parse_invalid() {
+        for selector in &[
+            "",
+            "env in prod",
+            "env in (prod",
+            "env in ()",
+            "env=",
+            "=prod",
+            "env=prod,",
+            "env prod",
+            "Env=prod",
+            "env=Prod",
+            "!env=prod",
+        ] {
+            assert!(parse(selector).is_err(), "{selector} should be invalid");
         }
     }
 
@@ -100,44 +281,76 @@ mod tests {
     fn test_matches() {
         let tests: Vec<(Vec<Selector>, Vec<Label>, bool)> = vec![
             (
-                vec![Selector {
-                    key: "key".to_string(),
-                    value: "val".to_string(),
-                    op: Operation::Equal,
-                }],
+                vec![selector("key", &["val"], Operation::Equal)],
                 vec![],
                 false,
             ),
             (
-                vec![Selector {
+                vec![selector("key", &["val"], Operation::Equal)],
+                vec![Label {
                     key: "key".to_string(),
-                    value: "val".to_string(),
-                    op: Operation::Equal,
+                    value: Some("val".to_string()),
                 }],
+                true,
+            ),
+            (
+                vec![selector("key", &["val"], Operation::NotEqual)],
                 vec![Label {
                     key: "key".to_string(),
                     value: Some("val".to_string()),
                 }],
-                true,
+                false,
             ),
             (
-                vec![Selector {
+                vec![selector("key", &["a", "b"], Operation::In)],
+                vec![Label {
                     key: "key".to_string(),
-                    value: "val".to_string(),
-                    op: Operation::NotEqual,
+                    value: Some("b".to_string()),
                 }],
+                true,
+            ),
+            (
+                vec![selector("key", &["a", "b"], Operation::NotIn)],
                 vec![Label {
                     key: "key".to_string(),
-                    value: Some("val".to_string()),
+                    value: Some("b".to_string()),
                 }],
                 false,
             ),
+            (
+                vec![selector("key", &["a", "b"], Operation::NotIn)],
+                vec![],
+                true,
+            ),
+            (vec![selector("key", &[], Operation::Exists)], vec![], false),
+            (
+                vec![selector("key", &[], Operation::DoesNotExist)],
+                vec![],
+                true,
+            ),
+            (
+                vec![
+                    selector("key", &[], Operation::Exists),
+                    selector("other", &[], Operation::DoesNotExist),
+                ],
+                vec![
+                    Label {
+                        key: "key".to_string(),
+                        value: Some("val".to_string()),
+                    },
+                    Label {
+                        key: "other".to_string(),
+                        value: Some("val".to_string()),
+                    },
+                ],
+                false,
+            ),
         ];
 
         for test in tests.iter() {
             let (selectors, labels, expected) = test;
-            let labels_map = metadata::labels::to_map(&labels);
-            assert_eq!(matches(&selectors, &labels_map), expected.to_owned());
+            let labels_map = metadata::labels::to_map(labels);
+            assert_eq!(matches(selectors, &labels_map), expected.to_owned());
         }
     }
 }
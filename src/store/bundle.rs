@@ -0,0 +1,135 @@
+use super::crypt;
+use crate::metadata::{self, migrations, ConfigMetadata, Metadata};
+use crate::Error;
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::Read;
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Environment variable to read the bundle passphrase from instead of
+/// prompting. Separate from the data store passphrase, as bundles are
+/// usually passed on to others.
+const PASSPHRASE_ENV: &str = "KBS_BUNDLE_PASSPHRASE";
+
+const PASSPHRASE_PROMPT: &str = "Passphrase for the bundle: ";
+
+const KUBECONFIG_EXTENSION: &str = ".kubeconfig";
+
+/// Kubeconfigs and their metadata, packaged into a single file to move them
+/// to another data store. Bundles are tar archives compressed with zstd,
+/// optionally encrypted with a passphrase. Kubeconfigs in a bundle are not
+/// encrypted with the key of a data store, so they can be imported anywhere.
+#[derive(Debug)]
+pub struct Bundle {
+    pub metadata: Metadata,
+    pub kubeconfigs: BTreeMap<String, Vec<u8>>,
+}
+
+impl Bundle {
+    pub fn new() -> Bundle {
+        Bundle {
+            metadata: Metadata::new(),
+            kubeconfigs: BTreeMap::new(),
+        }
+    }
+
+    pub fn add(&mut self, name: &str, kubeconfig: Vec<u8>, metadata: Option<&ConfigMetadata>) {
+        self.kubeconfigs.insert(name.to_string(), kubeconfig);
+
+        if let Some(metadata) = metadata {
+            self.metadata
+                .kubeconfigs
+                .insert(name.to_string(), metadata.clone());
+        }
+    }
+
+    /// Write the bundle to path. With encrypt, the bundle is encrypted with a
+    /// passphrase read from `$KBS_BUNDLE_PASSPHRASE` or prompted for.
+    pub fn write(&self, path: &Path, encrypt: bool) -> Result<(), Error> {
+        let mtime = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|since| since.as_secs())
+            .unwrap_or_default();
+
+        let mut archive = tar::Builder::new(vec![]);
+
+        let files = std::iter::once((
+            metadata::FILE.to_string(),
+            serde_json::to_vec(&self.metadata)?,
+        ))
+        .chain(
+            self.kubeconfigs
+                .iter()
+                .map(|(name, data)| (format!("{name}{KUBECONFIG_EXTENSION}"), data.clone())),
+        );
+
+        for (file, data) in files {
+            let mut header = tar::Header::new_gnu();
+            header.set_size(data.len() as u64);
+            header.set_mode(0o600);
+            header.set_mtime(mtime);
+            header.set_cksum();
+            archive.append_data(&mut header, file, data.as_slice())?;
+        }
+
+        let data = zstd::encode_all(archive.into_inner()?.as_slice(), 0)?;
+
+        let data = match encrypt {
+            false => data,
+            true => {
+                let passphrase = crypt::passphrase(PASSPHRASE_ENV, PASSPHRASE_PROMPT, true)?;
+                age::encrypt(&age::scrypt::Recipient::new(passphrase), &data)
+                    .map_err(|err| Error::Message(format!("failed to encrypt bundle: {err}")))?
+            }
+        };
+
+        super::write(path, &data)
+    }
+
+    /// Read a bundle from path, decrypting it if it has been encrypted.
+    pub fn read(path: &Path) -> Result<Bundle, Error> {
+        let data = fs::read(path)?;
+
+        let data = match crypt::is_encrypted(&data) {
+            false => data,
+            true => {
+                let passphrase = crypt::passphrase(PASSPHRASE_ENV, PASSPHRASE_PROMPT, false)?;
+                age::decrypt(&age::scrypt::Identity::new(passphrase), &data).map_err(|err| {
+                    Error::Message(format!(
+                        "failed to decrypt bundle (is the passphrase correct?): {err}"
+                    ))
+                })?
+            }
+        };
+
+        let data = zstd::decode_all(data.as_slice()).map_err(|err| {
+            Error::Message(format!("{} is not a kbs bundle: {err}", path.display()))
+        })?;
+
+        let mut bundle = Bundle::new();
+        let mut archive = tar::Archive::new(data.as_slice());
+
+        for entry in archive.entries()? {
+            let mut entry = entry?;
+            let file = entry.path()?.to_string_lossy().to_string();
+
+            let mut data = vec![];
+            entry.read_to_end(&mut data)?;
+
+            if file == metadata::FILE {
+                let raw: serde_json::Value = serde_json::from_slice(&data)?;
+                bundle.metadata = serde_json::from_value(migrations::migrate(raw)?)?;
+            } else if let Some(name) = file
+                .strip_suffix(KUBECONFIG_EXTENSION)
+                .filter(|name| !name.is_empty() && !name.contains('/'))
+            {
+                bundle.kubeconfigs.insert(name.to_string(), data);
+            } else {
+                log::warn!("skipping unknown file {file} in bundle");
+            }
+        }
+
+        Ok(bundle)
+    }
+}
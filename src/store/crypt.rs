@@ -0,0 +1,313 @@
+use crate::Error;
+use age::secrecy::{ExposeSecret, SecretString};
+use age::x25519;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io::{self, Read};
+use std::os::unix::fs::{DirBuilderExt, MetadataExt, PermissionsExt};
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+pub(super) const FILE_NAME: &str = "encryption.toml";
+
+/// Identity generated for passphrase-based encryption, encrypted with the
+/// passphrase.
+const IDENTITY_FILE_NAME: &str = "identity.age";
+
+/// Environment variable to read the passphrase from instead of prompting.
+const PASSPHRASE_ENV: &str = "KBS_PASSPHRASE";
+
+const PASSPHRASE_PROMPT: &str = "Passphrase for the kbs data store: ";
+
+// Binary age files start with the version line.
+const AGE_HEADER: &[u8] = b"age-encryption.org/";
+
+// Decrypting the identity might require a passphrase, so it is only done
+// once per process.
+static IDENTITIES: OnceLock<Vec<x25519::Identity>> = OnceLock::new();
+
+/// Encryption settings of a data store. Kubeconfigs are encrypted with
+/// [age](https://age-encryption.org) to the recipient (public key), so
+/// writing kubeconfigs never requires the passphrase or identity.
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+pub struct Encryption {
+    /// age recipient kubeconfigs are encrypted to.
+    pub recipient: String,
+    /// age identity (key file) to decrypt kubeconfigs with. If not set, the
+    /// identity is stored in the data store, encrypted with a passphrase.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub identity_file: Option<PathBuf>,
+}
+
+impl Encryption {
+    /// Load the encryption settings of a data store. Returns None if the
+    /// data store is not encrypted.
+    pub fn load(config_dir: &Path) -> Result<Option<Encryption>, Error> {
+        let path = config_dir.join(FILE_NAME);
+
+        let content = match fs::read_to_string(&path) {
+            Ok(content) => content,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(None),
+            Err(err) => return Err(Error::IO(err)),
+        };
+
+        toml::from_str(&content)
+            .map(Some)
+            .map_err(|err| Error::Message(format!("failed to parse {}: {err}", path.display())))
+    }
+
+    /// Set up encryption for a data store. Kubeconfigs are encrypted to the
+    /// (first) identity in the identity file if one is passed. Otherwise, a
+    /// new identity is generated and stored in the data store, encrypted
+    /// with a passphrase.
+    pub fn init(config_dir: &Path, identity_file: Option<&Path>) -> Result<Encryption, Error> {
+        let encryption = match identity_file {
+            Some(identity_file) => {
+                let identity_file = fs::canonicalize(identity_file)?;
+                let identities = parse_identities(&read_identity_file(&identity_file)?)?;
+
+                Encryption {
+                    recipient: identities[0].to_public().to_string(),
+                    identity_file: Some(identity_file),
+                }
+            }
+            None => {
+                let passphrase = passphrase(PASSPHRASE_ENV, PASSPHRASE_PROMPT, true)?;
+                let identity = x25519::Identity::generate();
+
+                let recipient = age::scrypt::Recipient::new(passphrase);
+                let encrypted =
+                    age::encrypt(&recipient, identity.to_string().expose_secret().as_bytes())
+                        .map_err(|err| {
+                            Error::Message(format!("failed to encrypt identity: {err}"))
+                        })?;
+                super::write(&config_dir.join(IDENTITY_FILE_NAME), &encrypted)?;
+
+                Encryption {
+                    recipient: identity.to_public().to_string(),
+                    identity_file: None,
+                }
+            }
+        };
+
+        super::write(
+            &config_dir.join(FILE_NAME),
+            toml::to_string(&encryption)
+                .map_err(|err| Error::Message(format!("failed to serialize {FILE_NAME}: {err}")))?
+                .as_bytes(),
+        )?;
+
+        Ok(encryption)
+    }
+
+    /// Remove the encryption settings (and the generated identity) from a
+    /// data store.
+    pub fn remove(config_dir: &Path) -> Result<(), Error> {
+        for file in [FILE_NAME, IDENTITY_FILE_NAME] {
+            match fs::remove_file(config_dir.join(file)) {
+                Err(err) if err.kind() != io::ErrorKind::NotFound => return Err(Error::IO(err)),
+                _ => {}
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn encrypt(&self, plaintext: &[u8]) -> Result<Vec<u8>, Error> {
+        let recipient: x25519::Recipient = self.recipient.parse().map_err(|err| {
+            Error::Message(format!(
+                "invalid recipient {} in {FILE_NAME}: {err}",
+                self.recipient
+            ))
+        })?;
+
+        age::encrypt(&recipient, plaintext)
+            .map_err(|err| Error::Message(format!("failed to encrypt kubeconfig: {err}")))
+    }
+
+    pub fn decrypt(&self, config_dir: &Path, ciphertext: &[u8]) -> Result<Vec<u8>, Error> {
+        let identities = self.identities(config_dir)?;
+
+        let decryptor = age::Decryptor::new(ciphertext)
+            .map_err(|err| Error::Message(format!("failed to read encrypted kubeconfig: {err}")))?;
+        let mut reader = decryptor
+            .decrypt(
+                identities
+                    .iter()
+                    .map(|identity| identity as &dyn age::Identity),
+            )
+            .map_err(|err| Error::Message(format!("failed to decrypt kubeconfig: {err}")))?;
+
+        let mut plaintext = vec![];
+        reader.read_to_end(&mut plaintext)?;
+        Ok(plaintext)
+    }
+
+    fn identities(&self, config_dir: &Path) -> Result<&'static [x25519::Identity], Error> {
+        if let Some(identities) = IDENTITIES.get() {
+            return Ok(identities);
+        }
+
+        let content = match &self.identity_file {
+            Some(identity_file) => read_identity_file(identity_file)?,
+            None => {
+                let encrypted = fs::read(config_dir.join(IDENTITY_FILE_NAME))?;
+                let identity = age::decrypt(
+                    &age::scrypt::Identity::new(passphrase(
+                        PASSPHRASE_ENV,
+                        PASSPHRASE_PROMPT,
+                        false,
+                    )?),
+                    &encrypted,
+                )
+                .map_err(|err| {
+                    Error::Message(format!(
+                        "failed to decrypt identity (is the passphrase correct?): {err}"
+                    ))
+                })?;
+
+                SecretString::from(String::from_utf8(identity).map_err(|_| {
+                    Error::Message(format!("invalid identity in {IDENTITY_FILE_NAME}"))
+                })?)
+            }
+        };
+
+        let identities = parse_identities(&content)?;
+        Ok(IDENTITIES.get_or_init(|| identities))
+    }
+}
+
+/// Check if data has been encrypted with age.
+pub fn is_encrypted(data: &[u8]) -> bool {
+    data.starts_with(AGE_HEADER)
+}
+
+/// Write a decrypted kubeconfig to a file that only the current user can
+/// read, in `$XDG_RUNTIME_DIR/kbs` (usually a tmpfs that is cleared on
+/// logout) or a directory for the current user in the temporary directory if
+/// that is not set. Files are named after the kubeconfig, so every kubeconfig
+/// is decrypted to the same file each time instead of piling up.
+pub fn write_runtime(name: &str, contents: &[u8]) -> Result<PathBuf, Error> {
+    // SAFETY: getuid has no preconditions and cannot fail.
+    let uid = unsafe { libc::getuid() };
+
+    let dir = match std::env::var_os("XDG_RUNTIME_DIR") {
+        Some(dir) if !dir.is_empty() => PathBuf::from(dir).join("kbs"),
+        _ => {
+            log::debug!("XDG_RUNTIME_DIR is not set, using the temporary directory");
+            std::env::temp_dir().join(format!("kbs-{uid}"))
+        }
+    };
+
+    match fs::DirBuilder::new().mode(0o700).create(&dir) {
+        Err(err) if err.kind() != io::ErrorKind::AlreadyExists => return Err(Error::IO(err)),
+        _ => {}
+    }
+
+    // the temporary directory is shared with other users, who could have
+    // created the directory (or a symlink) before.
+    let metadata = fs::symlink_metadata(&dir)?;
+    if !metadata.is_dir() || metadata.uid() != uid {
+        return Err(Error::Message(format!(
+            "refusing to decrypt kubeconfig to {}, it is not a directory owned by the current user",
+            dir.display()
+        )));
+    }
+    fs::set_permissions(&dir, fs::Permissions::from_mode(0o700))?;
+
+    let path = dir.join(format!("{name}.kubeconfig"));
+    super::write(&path, contents)?;
+
+    Ok(path)
+}
+
+fn read_identity_file(path: &Path) -> Result<SecretString, Error> {
+    fs::read_to_string(path)
+        .map(SecretString::from)
+        .map_err(|err| {
+            Error::Message(format!(
+                "failed to read identity file {}: {err}",
+                path.display()
+            ))
+        })
+}
+
+// Parse identities in the format written by `age-keygen`, one per line.
+fn parse_identities(content: &SecretString) -> Result<Vec<x25519::Identity>, Error> {
+    let identities = content
+        .expose_secret()
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(|line| {
+            line.parse::<x25519::Identity>()
+                .map_err(|err| Error::Message(format!("invalid identity: {err}")))
+        })
+        .collect::<Result<Vec<x25519::Identity>, Error>>()?;
+
+    if identities.is_empty() {
+        return Err(Error::Message("no identity found".to_string()));
+    }
+
+    Ok(identities)
+}
+
+/// Read a passphrase from the environment variable env or prompt for it on
+/// the terminal.
+pub(super) fn passphrase(env: &str, prompt: &str, confirm: bool) -> Result<SecretString, Error> {
+    if let Some(passphrase) = std::env::var(env)
+        .ok()
+        .filter(|passphrase| !passphrase.is_empty())
+    {
+        return Ok(SecretString::from(passphrase));
+    }
+
+    let passphrase = rpassword::prompt_password(prompt)?;
+    if passphrase.is_empty() {
+        return Err(Error::Message("passphrase must not be empty".to_string()));
+    }
+
+    if confirm && rpassword::prompt_password("Confirm passphrase: ")? != passphrase {
+        return Err(Error::Message("passphrases do not match".to_string()));
+    }
+
+    Ok(SecretString::from(passphrase))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_encrypt_decrypt() {
+        let dir = tempdir().unwrap();
+        let identity = x25519::Identity::generate();
+        let identity_file = dir.path().join("key.txt");
+        fs::write(
+            &identity_file,
+            format!(
+                "# created: today\n# public key: {}\n{}\n",
+                identity.to_public(),
+                identity.to_string().expose_secret()
+            ),
+        )
+        .unwrap();
+
+        let encryption = Encryption::init(dir.path(), Some(&identity_file)).unwrap();
+        assert_eq!(identity.to_public().to_string(), encryption.recipient);
+        assert!(Encryption::load(dir.path()).unwrap().is_some());
+
+        let encrypted = encryption.encrypt(b"apiVersion: v1").unwrap();
+        assert!(is_encrypted(&encrypted));
+        assert!(!is_encrypted(b"apiVersion: v1"));
+        assert_eq!(
+            b"apiVersion: v1".to_vec(),
+            encryption.decrypt(dir.path(), &encrypted).unwrap()
+        );
+
+        Encryption::remove(dir.path()).unwrap();
+        assert!(Encryption::load(dir.path()).unwrap().is_none());
+    }
+}
@@ -0,0 +1,337 @@
+use super::crypt;
+use crate::metadata::{self, Timestamp};
+use crate::Error;
+use git2::{
+    build::CheckoutBuilder, Commit, Delta, ErrorCode, IndexAddOption, ObjectType, Repository,
+    Signature, Sort, Tree, TreeWalkMode, TreeWalkResult,
+};
+use std::collections::BTreeSet;
+use std::fs;
+use std::os::unix::fs::PermissionsExt;
+use std::path::Path;
+use std::time::{Duration, UNIX_EPOCH};
+
+/// Files in the data store that are not worth tracking. Removed kubeconfigs
+/// are in the history already, so the trash is not tracked either.
+const IGNORE: &str = ".lock\nactive\n.trash/\n";
+
+/// A commit in the history of the data store.
+#[derive(Debug)]
+pub struct Revision {
+    pub id: String,
+    pub time: Timestamp,
+    pub message: String,
+}
+
+// Changes between two revisions of the data store, by kubeconfig name.
+#[derive(Debug, Default)]
+struct Changes {
+    added: BTreeSet<String>,
+    removed: BTreeSet<String>,
+    updated: BTreeSet<String>,
+    // other files in the data store, e.g. the settings.
+    files: BTreeSet<String>,
+}
+
+/// Check if the data store is tracked in git.
+pub fn is_enabled(config_dir: &Path) -> bool {
+    config_dir.join(".git").is_dir()
+}
+
+/// Start tracking the data store in a new git repository and commit its
+/// current content.
+pub fn init(config_dir: &Path) -> Result<(), Error> {
+    if is_enabled(config_dir) {
+        return Err(Error::Message(
+            "data store is tracked in git already".to_string(),
+        ));
+    }
+
+    // the repository holds every revision of every kubeconfig, so it must
+    // not be readable by anyone else.
+    fs::set_permissions(config_dir, fs::Permissions::from_mode(0o700))?;
+    Repository::init(config_dir)?;
+    fs::set_permissions(config_dir.join(".git"), fs::Permissions::from_mode(0o700))?;
+    super::write(&config_dir.join(".gitignore"), IGNORE.as_bytes())?;
+    commit(config_dir, "track data store")?;
+
+    Ok(())
+}
+
+/// Commit all changes to the data store with a message describing which
+/// kubeconfigs have been changed by command. Returns the id of the new
+/// commit, or `None` if nothing changed.
+pub fn commit(config_dir: &Path, command: &str) -> Result<Option<String>, Error> {
+    let repo = open(config_dir)?;
+
+    let mut index = repo.index()?;
+    index.add_all(["*"], IndexAddOption::DEFAULT, None)?;
+    index.update_all(["*"], None)?;
+    index.write()?;
+    let tree = repo.find_tree(index.write_tree()?)?;
+
+    let parent = head(&repo)?;
+    if parent
+        .as_ref()
+        .is_some_and(|parent| parent.tree_id() == tree.id())
+    {
+        log::debug!("no changes to commit to the data store");
+        return Ok(None);
+    }
+
+    let parent_tree = parent.as_ref().map(Commit::tree).transpose()?;
+    let message = format!(
+        "{command}: {}",
+        changes(&repo, parent_tree.as_ref(), &tree)?
+    );
+
+    let signature = match repo.signature() {
+        Ok(signature) => signature,
+        Err(_) => Signature::now("kbs", "kbs@localhost")?,
+    };
+
+    let parents: Vec<&Commit> = parent.iter().collect();
+    let id = repo.commit(
+        Some("HEAD"),
+        &signature,
+        &signature,
+        &message,
+        &tree,
+        &parents,
+    )?;
+
+    log::debug!("committed changes to the data store as {id}: {message}");
+
+    Ok(Some(short_id(&repo, id)?))
+}
+
+/// Get the history of the data store, newest first. With name, only
+/// revisions that changed that kubeconfig are returned.
+pub fn history(config_dir: &Path, name: Option<&str>) -> Result<Vec<Revision>, Error> {
+    let repo = open(config_dir)?;
+
+    if head(&repo)?.is_none() {
+        return Ok(vec![]);
+    }
+
+    let mut walk = repo.revwalk()?;
+    walk.set_sorting(Sort::TOPOLOGICAL | Sort::TIME)?;
+    walk.push_head()?;
+
+    let mut revisions = vec![];
+
+    for id in walk {
+        let commit = repo.find_commit(id?)?;
+
+        if let Some(name) = name {
+            let parent_tree = match commit.parent(0) {
+                Ok(parent) => Some(parent.tree()?),
+                Err(_) => None,
+            };
+
+            if !changes(&repo, parent_tree.as_ref(), &commit.tree()?)?.contains(name) {
+                continue;
+            }
+        }
+
+        revisions.push(Revision {
+            id: short_id(&repo, commit.id())?,
+            time: Timestamp(UNIX_EPOCH + Duration::from_secs(commit.time().seconds() as u64)),
+            message: commit.summary().unwrap_or_default().to_string(),
+        });
+    }
+
+    Ok(revisions)
+}
+
+/// Restore the data store to the state of a revision (anything git
+/// understands, e.g. `3f2a1b4` or `HEAD~2`). The rollback is recorded as a
+/// new commit, so it can be rolled back itself. Returns the id of that
+/// commit, or `None` if the data store is in that state already.
+///
+/// Rolling back across a change to the encryption settings would mix
+/// encrypted and plain kubeconfigs, so it is refused unless force is set.
+pub fn rollback(config_dir: &Path, revision: &str, force: bool) -> Result<Option<String>, Error> {
+    let repo = open(config_dir)?;
+
+    let target = repo
+        .revparse_single(revision)
+        .and_then(|object| object.peel_to_commit())
+        .map_err(|err| Error::Message(format!("unknown revision {revision}: {}", err.message())))?;
+
+    // changes that have not been committed yet (e.g. by `kbs use`) would be
+    // lost otherwise.
+    commit(config_dir, "record changes")?;
+
+    let encryption = |tree: Option<Tree>| {
+        tree.and_then(|tree| tree.get_name(crypt::FILE_NAME).map(|entry| entry.id()))
+    };
+    let current = head(&repo)?.map(|head| head.tree()).transpose()?;
+    if !force && encryption(current) != encryption(Some(target.tree()?)) {
+        return Err(Error::Message(format!(
+            "encryption of the data store has changed since revision {revision}, pass --force to roll back anyway"
+        )));
+    }
+
+    repo.checkout_tree(target.as_object(), Some(CheckoutBuilder::new().force()))?;
+    restrict_permissions(config_dir, &target.tree()?)?;
+
+    commit(
+        config_dir,
+        &format!("rollback to {}", short_id(&repo, target.id())?),
+    )
+}
+
+// Files checked out by git are created with the umask, so the mode of the
+// data store has to be restored for everything in tree.
+fn restrict_permissions(config_dir: &Path, tree: &Tree) -> Result<(), Error> {
+    let mut result = Ok(());
+
+    let walk = tree.walk(TreeWalkMode::PreOrder, |root, entry| {
+        let mode = match entry.kind() {
+            Some(ObjectType::Tree) => 0o700,
+            _ => super::FILE_MODE,
+        };
+        let path = config_dir.join(root).join(entry.name().unwrap_or_default());
+
+        match fs::set_permissions(&path, fs::Permissions::from_mode(mode)) {
+            Ok(()) => TreeWalkResult::Ok,
+            Err(err) => {
+                result = Err(Error::IO(err));
+                TreeWalkResult::Abort
+            }
+        }
+    });
+
+    result?;
+    Ok(walk?)
+}
+
+fn open(config_dir: &Path) -> Result<Repository, Error> {
+    if !is_enabled(config_dir) {
+        return Err(Error::Message(
+            "data store is not tracked in git, run 'kbs store track' to enable it".to_string(),
+        ));
+    }
+
+    Ok(Repository::open(config_dir)?)
+}
+
+fn head(repo: &Repository) -> Result<Option<Commit<'_>>, Error> {
+    match repo.head() {
+        Ok(head) => Ok(Some(head.peel_to_commit()?)),
+        Err(err) if err.code() == ErrorCode::UnbornBranch => Ok(None),
+        Err(err) => Err(Error::Git(err)),
+    }
+}
+
+fn short_id(repo: &Repository, id: git2::Oid) -> Result<String, Error> {
+    let object = repo.find_object(id, None)?;
+    Ok(object.short_id()?.as_str().unwrap_or_default().to_string())
+}
+
+// Determine which kubeconfigs have been changed between two trees. Besides
+// kubeconfig files, credentials and metadata entries are taken into account.
+fn changes(repo: &Repository, old: Option<&Tree>, new: &Tree) -> Result<Changes, Error> {
+    let diff = repo.diff_tree_to_tree(old, Some(new), None)?;
+    let mut changes = Changes::default();
+
+    for delta in diff.deltas() {
+        let Some(path) = delta.new_file().path().or(delta.old_file().path()) else {
+            continue;
+        };
+        let path = path.to_string_lossy();
+
+        if let Some(name) = path
+            .strip_suffix(".kubeconfig")
+            .filter(|name| !name.contains('/'))
+        {
+            match delta.status() {
+                Delta::Added => changes.added.insert(name.to_string()),
+                Delta::Deleted => changes.removed.insert(name.to_string()),
+                _ => changes.updated.insert(name.to_string()),
+            };
+        } else if let Some(name) = path
+            .strip_prefix("credentials/")
+            .and_then(|file| file.strip_suffix(".json"))
+        {
+            changes.updated.insert(name.to_string());
+        } else if path == metadata::FILE {
+            let old = metadata_entries(repo, old)?;
+            let new = metadata_entries(repo, Some(new))?;
+
+            changes.updated.extend(
+                old.keys()
+                    .chain(new.keys())
+                    .filter(|name| old.get(*name) != new.get(*name))
+                    .cloned(),
+            );
+        } else if path != ".gitignore" {
+            changes.files.insert(path.to_string());
+        }
+    }
+
+    for name in changes.added.iter().chain(changes.removed.iter()) {
+        changes.updated.remove(name);
+    }
+
+    Ok(changes)
+}
+
+// Read the metadata entries of all kubeconfigs from a tree. The time of last
+// use changes with every `kbs use` and is not considered a change.
+fn metadata_entries(
+    repo: &Repository,
+    tree: Option<&Tree>,
+) -> Result<serde_json::Map<String, serde_json::Value>, Error> {
+    let Some(entry) = tree.and_then(|tree| tree.get_name(metadata::FILE)) else {
+        return Ok(serde_json::Map::new());
+    };
+
+    let blob = repo.find_blob(entry.id())?;
+    let mut metadata: serde_json::Value = serde_json::from_slice(blob.content())?;
+
+    let mut entries = match metadata["kubeconfigs"].take() {
+        serde_json::Value::Object(entries) => entries,
+        _ => serde_json::Map::new(),
+    };
+
+    for entry in entries.values_mut() {
+        if let Some(entry) = entry.as_object_mut() {
+            entry.remove("lastUsed");
+        }
+    }
+
+    Ok(entries)
+}
+
+impl Changes {
+    fn contains(&self, name: &str) -> bool {
+        self.added.contains(name) || self.removed.contains(name) || self.updated.contains(name)
+    }
+}
+
+impl std::fmt::Display for Changes {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let parts: Vec<String> = [
+            ("add", &self.added),
+            ("remove", &self.removed),
+            ("update", &self.updated),
+            ("update", &self.files),
+        ]
+        .iter()
+        .filter(|(_, names)| !names.is_empty())
+        .map(|(verb, names)| {
+            format!(
+                "{verb} {}",
+                names.iter().cloned().collect::<Vec<String>>().join(", ")
+            )
+        })
+        .collect();
+
+        match parts.is_empty() {
+            true => write!(f, "update data store"),
+            false => write!(f, "{}", parts.join("; ")),
+        }
+    }
+}
@@ -0,0 +1,69 @@
+use crate::Error;
+use std::{
+    fs::{File, OpenOptions, TryLockError},
+    path::Path,
+    thread,
+    time::{Duration, Instant},
+};
+
+const LOCK_FILE: &str = ".lock";
+const RETRY_INTERVAL: Duration = Duration::from_millis(50);
+
+/// An exclusive, advisory lock on the data store. The lock is released
+/// when this is dropped (or the process exits).
+pub struct Lock {
+    _file: File,
+}
+
+/// Acquire an exclusive lock on the data store in config_dir, waiting for up
+/// to timeout if another process holds it already.
+pub fn lock(config_dir: &Path, timeout: Duration) -> Result<Lock, Error> {
+    let path = config_dir.join(LOCK_FILE);
+    let file = open(&path)?;
+
+    let start = Instant::now();
+
+    loop {
+        match file.try_lock() {
+            Ok(()) => {
+                log::debug!("acquired lock on {}", path.display());
+                return Ok(Lock { _file: file });
+            }
+            Err(TryLockError::WouldBlock) if start.elapsed() < timeout => {
+                thread::sleep(RETRY_INTERVAL);
+            }
+            Err(TryLockError::WouldBlock) => {
+                return Err(Error::Message(format!(
+                    "timed out after {} waiting for lock on {}, another kbs process is modifying the data store",
+                    humantime::format_duration(timeout),
+                    path.display()
+                )));
+            }
+            Err(TryLockError::Error(err)) => return Err(Error::IO(err)),
+        }
+    }
+}
+
+/// Acquire an exclusive lock on the data store in config_dir if no other
+/// process holds it. Returns None instead of waiting otherwise.
+pub fn try_lock(config_dir: &Path) -> Result<Option<Lock>, Error> {
+    let path = config_dir.join(LOCK_FILE);
+    let file = open(&path)?;
+
+    match file.try_lock() {
+        Ok(()) => {
+            log::debug!("acquired lock on {}", path.display());
+            Ok(Some(Lock { _file: file }))
+        }
+        Err(TryLockError::WouldBlock) => Ok(None),
+        Err(TryLockError::Error(err)) => Err(Error::IO(err)),
+    }
+}
+
+fn open(path: &Path) -> Result<File, Error> {
+    Ok(OpenOptions::new()
+        .create(true)
+        .truncate(false)
+        .write(true)
+        .open(path)?)
+}
@@ -0,0 +1,88 @@
+use crate::Error;
+use std::{
+    fs::{self, File},
+    io::{self, Write},
+    os::unix::fs::PermissionsExt,
+    path::Path,
+};
+use tempfile::NamedTempFile;
+
+mod bundle;
+mod crypt;
+pub mod git;
+mod lock;
+
+pub use bundle::Bundle;
+pub use crypt::{is_encrypted, write_runtime, Encryption};
+pub use lock::{lock, try_lock};
+
+#[cfg(test)]
+mod tests;
+
+/// Mode for all files written to the data store, as they might contain credentials.
+const FILE_MODE: u32 = 0o600;
+
+/// Atomically replace the file at path with contents.
+pub fn write(path: &Path, contents: &[u8]) -> Result<(), Error> {
+    write_with(path, |file| file.write_all(contents))
+}
+
+/// Atomically replace the file at path with whatever `f` writes. Data is
+/// written to a temporary file in the same directory first, which is synced
+/// to disk and then renamed to path. If writing fails or is interrupted,
+/// the previous content of path stays intact.
+pub fn write_with<F>(path: &Path, f: F) -> Result<(), Error>
+where
+    F: FnOnce(&mut File) -> io::Result<()>,
+{
+    let dir = match path.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir,
+        _ => Path::new("."),
+    };
+
+    // the temporary file is removed when it is dropped without being persisted.
+    let mut temp_file = NamedTempFile::new_in(dir)?;
+    temp_file
+        .as_file()
+        .set_permissions(fs::Permissions::from_mode(FILE_MODE))?;
+
+    f(temp_file.as_file_mut())?;
+    temp_file.as_file().sync_all()?;
+
+    temp_file
+        .persist(path)
+        .map_err(|err| Error::IO(err.error))?;
+
+    // sync the directory so the rename itself is durable.
+    File::open(dir)?.sync_all()?;
+
+    Ok(())
+}
+
+/// Read a file holding credentials (e.g. a kubeconfig) from the data store,
+/// decrypting it if it is encrypted.
+pub fn read_private(config_dir: &Path, path: &Path) -> Result<Vec<u8>, Error> {
+    let data = fs::read(path)?;
+
+    if !is_encrypted(&data) {
+        return Ok(data);
+    }
+
+    Encryption::load(config_dir)?
+        .ok_or_else(|| {
+            Error::Message(format!(
+                "{} is encrypted, but no encryption is set up for the data store",
+                path.display()
+            ))
+        })?
+        .decrypt(config_dir, &data)
+}
+
+/// Write a file holding credentials (e.g. a kubeconfig) to the data store,
+/// encrypting it if encryption is set up for the data store.
+pub fn write_private(config_dir: &Path, path: &Path, contents: &[u8]) -> Result<(), Error> {
+    match Encryption::load(config_dir)? {
+        Some(encryption) => write(path, &encryption.encrypt(contents)?),
+        None => write(path, contents),
+    }
+}
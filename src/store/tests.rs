@@ -0,0 +1,90 @@
+use super::*;
+use anyhow::Result;
+use std::time::Duration;
+use tempfile::tempdir;
+
+#[test]
+fn test_write() -> Result<()> {
+    let dir = tempdir()?;
+    let path = dir.path().join("metadata.json");
+
+    write(&path, b"first")?;
+    assert_eq!("first", fs::read_to_string(&path)?);
+
+    write(&path, b"second")?;
+    assert_eq!("second", fs::read_to_string(&path)?);
+
+    Ok(())
+}
+
+#[test]
+fn test_write_sets_mode() -> Result<()> {
+    let dir = tempdir()?;
+    let path = dir.path().join("test.kubeconfig");
+
+    fs::write(&path, "previous")?;
+    fs::set_permissions(&path, fs::Permissions::from_mode(0o644))?;
+
+    write(&path, b"new")?;
+    assert_eq!(FILE_MODE, fs::metadata(&path)?.permissions().mode() & 0o777);
+
+    Ok(())
+}
+
+#[test]
+fn test_interrupted_write_keeps_previous_content() -> Result<()> {
+    let dir = tempdir()?;
+    let path = dir.path().join("metadata.json");
+
+    write(&path, br#"{"version":"0.2","kubeconfigs":{}}"#)?;
+
+    // simulate a write that fails halfway through, e.g. because the disk is full.
+    let res = write_with(&path, |file| {
+        file.write_all(br#"{"version":"0.2","kube"#)?;
+        Err(io::Error::new(io::ErrorKind::StorageFull, "no space left"))
+    });
+
+    assert!(res.is_err());
+    assert_eq!(
+        r#"{"version":"0.2","kubeconfigs":{}}"#,
+        fs::read_to_string(&path)?
+    );
+
+    // no temporary files are left behind.
+    assert_eq!(1, fs::read_dir(dir.path())?.count());
+
+    Ok(())
+}
+
+#[test]
+fn test_lock() -> Result<()> {
+    let dir = tempdir()?;
+
+    let lock_guard = lock(dir.path(), Duration::from_secs(1))?;
+
+    // a second lock cannot be acquired while the first one is held.
+    let err = lock(dir.path(), Duration::from_millis(100)).err().unwrap();
+    assert!(err.to_string().contains("timed out"));
+
+    drop(lock_guard);
+    lock(dir.path(), Duration::from_millis(100))?;
+
+    Ok(())
+}
+
+#[test]
+fn test_try_lock() -> Result<()> {
+    let dir = tempdir()?;
+
+    let lock_guard = try_lock(dir.path())?;
+    assert!(lock_guard.is_some());
+
+    // trying to acquire a held lock does not wait for it.
+    assert!(try_lock(dir.path())?.is_none());
+    assert!(lock(dir.path(), Duration::from_millis(100)).is_err());
+
+    drop(lock_guard);
+    assert!(try_lock(dir.path())?.is_some());
+
+    Ok(())
+}
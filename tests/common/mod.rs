@@ -0,0 +1,63 @@
+// Helpers shared by the integration tests. Not every test uses all of them.
+#![allow(dead_code)]
+
+use assert_cmd::Command;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+/// Directory holding the kubeconfigs used by tests.
+pub fn files_dir() -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/files")
+}
+
+/// Import a kubeconfig file into the data store at config_dir, passing
+/// additional arguments to `kbs import`.
+pub fn import(config_dir: &Path, path: &Path, args: &[&str]) {
+    Command::cargo_bin("kbs")
+        .unwrap()
+        .args(["-c", config_dir.to_str().unwrap(), "import"])
+        .arg(path)
+        .args(args)
+        .assert()
+        .success();
+}
+
+/// Import test.kubeconfig (as `kubernetes.embik.me`, labeled `env=prod`) and
+/// localhost.kubeconfig (as `localhost`, labeled `env=dev`).
+pub fn import_test_kubeconfigs(config_dir: &Path) {
+    for (file, labels) in [
+        ("test.kubeconfig", "env=prod"),
+        ("localhost.kubeconfig", "env=dev"),
+    ] {
+        import(config_dir, &files_dir().join(file), &["-l", labels]);
+    }
+}
+
+/// Import a kubeconfig for a single cluster with the given server and user
+/// configuration (YAML indented to fit below `user:`) as name.
+pub fn import_kubeconfig(config_dir: &Path, name: &str, server: &str, user: &str) {
+    let mut kubeconfig = tempfile::NamedTempFile::new().unwrap();
+    write!(
+        kubeconfig,
+        r#"apiVersion: v1
+kind: Config
+clusters:
+- name: {name}
+  cluster:
+    server: {server}
+contexts:
+- name: {name}
+  context:
+    cluster: {name}
+    user: {name}
+current-context: {name}
+users:
+- name: {name}
+  user:
+    {user}
+"#
+    )
+    .unwrap();
+
+    import(config_dir, kubeconfig.path(), &["-n", name]);
+}
@@ -0,0 +1,175 @@
+use std::fs;
+
+use assert_cmd::Command;
+use predicates::str::{contains, is_match};
+use tempfile::tempdir;
+
+#[test]
+fn test_kbs_config_set_view() {
+    let temp_dir = tempdir().unwrap();
+
+    Command::cargo_bin("kbs")
+        .unwrap()
+        .args([
+            "-c",
+            temp_dir.path().to_str().unwrap(),
+            "config",
+            "set",
+            "list.output",
+            "table",
+        ])
+        .assert()
+        .success();
+
+    Command::cargo_bin("kbs")
+        .unwrap()
+        .args([
+            "-c",
+            temp_dir.path().to_str().unwrap(),
+            "config",
+            "set",
+            "import.labels",
+            "owner=embik,team=platform",
+        ])
+        .assert()
+        .success();
+
+    Command::cargo_bin("kbs")
+        .unwrap()
+        .args(["-c", temp_dir.path().to_str().unwrap(), "config", "view"])
+        .assert()
+        .success()
+        .stdout(contains("[list]\noutput = \"table\""))
+        .stdout(contains(
+            "[import.labels]\nowner = \"embik\"\nteam = \"platform\"",
+        ));
+
+    // invalid values are rejected.
+    Command::cargo_bin("kbs")
+        .unwrap()
+        .args([
+            "-c",
+            temp_dir.path().to_str().unwrap(),
+            "config",
+            "set",
+            "prune.timeout",
+            "soon",
+        ])
+        .assert()
+        .failure()
+        .stderr(contains("invalid prune.timeout setting"));
+
+    Command::cargo_bin("kbs")
+        .unwrap()
+        .args([
+            "-c",
+            temp_dir.path().to_str().unwrap(),
+            "config",
+            "set",
+            "prune.concurrency",
+            "0",
+        ])
+        .assert()
+        .failure()
+        .stderr(contains("invalid prune.concurrency setting"));
+
+    Command::cargo_bin("kbs")
+        .unwrap()
+        .args([
+            "-c",
+            temp_dir.path().to_str().unwrap(),
+            "config",
+            "set",
+            "prune.prune-on",
+            "network,everything",
+        ])
+        .assert()
+        .failure()
+        .stderr(contains("invalid prune.prune-on setting"));
+
+    Command::cargo_bin("kbs")
+        .unwrap()
+        .args([
+            "-c",
+            temp_dir.path().to_str().unwrap(),
+            "config",
+            "set",
+            "import.exec-credentials",
+            "yes",
+        ])
+        .assert()
+        .failure()
+        .stderr(contains("invalid import.exec-credentials setting"));
+
+    Command::cargo_bin("kbs")
+        .unwrap()
+        .args([
+            "-c",
+            temp_dir.path().to_str().unwrap(),
+            "config",
+            "set",
+            "list.colour",
+            "blue",
+        ])
+        .assert()
+        .failure();
+}
+
+#[test]
+fn test_kbs_config_precedence() {
+    let temp_dir = tempdir().unwrap();
+    let base_dir = std::path::PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/files");
+
+    fs::write(
+        temp_dir.path().join("config.toml"),
+        "[list]\noutput = \"table\"\n\n[import.labels]\nowner = \"embik\"\n",
+    )
+    .unwrap();
+
+    // default labels from the settings are merged with labels from flags.
+    Command::cargo_bin("kbs")
+        .unwrap()
+        .args([
+            "-c",
+            temp_dir.path().to_str().unwrap(),
+            "import",
+            base_dir.join("test.kubeconfig").to_str().unwrap(),
+            "-l",
+            "team=platform",
+        ])
+        .assert()
+        .success();
+
+    // the settings file changes the default output.
+    Command::cargo_bin("kbs")
+        .unwrap()
+        .args(["-c", temp_dir.path().to_str().unwrap(), "list"])
+        .assert()
+        .success()
+        .stdout(contains("NAME"))
+        .stdout(contains("owner=embik,team=platform"));
+
+    // environment variables take precedence over the settings file.
+    Command::cargo_bin("kbs")
+        .unwrap()
+        .env("KBS_OUTPUT", "name")
+        .args(["-c", temp_dir.path().to_str().unwrap(), "list"])
+        .assert()
+        .success()
+        .stdout(is_match("^kubernetes.embik.me\n$").unwrap());
+
+    // flags take precedence over environment variables.
+    Command::cargo_bin("kbs")
+        .unwrap()
+        .env("KBS_OUTPUT", "table")
+        .args([
+            "-c",
+            temp_dir.path().to_str().unwrap(),
+            "list",
+            "-o",
+            "name",
+        ])
+        .assert()
+        .success()
+        .stdout(is_match("^kubernetes.embik.me\n$").unwrap());
+}
@@ -0,0 +1,248 @@
+use assert_cmd::Command;
+use predicates::prelude::*;
+use predicates::str::contains;
+use tempfile::tempdir;
+
+#[test]
+fn test_kbs_credential() {
+    let temp_dir = tempdir().unwrap();
+    let config_dir = temp_dir.path().join("kbs");
+    let base_dir = std::path::PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/files");
+
+    Command::cargo_bin("kbs")
+        .unwrap()
+        .args([
+            "-c",
+            config_dir.to_str().unwrap(),
+            "import",
+            "--exec-credentials",
+            base_dir.join("test.kubeconfig").to_str().unwrap(),
+        ])
+        .assert()
+        .success();
+
+    // credentials are replaced by an exec stanza calling kbs.
+    let content =
+        std::fs::read_to_string(config_dir.join("kubernetes.embik.me.kubeconfig")).unwrap();
+    assert!(!content.contains("client-key-data"));
+    assert!(!content.contains("client-certificate-data"));
+    assert!(content.contains("command: kbs"));
+    assert!(config_dir
+        .join("credentials/kubernetes.embik.me.json")
+        .is_file());
+
+    // the certificate expiry is still known.
+    Command::cargo_bin("kbs")
+        .unwrap()
+        .args([
+            "-c",
+            config_dir.to_str().unwrap(),
+            "expiring",
+            "--within",
+            "1d",
+        ])
+        .assert()
+        .failure()
+        .stdout(contains("kubernetes.embik.me"));
+
+    Command::cargo_bin("kbs")
+        .unwrap()
+        .args([
+            "-c",
+            config_dir.to_str().unwrap(),
+            "credential",
+            "kubernetes.embik.me",
+            "--user",
+            "kubernetes-admin",
+        ])
+        .env(
+            "KUBERNETES_EXEC_INFO",
+            r#"{"apiVersion":"client.authentication.k8s.io/v1beta1","kind":"ExecCredential","spec":{"interactive":false}}"#,
+        )
+        .assert()
+        .success()
+        .stdout(contains(
+            r#""apiVersion": "client.authentication.k8s.io/v1beta1""#,
+        ))
+        .stdout(contains(r#""kind": "ExecCredential""#))
+        .stdout(contains(
+            r#""clientCertificateData": "-----BEGIN CERTIFICATE-----"#,
+        ))
+        .stdout(contains(r#""clientKeyData": "-----BEGIN"#));
+
+    Command::cargo_bin("kbs")
+        .unwrap()
+        .args([
+            "-c",
+            config_dir.to_str().unwrap(),
+            "credential",
+            "kubernetes.embik.me",
+            "--user",
+            "unknown",
+        ])
+        .assert()
+        .failure()
+        .stderr(contains("no credentials stored for user 'unknown'"));
+
+    // credentials move along with the kubeconfig.
+    Command::cargo_bin("kbs")
+        .unwrap()
+        .args([
+            "-c",
+            config_dir.to_str().unwrap(),
+            "move",
+            "kubernetes.embik.me",
+            "test",
+        ])
+        .assert()
+        .success();
+
+    let content = std::fs::read_to_string(config_dir.join("test.kubeconfig")).unwrap();
+    assert!(content.contains("- credential\n      - test\n"));
+
+    Command::cargo_bin("kbs")
+        .unwrap()
+        .args([
+            "-c",
+            config_dir.to_str().unwrap(),
+            "credential",
+            "test",
+            "--user",
+            "kubernetes-admin",
+        ])
+        .assert()
+        .success()
+        .stdout(contains(r#""clientKeyData": "-----BEGIN"#));
+
+    Command::cargo_bin("kbs")
+        .unwrap()
+        .args(["-c", config_dir.to_str().unwrap(), "remove", "test"])
+        .assert()
+        .success();
+
+    assert!(!config_dir.join("credentials/test.json").exists());
+
+    Command::cargo_bin("kbs")
+        .unwrap()
+        .args([
+            "-c",
+            config_dir.to_str().unwrap(),
+            "credential",
+            "test",
+            "--user",
+            "kubernetes-admin",
+        ])
+        .assert()
+        .failure()
+        .stderr(contains("no credentials stored for kubeconfig test"));
+}
+
+#[test]
+fn test_kbs_credential_encrypted() {
+    let temp_dir = tempdir().unwrap();
+    let config_dir = temp_dir.path().join("kbs");
+    let base_dir = std::path::PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/files");
+
+    Command::cargo_bin("kbs")
+        .unwrap()
+        .args([
+            "-c",
+            config_dir.to_str().unwrap(),
+            "import",
+            "--exec-credentials",
+            base_dir.join("test.kubeconfig").to_str().unwrap(),
+        ])
+        .assert()
+        .success();
+
+    Command::cargo_bin("kbs")
+        .unwrap()
+        .args(["-c", config_dir.to_str().unwrap(), "store", "encrypt"])
+        .env("KBS_PASSPHRASE", "secret")
+        .assert()
+        .success();
+
+    let content = std::fs::read(config_dir.join("credentials/kubernetes.embik.me.json")).unwrap();
+    assert!(content.starts_with(b"age-encryption.org/v1\n"));
+
+    Command::cargo_bin("kbs")
+        .unwrap()
+        .args([
+            "-c",
+            config_dir.to_str().unwrap(),
+            "credential",
+            "kubernetes.embik.me",
+            "--user",
+            "kubernetes-admin",
+        ])
+        .env("KBS_PASSPHRASE", "secret")
+        .assert()
+        .success()
+        .stdout(contains(r#""clientKeyData": "-----BEGIN"#).and(contains("ExecCredential")));
+}
+
+#[test]
+fn test_kbs_credential_token() {
+    let temp_dir = tempdir().unwrap();
+    let config_dir = temp_dir.path().join("kbs");
+    let kubeconfig = temp_dir.path().join("token.kubeconfig");
+
+    // the token is a eyJhbGciOiJSUzI1NiJ9.eyJzdWIiOiJzeXN0ZW06c2VydmljZWFjY291bnQ6ZGVmYXVsdDprYnMiLCJleHAiOjE3MDAwMDAwMDB9.signature that expired at 2023-11-14T22:13:20Z.
+    std::fs::write(
+        &kubeconfig,
+        r#"apiVersion: v1
+kind: Config
+clusters:
+- cluster:
+    server: https://token.embik.me:6443
+  name: token
+contexts:
+- context:
+    cluster: token
+    user: kbs
+  name: kbs@token
+- context:
+    cluster: token
+    user: admin
+  name: admin@token
+current-context: kbs@token
+users:
+- name: kbs
+  user:
+    token: eyJhbGciOiJSUzI1NiJ9.eyJzdWIiOiJzeXN0ZW06c2VydmljZWFjY291bnQ6ZGVmYXVsdDprYnMiLCJleHAiOjE3MDAwMDAwMDB9.signature
+- name: admin
+  user:
+    username: admin
+    password: secret
+"#,
+    )
+    .unwrap();
+
+    Command::cargo_bin("kbs")
+        .unwrap()
+        .args([
+            "-c",
+            config_dir.to_str().unwrap(),
+            "import",
+            "--exec-credentials",
+            kubeconfig.to_str().unwrap(),
+        ])
+        .assert()
+        .success()
+        .stderr(contains(
+            "user 'admin' authenticates with a password, which cannot be moved to the credential store",
+        ));
+
+    let content = std::fs::read_to_string(config_dir.join("token.embik.me.kubeconfig")).unwrap();
+    assert!(!content.contains("token: "));
+
+    // the token expiry is still known.
+    Command::cargo_bin("kbs")
+        .unwrap()
+        .args(["-c", config_dir.to_str().unwrap(), "prune", "--expired"])
+        .assert()
+        .success()
+        .stderr(contains(
+            "'token.embik.me' should be pruned: token expired at 2023-11-14T22:13:20Z",
+        ));
+}
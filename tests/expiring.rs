@@ -0,0 +1,54 @@
+use assert_cmd::Command;
+use predicates::prelude::*;
+use predicates::str::contains;
+use tempfile::tempdir;
+
+mod common;
+
+#[test]
+fn test_kbs_expiring() {
+    let temp_dir = tempdir().unwrap();
+    common::import_test_kubeconfigs(temp_dir.path());
+
+    // expiry is recorded on import.
+    let metadata = std::fs::read_to_string(temp_dir.path().join("metadata.json")).unwrap();
+    assert!(metadata.contains(r#""expiry":{"clientCert":"2024-01-27T21:31:24Z"}"#));
+
+    // the client certificate in test.kubeconfig has expired.
+    Command::cargo_bin("kbs")
+        .unwrap()
+        .args(["-c", temp_dir.path().to_str().unwrap(), "expiring"])
+        .assert()
+        .failure()
+        .stdout(contains(
+            "kubernetes.embik.me   client certificate   2024-01-27T21:31:24Z   expired\n",
+        ))
+        .stdout(contains("localhost").not());
+
+    Command::cargo_bin("kbs")
+        .unwrap()
+        .args([
+            "-c",
+            temp_dir.path().to_str().unwrap(),
+            "expiring",
+            "--within",
+            "1y",
+            "-l",
+            "env=dev",
+        ])
+        .assert()
+        .success()
+        .stdout("");
+
+    Command::cargo_bin("kbs")
+        .unwrap()
+        .args([
+            "-c",
+            temp_dir.path().to_str().unwrap(),
+            "expiring",
+            "--within",
+            "soon",
+        ])
+        .assert()
+        .failure();
+}
@@ -0,0 +1,110 @@
+use std::{
+    fs,
+    io::{Read, Write},
+    net::TcpListener,
+    thread,
+};
+
+use assert_cmd::Command;
+use predicates::str::is_match;
+use tempfile::tempdir;
+
+// serve a single HTTP response with the given body on a random local port.
+fn serve_once(body: Vec<u8>) -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+
+    thread::spawn(move || {
+        let (mut stream, _) = listener.accept().unwrap();
+        let mut buf = [0; 1024];
+        let _ = stream.read(&mut buf).unwrap();
+
+        write!(
+            stream,
+            "HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+            body.len()
+        )
+        .unwrap();
+        stream.write_all(&body).unwrap();
+    });
+
+    format!("http://{addr}/admin.kubeconfig")
+}
+
+#[test]
+fn test_kbs_fetch_http() {
+    let temp_dir = tempdir().unwrap();
+    let base_dir = std::path::PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/files");
+
+    let url = serve_once(fs::read(base_dir.join("test.kubeconfig")).unwrap());
+
+    Command::cargo_bin("kbs")
+        .unwrap()
+        .args([
+            "-c",
+            temp_dir.path().to_str().unwrap(),
+            "fetch",
+            &url,
+            "-l",
+            "source=http",
+        ])
+        .assert()
+        .success();
+
+    Command::cargo_bin("kbs")
+        .unwrap()
+        .args([
+            "-c",
+            temp_dir.path().to_str().unwrap(),
+            "list",
+            "-l",
+            "source=http",
+        ])
+        .assert()
+        .success()
+        .stdout(is_match("^kubernetes.embik.me\n$").unwrap());
+}
+
+#[test]
+fn test_kbs_fetch_command() {
+    let temp_dir = tempdir().unwrap();
+    let base_dir = std::path::PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/files");
+
+    Command::cargo_bin("kbs")
+        .unwrap()
+        .args([
+            "-c",
+            temp_dir.path().to_str().unwrap(),
+            "fetch",
+            &format!(
+                "cmd:cat {}",
+                base_dir.join("test.kubeconfig").to_str().unwrap()
+            ),
+            "-s",
+        ])
+        .assert()
+        .success();
+
+    Command::cargo_bin("kbs")
+        .unwrap()
+        .args(["-c", temp_dir.path().to_str().unwrap(), "list"])
+        .assert()
+        .success()
+        .stdout(is_match("^kubernetes\n$").unwrap());
+}
+
+#[test]
+fn test_kbs_fetch_command_failure() {
+    let temp_dir = tempdir().unwrap();
+
+    Command::cargo_bin("kbs")
+        .unwrap()
+        .args([
+            "-c",
+            temp_dir.path().to_str().unwrap(),
+            "fetch",
+            "cmd:exit 1",
+        ])
+        .assert()
+        .failure();
+}
@@ -0,0 +1,63 @@
+use assert_cmd::Command;
+use predicates::str::{contains, is_match};
+use tempfile::tempdir;
+
+#[test]
+fn test_kbs_get() {
+    let temp_dir = tempdir().unwrap();
+    let base_dir = std::path::PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/files");
+
+    Command::cargo_bin("kbs")
+        .unwrap()
+        .args([
+            "-c",
+            temp_dir.path().to_str().unwrap(),
+            "import",
+            base_dir.join("test.kubeconfig").to_str().unwrap(),
+            "-l",
+            "env=prod",
+        ])
+        .assert()
+        .success();
+
+    Command::cargo_bin("kbs")
+        .unwrap()
+        .args(["-c", temp_dir.path().to_str().unwrap(), "get", "kubernetes.embik.me"])
+        .assert()
+        .success()
+        .stdout(is_match("^name: kubernetes.embik.me\nlabels:\n  env: prod\nserver: https://kubernetes.embik.me:6443\n").unwrap())
+        .stdout(contains("source:\n  origin:\n    file: "));
+
+    for (output, expected) in [
+        (
+            r#"jsonpath={.server}{"\n"}"#,
+            "^https://kubernetes.embik.me:6443\n$",
+        ),
+        ("jsonpath={.labels.env}", "^prod$"),
+        (
+            "template={{ name }}: {{ auth | join(',') }}",
+            "^kubernetes.embik.me: cert$",
+        ),
+        ("go-template={{ labels.env }}", "^prod$"),
+    ] {
+        Command::cargo_bin("kbs")
+            .unwrap()
+            .args([
+                "-c",
+                temp_dir.path().to_str().unwrap(),
+                "get",
+                "kubernetes.embik.me",
+                "-o",
+                output,
+            ])
+            .assert()
+            .success()
+            .stdout(is_match(expected).unwrap());
+    }
+
+    Command::cargo_bin("kbs")
+        .unwrap()
+        .args(["-c", temp_dir.path().to_str().unwrap(), "get", "unknown"])
+        .assert()
+        .failure();
+}
@@ -0,0 +1,268 @@
+use assert_cmd::Command;
+use predicates::prelude::*;
+use predicates::str::{contains, is_match};
+use std::os::unix::fs::PermissionsExt;
+use tempfile::tempdir;
+
+#[test]
+fn test_kbs_history_rollback() {
+    let temp_dir = tempdir().unwrap();
+    let config_dir = temp_dir.path().join("kbs");
+    let base_dir = std::path::PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/files");
+
+    Command::cargo_bin("kbs")
+        .unwrap()
+        .args([
+            "-c",
+            config_dir.to_str().unwrap(),
+            "import",
+            base_dir.join("test.kubeconfig").to_str().unwrap(),
+        ])
+        .assert()
+        .success();
+
+    Command::cargo_bin("kbs")
+        .unwrap()
+        .args(["-c", config_dir.to_str().unwrap(), "store", "track"])
+        .assert()
+        .success();
+
+    Command::cargo_bin("kbs")
+        .unwrap()
+        .args([
+            "-c",
+            config_dir.to_str().unwrap(),
+            "import",
+            base_dir.join("localhost.kubeconfig").to_str().unwrap(),
+        ])
+        .assert()
+        .success();
+
+    Command::cargo_bin("kbs")
+        .unwrap()
+        .args([
+            "-c",
+            config_dir.to_str().unwrap(),
+            "label",
+            "--name",
+            "localhost",
+            "env=prod",
+        ])
+        .assert()
+        .success();
+
+    Command::cargo_bin("kbs")
+        .unwrap()
+        .args([
+            "-c",
+            config_dir.to_str().unwrap(),
+            "move",
+            "kubernetes.embik.me",
+            "test",
+        ])
+        .assert()
+        .success();
+
+    Command::cargo_bin("kbs")
+        .unwrap()
+        .args(["-c", config_dir.to_str().unwrap(), "history"])
+        .assert()
+        .success()
+        .stdout(is_match(r"^REVISION\s+DATE\s+CHANGE\n").unwrap())
+        .stdout(
+            is_match(r"\n[0-9a-f]{7,}\s+\S+\s+move: add test; remove kubernetes.embik.me\n")
+                .unwrap(),
+        )
+        .stdout(contains("label: update localhost\n"))
+        .stdout(contains("import: add localhost\n"))
+        .stdout(contains("track data store: add kubernetes.embik.me\n"));
+
+    let output = Command::cargo_bin("kbs")
+        .unwrap()
+        .args(["-c", config_dir.to_str().unwrap(), "history", "localhost"])
+        .output()
+        .unwrap();
+    let history = String::from_utf8(output.stdout).unwrap();
+
+    assert!(!history.contains("move:"));
+    assert!(history.contains("label: update localhost"));
+
+    // roll back to the revision before labeling.
+    let revision = history
+        .lines()
+        .find(|line| line.contains("import: add localhost"))
+        .and_then(|line| line.split_whitespace().next())
+        .unwrap()
+        .to_string();
+
+    Command::cargo_bin("kbs")
+        .unwrap()
+        .args(["-c", config_dir.to_str().unwrap(), "rollback", &revision])
+        .assert()
+        .success()
+        .stderr(contains(format!(
+            "restored data store to revision {revision}"
+        )));
+
+    Command::cargo_bin("kbs")
+        .unwrap()
+        .args(["-c", config_dir.to_str().unwrap(), "list", "-o", "table"])
+        .assert()
+        .success()
+        .stdout(is_match(r"\nkubernetes.embik.me\s*\nlocalhost\s*\n$").unwrap());
+
+    Command::cargo_bin("kbs")
+        .unwrap()
+        .args(["-c", config_dir.to_str().unwrap(), "history"])
+        .assert()
+        .success()
+        .stdout(contains(format!(
+            "rollback to {revision}: add kubernetes.embik.me; remove test; update localhost\n"
+        )));
+
+    // the rollback itself can be rolled back.
+    Command::cargo_bin("kbs")
+        .unwrap()
+        .args(["-c", config_dir.to_str().unwrap(), "rollback", "HEAD~1"])
+        .assert()
+        .success();
+
+    Command::cargo_bin("kbs")
+        .unwrap()
+        .args(["-c", config_dir.to_str().unwrap(), "list", "-o", "table"])
+        .assert()
+        .success()
+        .stdout(is_match(r"\nlocalhost\s+env=prod\s*\ntest\s*\n$").unwrap());
+
+    Command::cargo_bin("kbs")
+        .unwrap()
+        .args(["-c", config_dir.to_str().unwrap(), "rollback", "unknown"])
+        .assert()
+        .failure()
+        .stderr(contains("unknown revision unknown"));
+}
+
+#[test]
+fn test_kbs_history_untracked() {
+    let temp_dir = tempdir().unwrap();
+
+    Command::cargo_bin("kbs")
+        .unwrap()
+        .args(["-c", temp_dir.path().to_str().unwrap(), "history"])
+        .assert()
+        .failure()
+        .stderr(contains("data store is not tracked in git"));
+
+    Command::cargo_bin("kbs")
+        .unwrap()
+        .args(["-c", temp_dir.path().to_str().unwrap(), "rollback", "HEAD"])
+        .assert()
+        .failure()
+        .stderr(contains("data store is not tracked in git"));
+
+    // commands changing the data store don't create a repository.
+    Command::cargo_bin("kbs")
+        .unwrap()
+        .args([
+            "-c",
+            temp_dir.path().to_str().unwrap(),
+            "config",
+            "set",
+            "list.output",
+            "wide",
+        ])
+        .assert()
+        .success();
+
+    assert!(!temp_dir.path().join(".git").exists());
+    assert!(predicate::path::missing().eval(&temp_dir.path().join(".gitignore")));
+}
+
+#[test]
+fn test_kbs_rollback_restores_data_store_state() {
+    let temp_dir = tempdir().unwrap();
+    let config_dir = temp_dir.path().join("kbs");
+    let base_dir = std::path::PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/files");
+    let mode =
+        |path: &std::path::Path| std::fs::metadata(path).unwrap().permissions().mode() & 0o777;
+
+    Command::cargo_bin("kbs")
+        .unwrap()
+        .args([
+            "-c",
+            config_dir.to_str().unwrap(),
+            "import",
+            base_dir.join("test.kubeconfig").to_str().unwrap(),
+        ])
+        .assert()
+        .success();
+
+    Command::cargo_bin("kbs")
+        .unwrap()
+        .args(["-c", config_dir.to_str().unwrap(), "store", "track"])
+        .assert()
+        .success();
+
+    assert_eq!(0o700, mode(&config_dir));
+    assert_eq!(0o700, mode(&config_dir.join(".git")));
+
+    Command::cargo_bin("kbs")
+        .unwrap()
+        .args([
+            "-c",
+            config_dir.to_str().unwrap(),
+            "remove",
+            "kubernetes.embik.me",
+        ])
+        .assert()
+        .success();
+
+    Command::cargo_bin("kbs")
+        .unwrap()
+        .args(["-c", config_dir.to_str().unwrap(), "rollback", "HEAD~1"])
+        .assert()
+        .success();
+
+    // the kubeconfig is private again and not left in the trash.
+    assert_eq!(
+        0o600,
+        mode(&config_dir.join("kubernetes.embik.me.kubeconfig"))
+    );
+
+    Command::cargo_bin("kbs")
+        .unwrap()
+        .args(["-c", config_dir.to_str().unwrap(), "trash", "list"])
+        .assert()
+        .success()
+        .stderr(contains("trash is empty"));
+
+    Command::cargo_bin("kbs")
+        .unwrap()
+        .env("KBS_PASSPHRASE", "correct horse battery staple")
+        .args(["-c", config_dir.to_str().unwrap(), "store", "encrypt"])
+        .assert()
+        .success();
+
+    Command::cargo_bin("kbs")
+        .unwrap()
+        .args(["-c", config_dir.to_str().unwrap(), "rollback", "HEAD~1"])
+        .assert()
+        .failure()
+        .stderr(contains("pass --force to roll back anyway"));
+
+    assert!(config_dir.join("encryption.toml").exists());
+
+    Command::cargo_bin("kbs")
+        .unwrap()
+        .args([
+            "-c",
+            config_dir.to_str().unwrap(),
+            "rollback",
+            "--force",
+            "HEAD~1",
+        ])
+        .assert()
+        .success();
+
+    assert!(!config_dir.join("encryption.toml").exists());
+}
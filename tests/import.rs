@@ -1,6 +1,7 @@
 use std::fs;
 
 use assert_cmd::Command;
+use predicates::prelude::*;
 use predicates::str::{contains, is_match};
 use tempfile::tempdir;
 
@@ -67,6 +68,106 @@ fn test_kbs_import_duplicate_error() {
         .stderr(contains("kubeconfig kubernetes.embik.me already exists"));
 }
 
+#[test]
+fn test_kbs_import_on_conflict() {
+    let temp_dir = tempdir().unwrap();
+    let base_dir = std::path::PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/files");
+
+    let import = |args: &[&str]| {
+        Command::cargo_bin("kbs")
+            .unwrap()
+            .args([
+                "-c",
+                temp_dir.path().to_str().unwrap(),
+                "import",
+                base_dir.join("test.kubeconfig").to_str().unwrap(),
+            ])
+            .args(args)
+            .assert()
+    };
+
+    import(&["-l", "owner=embik"]).success();
+
+    // skip leaves the existing kubeconfig alone.
+    import(&["--on-conflict", "skip"])
+        .success()
+        .stderr(contains("kubernetes.embik.me already exists, skipping"));
+
+    // rename imports the kubeconfig with a numeric suffix.
+    import(&["--on-conflict", "rename"]).success();
+    import(&["--on-conflict", "rename"]).success();
+
+    Command::cargo_bin("kbs")
+        .unwrap()
+        .args(["-c", temp_dir.path().to_str().unwrap(), "list"])
+        .assert()
+        .success()
+        .stdout(
+            is_match("^kubernetes.embik.me-1\nkubernetes.embik.me-2\nkubernetes.embik.me\n$")
+                .unwrap(),
+        );
+
+    // overwrite keeps the labels of the existing kubeconfig.
+    import(&["--on-conflict", "overwrite", "-l", "team=platform"]).success();
+
+    Command::cargo_bin("kbs")
+        .unwrap()
+        .args([
+            "-c",
+            temp_dir.path().to_str().unwrap(),
+            "list",
+            "-l",
+            "owner=embik,team=platform",
+        ])
+        .assert()
+        .success()
+        .stdout(is_match("^kubernetes.embik.me\n$").unwrap());
+
+    // prompting is not possible without a terminal.
+    import(&["--on-conflict", "prompt"])
+        .failure()
+        .stderr(contains("without a terminal"));
+}
+
+#[test]
+fn test_kbs_import_on_conflict_skip_delete() {
+    let temp_dir = tempdir().unwrap();
+    let source_dir = tempdir().unwrap();
+    let base_dir = std::path::PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/files");
+
+    let source = source_dir.path().join("test.kubeconfig");
+    fs::copy(base_dir.join("test.kubeconfig"), &source).unwrap();
+
+    Command::cargo_bin("kbs")
+        .unwrap()
+        .args([
+            "-c",
+            temp_dir.path().to_str().unwrap(),
+            "import",
+            base_dir.join("test.kubeconfig").to_str().unwrap(),
+        ])
+        .assert()
+        .success();
+
+    // a skipped kubeconfig has not been stored, so it must not be deleted.
+    Command::cargo_bin("kbs")
+        .unwrap()
+        .args([
+            "-c",
+            temp_dir.path().to_str().unwrap(),
+            "import",
+            source.to_str().unwrap(),
+            "--on-conflict",
+            "skip",
+            "--delete",
+        ])
+        .assert()
+        .success()
+        .stderr(contains("kubernetes.embik.me already exists, skipping"));
+
+    assert!(source.exists());
+}
+
 #[test]
 fn test_kbs_import_name_override() {
     let temp_dir = tempdir().unwrap();
@@ -128,7 +229,7 @@ fn test_kbs_import_with_labels() {
         .success()
         .stdout(
             is_match(
-                "^NAME( +)\tLABELS( +)\nkubernetes.embik.me( +)\tlocation=nonexistent,owner=embik\n$",
+                "^NAME                  LABELS\nkubernetes.embik.me   location=nonexistent,owner=embik\n$",
             )
             .unwrap(),
         );
@@ -221,6 +322,131 @@ fn test_kbs_import_mixed_kubeconfig() {
         .stdout(is_match("^kubernetes.embik.me\n$").unwrap());
 }
 
+#[test]
+fn test_kbs_import_split() {
+    let temp_dir = tempdir().unwrap();
+    let base_dir = std::path::PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/files");
+
+    Command::cargo_bin("kbs")
+        .unwrap()
+        .args([
+            "-c",
+            temp_dir.path().to_str().unwrap(),
+            "import",
+            base_dir.join("mixed.kubeconfig").to_str().unwrap(),
+            "--split",
+        ])
+        .assert()
+        .success()
+        .stderr(contains(
+            "imported context 'kubernetes-admin@kubernetes' from",
+        ))
+        .stderr(contains("as kubernetes.embik.me"))
+        .stderr(contains(
+            "imported context 'kubernetes-admin@localhost' from",
+        ))
+        .stderr(contains("as localhost"));
+
+    Command::cargo_bin("kbs")
+        .unwrap()
+        .args(["-c", temp_dir.path().to_str().unwrap(), "list"])
+        .assert()
+        .success()
+        .stdout(is_match("^kubernetes.embik.me\nlocalhost\n$").unwrap());
+
+    // each kubeconfig only holds the cluster of its context.
+    let localhost = fs::read_to_string(temp_dir.path().join("localhost.kubeconfig")).unwrap();
+    assert!(localhost.contains("https://localhost:6443"));
+    assert!(!localhost.contains("kubernetes.embik.me"));
+    assert!(localhost.contains("current-context: localhost"));
+}
+
+#[test]
+fn test_kbs_import_split_partial_failure() {
+    let temp_dir = tempdir().unwrap();
+    let source_dir = tempdir().unwrap();
+    let base_dir = std::path::PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/files");
+
+    // add a context referencing a cluster that does not exist.
+    let source = source_dir.path().join("mixed.kubeconfig");
+    let content = fs::read_to_string(base_dir.join("mixed.kubeconfig"))
+        .unwrap()
+        .replace(
+            "contexts:\n",
+            "contexts:\n- context:\n    cluster: missing\n    user: kubernetes-admin\n  name: broken\n",
+        );
+    fs::write(&source, content).unwrap();
+
+    Command::cargo_bin("kbs")
+        .unwrap()
+        .args([
+            "-c",
+            temp_dir.path().to_str().unwrap(),
+            "import",
+            source.to_str().unwrap(),
+            "--split",
+            "--delete",
+            "-l",
+            "owner=embik",
+        ])
+        .assert()
+        .failure()
+        .stderr(contains("failed to import context 'broken'"))
+        .stderr(contains(format!(
+            "created 2 kubeconfig(s) from {}: kubernetes.embik.me, localhost, failed to import 1 context(s): broken",
+            source.display()
+        )))
+        .stderr(contains("ERROR] failed to import 1 context(s)"));
+
+    // the source still holds the context that failed to import.
+    assert!(source.exists());
+
+    // the other contexts have been imported along with their metadata.
+    Command::cargo_bin("kbs")
+        .unwrap()
+        .args([
+            "-c",
+            temp_dir.path().to_str().unwrap(),
+            "list",
+            "-l",
+            "owner=embik",
+        ])
+        .assert()
+        .success()
+        .stdout(is_match("^kubernetes.embik.me\nlocalhost\n$").unwrap());
+}
+
+#[test]
+fn test_kbs_import_split_name_template() {
+    let temp_dir = tempdir().unwrap();
+    let base_dir = std::path::PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/files");
+
+    // the template renders the same name for both contexts.
+    Command::cargo_bin("kbs")
+        .unwrap()
+        .args([
+            "-c",
+            temp_dir.path().to_str().unwrap(),
+            "import",
+            base_dir.join("mixed.kubeconfig").to_str().unwrap(),
+            "--split",
+            "--name-template",
+            "{file}",
+        ])
+        .assert()
+        .success();
+
+    Command::cargo_bin("kbs")
+        .unwrap()
+        .args(["-c", temp_dir.path().to_str().unwrap(), "list"])
+        .assert()
+        .success()
+        .stdout(
+            is_match("^mixed-kubernetes-admin@kubernetes\nmixed-kubernetes-admin@localhost\n$")
+                .unwrap(),
+        );
+}
+
 #[test]
 fn test_kbs_import_directory() {
     let temp_dir = tempdir().unwrap();
@@ -245,6 +471,197 @@ fn test_kbs_import_directory() {
         .stdout(is_match("^kubernetes.embik.me\nlocalhost\n$").unwrap());
 }
 
+#[test]
+fn test_kbs_import_directory_skips_empty_kubeconfig() {
+    let temp_dir = tempdir().unwrap();
+    let source_dir = tempdir().unwrap();
+    let base_dir = std::path::PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/files");
+
+    fs::copy(
+        base_dir.join("test.kubeconfig"),
+        source_dir.path().join("test.kubeconfig"),
+    )
+    .unwrap();
+    fs::write(
+        source_dir.path().join("empty.kubeconfig"),
+        "apiVersion: v1\nkind: Config\n",
+    )
+    .unwrap();
+
+    Command::cargo_bin("kbs")
+        .unwrap()
+        .args([
+            "-c",
+            temp_dir.path().to_str().unwrap(),
+            "import",
+            source_dir.path().to_str().unwrap(),
+        ])
+        .assert()
+        .success()
+        .stderr(contains("imported 1 file(s)"))
+        .stderr(contains("skipped 1, failed 0"))
+        .stderr(contains("WARN").not());
+}
+
+#[test]
+fn test_kbs_import_directory_recursive() {
+    let temp_dir = tempdir().unwrap();
+    let source_dir = tempdir().unwrap();
+    let base_dir = std::path::PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/files");
+
+    for dir in ["prod", "dev", "old"] {
+        fs::create_dir(source_dir.path().join(dir)).unwrap();
+    }
+
+    fs::copy(
+        base_dir.join("test.kubeconfig"),
+        source_dir.path().join("prod/admin.kubeconfig"),
+    )
+    .unwrap();
+    fs::copy(
+        base_dir.join("localhost.kubeconfig"),
+        source_dir.path().join("dev/admin.kubeconfig"),
+    )
+    .unwrap();
+    fs::copy(
+        base_dir.join("test.kubeconfig"),
+        source_dir.path().join("old/admin.kubeconfig"),
+    )
+    .unwrap();
+    fs::write(source_dir.path().join("dev/notes.txt"), "not a kubeconfig").unwrap();
+
+    // without --recursive, subdirectories are ignored.
+    Command::cargo_bin("kbs")
+        .unwrap()
+        .args([
+            "-c",
+            temp_dir.path().to_str().unwrap(),
+            "import",
+            source_dir.path().to_str().unwrap(),
+        ])
+        .assert()
+        .success()
+        .stderr(contains("imported 0 file(s)"));
+
+    Command::cargo_bin("kbs")
+        .unwrap()
+        .args([
+            "-c",
+            temp_dir.path().to_str().unwrap(),
+            "import",
+            source_dir.path().to_str().unwrap(),
+            "--recursive",
+            "--exclude",
+            "old/*",
+            "--name-template",
+            "{dir}-{file}",
+            "--delete",
+        ])
+        .assert()
+        .success()
+        .stderr(contains("imported 2 file(s)"))
+        .stderr(contains("skipped 1, failed 0"));
+
+    Command::cargo_bin("kbs")
+        .unwrap()
+        .args(["-c", temp_dir.path().to_str().unwrap(), "list"])
+        .assert()
+        .success()
+        .stdout(is_match("^dev-admin\nprod-admin\n$").unwrap());
+
+    // only successfully imported files are deleted.
+    assert!(!source_dir.path().join("prod/admin.kubeconfig").exists());
+    assert!(!source_dir.path().join("dev/admin.kubeconfig").exists());
+    assert!(source_dir.path().join("dev/notes.txt").exists());
+    assert!(source_dir.path().join("old/admin.kubeconfig").exists());
+}
+
+#[test]
+fn test_kbs_import_directory_include() {
+    let temp_dir = tempdir().unwrap();
+    let base_dir = std::path::PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/files");
+
+    Command::cargo_bin("kbs")
+        .unwrap()
+        .args([
+            "-c",
+            temp_dir.path().to_str().unwrap(),
+            "import",
+            base_dir.to_str().unwrap(),
+            "--include",
+            "local*",
+        ])
+        .assert()
+        .success()
+        .stderr(contains("imported 1 file(s)"));
+
+    Command::cargo_bin("kbs")
+        .unwrap()
+        .args(["-c", temp_dir.path().to_str().unwrap(), "list"])
+        .assert()
+        .success()
+        .stdout(is_match("^localhost\n$").unwrap());
+}
+
+#[test]
+fn test_kbs_import_name_template() {
+    let temp_dir = tempdir().unwrap();
+    let base_dir = std::path::PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/files");
+
+    // a default name template can be configured in config.toml.
+    fs::write(
+        temp_dir.path().join("config.toml"),
+        "[import]\nname-template = '{host:^kubernetes\\.([a-z]+)}-{port}'\n",
+    )
+    .unwrap();
+
+    Command::cargo_bin("kbs")
+        .unwrap()
+        .args([
+            "-c",
+            temp_dir.path().to_str().unwrap(),
+            "import",
+            base_dir.join("test.kubeconfig").to_str().unwrap(),
+        ])
+        .assert()
+        .success();
+
+    // the flag takes precedence over the configured template.
+    Command::cargo_bin("kbs")
+        .unwrap()
+        .args([
+            "-c",
+            temp_dir.path().to_str().unwrap(),
+            "import",
+            base_dir.join("test.kubeconfig").to_str().unwrap(),
+            "--name-template",
+            "{host[1]}-{user}",
+        ])
+        .assert()
+        .success();
+
+    Command::cargo_bin("kbs")
+        .unwrap()
+        .args(["-c", temp_dir.path().to_str().unwrap(), "list"])
+        .assert()
+        .success()
+        .stdout(is_match("^embik-6443\nembik-kubernetes-admin\n$").unwrap());
+
+    Command::cargo_bin("kbs")
+        .unwrap()
+        .args([
+            "-c",
+            temp_dir.path().to_str().unwrap(),
+            "import",
+            base_dir.join("test.kubeconfig").to_str().unwrap(),
+            "--name-template",
+            "{host:^api}",
+        ])
+        .assert()
+        .failure()
+        .stderr(contains("does not match"));
+}
+
 #[test]
 fn test_kbs_import_from_stdin() {
     let temp_dir = tempdir().unwrap();
@@ -266,3 +683,48 @@ fn test_kbs_import_from_stdin() {
         .success()
         .stdout(is_match("^kubernetes.embik.me\n$").unwrap());
 }
+
+#[test]
+fn test_kbs_import_concurrent() {
+    let temp_dir = tempdir().unwrap();
+    let base_dir = std::path::PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/files");
+
+    // spawn a couple of imports at once, each of them has to wait
+    // for the others to finish updating the metadata store.
+    let children = (0..10)
+        .map(|i| {
+            std::process::Command::new(assert_cmd::cargo::cargo_bin("kbs"))
+                .args([
+                    "-c",
+                    temp_dir.path().to_str().unwrap(),
+                    "import",
+                    base_dir.join("test.kubeconfig").to_str().unwrap(),
+                    "-n",
+                    &format!("cluster-{i}"),
+                    "-l",
+                    &format!("index={i}"),
+                ])
+                .spawn()
+                .unwrap()
+        })
+        .collect::<Vec<_>>();
+
+    for mut child in children {
+        assert!(child.wait().unwrap().success());
+    }
+
+    for i in 0..10 {
+        Command::cargo_bin("kbs")
+            .unwrap()
+            .args([
+                "-c",
+                temp_dir.path().to_str().unwrap(),
+                "list",
+                "-l",
+                &format!("index={i}"),
+            ])
+            .assert()
+            .success()
+            .stdout(is_match(format!("^cluster-{i}\n$")).unwrap());
+    }
+}
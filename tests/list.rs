@@ -2,6 +2,8 @@ use assert_cmd::Command;
 use predicates::str::is_match;
 use tempfile::tempdir;
 
+mod common;
+
 #[test]
 fn test_kbs_list_label_selector() {
     let temp_dir = tempdir().unwrap();
@@ -132,3 +134,275 @@ fn test_kbs_list_label_selector_not_equal() {
         .success()
         .stdout(is_match("^kubernetes.embik.me\nkubernetes.not.embik.me\n$").unwrap());
 }
+
+#[test]
+fn test_kbs_list_label_selector_set_based() {
+    let temp_dir = tempdir().unwrap();
+    let base_dir = std::path::PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/files");
+
+    for (name, labels) in [
+        ("prod", "env=prod,team=platform"),
+        ("staging", "env=staging"),
+        ("dev", "env=dev,team=platform"),
+    ] {
+        Command::cargo_bin("kbs")
+            .unwrap()
+            .args([
+                "-c",
+                temp_dir.path().to_str().unwrap(),
+                "import",
+                base_dir.join("test.kubeconfig").to_str().unwrap(),
+                "-l",
+                labels,
+                "-n",
+                name,
+            ])
+            .assert()
+            .success();
+    }
+
+    for (selector, expected) in [
+        ("env in (prod,staging)", "^prod\nstaging\n$"),
+        ("env notin (prod, staging)", "^dev\n$"),
+        ("team", "^dev\nprod\n$"),
+        ("!team", "^staging\n$"),
+        ("team,env in (prod,staging)", "^prod\n$"),
+    ] {
+        Command::cargo_bin("kbs")
+            .unwrap()
+            .args([
+                "-c",
+                temp_dir.path().to_str().unwrap(),
+                "list",
+                "-l",
+                selector,
+            ])
+            .assert()
+            .success()
+            .stdout(is_match(expected).unwrap());
+    }
+
+    // multiple selector flags are combined.
+    Command::cargo_bin("kbs")
+        .unwrap()
+        .args([
+            "-c",
+            temp_dir.path().to_str().unwrap(),
+            "list",
+            "-l",
+            "team",
+            "-l",
+            "env!=dev",
+        ])
+        .assert()
+        .success()
+        .stdout(is_match("^prod\n$").unwrap());
+}
+
+#[test]
+fn test_kbs_list_field_and_search() {
+    let temp_dir = tempdir().unwrap();
+    common::import_test_kubeconfigs(temp_dir.path());
+
+    for (args, expected) in [
+        (
+            vec!["--field", r"server=~\.embik\.me:6443$"],
+            "^kubernetes.embik.me\n$",
+        ),
+        (
+            vec!["--field", "server!=https://localhost:6443"],
+            "^kubernetes.embik.me\n$",
+        ),
+        (
+            vec!["--field", "auth=cert", "--field", "user=kubernetes-admin"],
+            "^kubernetes.embik.me\nlocalhost\n$",
+        ),
+        (vec!["--field", "auth=exec"], "^$"),
+        (vec!["--search", "ENV=DEV"], "^localhost\n$"),
+        (vec!["--search", "embik"], "^kubernetes.embik.me\n$"),
+        (
+            vec!["--search", "6443", "-l", "env=prod"],
+            "^kubernetes.embik.me\n$",
+        ),
+    ] {
+        Command::cargo_bin("kbs")
+            .unwrap()
+            .args(["-c", temp_dir.path().to_str().unwrap(), "list"])
+            .args(args)
+            .assert()
+            .success()
+            .stdout(is_match(expected).unwrap());
+    }
+
+    Command::cargo_bin("kbs")
+        .unwrap()
+        .args([
+            "-c",
+            temp_dir.path().to_str().unwrap(),
+            "list",
+            "--field",
+            "auth=password",
+        ])
+        .assert()
+        .failure();
+}
+
+#[test]
+fn test_kbs_list_output() {
+    let temp_dir = tempdir().unwrap();
+    common::import_test_kubeconfigs(temp_dir.path());
+
+    // using a kubeconfig records when it was last used.
+    Command::cargo_bin("kbs")
+        .unwrap()
+        .args(["-c", temp_dir.path().to_str().unwrap(), "use", "localhost"])
+        .assert()
+        .success();
+
+    let output = Command::cargo_bin("kbs")
+        .unwrap()
+        .args([
+            "-c",
+            temp_dir.path().to_str().unwrap(),
+            "list",
+            "-o",
+            "json",
+        ])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+
+    let document: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    let entries = &document["items"];
+    assert_eq!(entries[0]["name"], "kubernetes.embik.me");
+    assert_eq!(entries[0]["labels"]["env"], "prod");
+    assert_eq!(entries[0]["server"], "https://kubernetes.embik.me:6443");
+    assert_eq!(entries[0]["auth"][0], "cert");
+    assert_eq!(entries[0]["certExpiry"], "2024-01-27T21:31:24Z");
+    assert!(entries[0].get("lastUsed").is_none());
+    assert!(entries[1]["lastUsed"].is_string());
+
+    Command::cargo_bin("kbs")
+        .unwrap()
+        .args([
+            "-c",
+            temp_dir.path().to_str().unwrap(),
+            "list",
+            "-o",
+            "yaml",
+        ])
+        .assert()
+        .success()
+        .stdout(
+            is_match("^items:\n- name: kubernetes.embik.me\n  labels:\n    env: prod\n").unwrap(),
+        );
+
+    Command::cargo_bin("kbs")
+        .unwrap()
+        .args(["-c", temp_dir.path().to_str().unwrap(), "list", "-o", "wide"])
+        .assert()
+        .success()
+        .stdout(
+            is_match(
+                "^NAME                  LABELS     SERVER                             AUTH   CERT EXPIRY            CA EXPIRY   LAST USED\n\
+                 kubernetes.embik.me   env=prod   https://kubernetes.embik.me:6443   cert   2024-01-27T21:31:24Z   <none>      <none>\n\
+                 localhost             env=dev    https://localhost:6443             cert   <none>                 <none>      [0-9TZ:-]+\n$",
+            )
+            .unwrap(),
+        );
+
+    Command::cargo_bin("kbs")
+        .unwrap()
+        .args([
+            "-c",
+            temp_dir.path().to_str().unwrap(),
+            "list",
+            "-o",
+            "custom-columns=NAME:.name,ENV:.labels.env,OWNER:.labels.owner",
+        ])
+        .assert()
+        .success()
+        .stdout(
+            is_match(
+                "^NAME                  ENV    OWNER\nkubernetes.embik.me   prod   <none>\nlocalhost             dev    <none>\n$",
+            )
+            .unwrap(),
+        );
+}
+
+#[test]
+fn test_kbs_list_jsonpath_and_template() {
+    let temp_dir = tempdir().unwrap();
+    common::import_test_kubeconfigs(temp_dir.path());
+
+    for (output, expected) in [
+        (
+            "jsonpath={.items[*].server}",
+            "^https://kubernetes.embik.me:6443 https://localhost:6443$",
+        ),
+        (
+            r#"jsonpath={.items[?(@.labels.env == "dev")].name}"#,
+            "^localhost$",
+        ),
+        (
+            "template={% for item in items %}{{ item.name }}={{ item.labels.env }}\n{% endfor %}",
+            "^kubernetes.embik.me=prod\nlocalhost=dev\n$",
+        ),
+    ] {
+        Command::cargo_bin("kbs")
+            .unwrap()
+            .args([
+                "-c",
+                temp_dir.path().to_str().unwrap(),
+                "list",
+                "-o",
+                output,
+            ])
+            .assert()
+            .success()
+            .stdout(is_match(expected).unwrap());
+    }
+
+    Command::cargo_bin("kbs")
+        .unwrap()
+        .args([
+            "-c",
+            temp_dir.path().to_str().unwrap(),
+            "list",
+            "-o",
+            "template={% for item in items %}",
+        ])
+        .assert()
+        .failure();
+}
+
+#[test]
+fn test_kbs_use_does_not_wait_for_lock() {
+    let temp_dir = tempdir().unwrap();
+    common::import_test_kubeconfigs(temp_dir.path());
+
+    // another kbs process is modifying the data store.
+    let lock = std::fs::File::create(temp_dir.path().join(".lock")).unwrap();
+    lock.lock().unwrap();
+
+    Command::cargo_bin("kbs")
+        .unwrap()
+        .timeout(std::time::Duration::from_secs(30))
+        .args([
+            "-c",
+            temp_dir.path().to_str().unwrap(),
+            "--lock-timeout",
+            "1h",
+            "use",
+            "localhost",
+        ])
+        .assert()
+        .success()
+        .stdout(is_match("^export KUBECONFIG=.*/localhost.kubeconfig$").unwrap());
+
+    // usage is not recorded while the data store is locked.
+    let metadata = std::fs::read_to_string(temp_dir.path().join("metadata.json")).unwrap();
+    assert!(!metadata.contains("lastUsed"));
+
+    drop(lock);
+}
@@ -0,0 +1,112 @@
+use std::fs;
+
+use assert_cmd::Command;
+use predicates::str::{contains, is_match};
+use tempfile::tempdir;
+
+#[test]
+fn test_kbs_metadata_migrate_v0_1() {
+    let temp_dir = tempdir().unwrap();
+    let base_dir = std::path::PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/files");
+    let metadata_path = temp_dir.path().join("metadata.json");
+
+    fs::copy(base_dir.join("metadata/v0.1.json"), &metadata_path).unwrap();
+    fs::copy(
+        base_dir.join("test.kubeconfig"),
+        temp_dir.path().join("kubernetes.embik.me.kubeconfig"),
+    )
+    .unwrap();
+
+    // a dry run prints the migrated metadata but leaves the file alone.
+    Command::cargo_bin("kbs")
+        .unwrap()
+        .args([
+            "-c",
+            temp_dir.path().to_str().unwrap(),
+            "metadata",
+            "migrate",
+            "--dry-run",
+        ])
+        .assert()
+        .success()
+        .stdout(contains("\"version\": \"0.2\""))
+        .stderr(contains("0.1 -> 0.2"));
+
+    assert_eq!(
+        fs::read_to_string(&metadata_path).unwrap(),
+        fs::read_to_string(base_dir.join("metadata/v0.1.json")).unwrap()
+    );
+
+    // read-only commands migrate the metadata in memory only.
+    Command::cargo_bin("kbs")
+        .unwrap()
+        .args([
+            "-c",
+            temp_dir.path().to_str().unwrap(),
+            "list",
+            "-l",
+            "owner=embik",
+        ])
+        .assert()
+        .success()
+        .stdout(is_match("^kubernetes.embik.me\n$").unwrap());
+
+    assert_eq!(
+        fs::read_to_string(&metadata_path).unwrap(),
+        fs::read_to_string(base_dir.join("metadata/v0.1.json")).unwrap()
+    );
+    assert!(!temp_dir.path().join("metadata.json.v0.1.bak").exists());
+
+    // commands writing the metadata persist the migration.
+    Command::cargo_bin("kbs")
+        .unwrap()
+        .args([
+            "-c",
+            temp_dir.path().to_str().unwrap(),
+            "label",
+            "team=platform",
+            "-n",
+            "kubernetes.embik.me",
+        ])
+        .assert()
+        .success()
+        .stderr(contains("migrated metadata from version 0.1 to 0.2"));
+
+    assert!(fs::read_to_string(&metadata_path)
+        .unwrap()
+        .contains("\"version\":\"0.2\""));
+    assert_eq!(
+        fs::read_to_string(temp_dir.path().join("metadata.json.v0.1.bak")).unwrap(),
+        fs::read_to_string(base_dir.join("metadata/v0.1.json")).unwrap()
+    );
+
+    Command::cargo_bin("kbs")
+        .unwrap()
+        .args([
+            "-c",
+            temp_dir.path().to_str().unwrap(),
+            "metadata",
+            "migrate",
+        ])
+        .assert()
+        .success()
+        .stderr(contains("metadata is at version 0.2 already"));
+}
+
+#[test]
+fn test_kbs_metadata_unknown_version() {
+    let temp_dir = tempdir().unwrap();
+
+    fs::write(
+        temp_dir.path().join("metadata.json"),
+        r#"{"version":"99.0","kubeconfigs":{}}"#,
+    )
+    .unwrap();
+
+    Command::cargo_bin("kbs")
+        .unwrap()
+        .args(["-c", temp_dir.path().to_str().unwrap(), "list"])
+        .assert()
+        .failure()
+        .stderr(contains("unknown metadata version: 99.0"));
+}
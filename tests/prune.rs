@@ -1,7 +1,13 @@
 use assert_cmd::Command;
-use predicates::str::{is_empty, is_match};
+use predicates::str::{contains, is_empty, is_match};
+use std::io::Read;
+use std::net::TcpListener;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
 use tempfile::tempdir;
 
+mod common;
+
 #[test]
 fn test_kbs_prune() {
     let temp_dir = tempdir().unwrap();
@@ -142,3 +148,281 @@ fn test_kbs_prune_label_selector() {
         .success()
         .stdout(is_match("^kubernetes.beckers.dev\n$").unwrap());
 }
+
+#[test]
+fn test_kbs_prune_strategies() {
+    let temp_dir = tempdir().unwrap();
+    common::import_test_kubeconfigs(temp_dir.path());
+
+    Command::cargo_bin("kbs")
+        .unwrap()
+        .args([
+            "-c",
+            temp_dir.path().to_str().unwrap(),
+            "prune",
+            "--unused-for",
+            "0s",
+        ])
+        .assert()
+        .success()
+        .stderr(is_match("'localhost' should be pruned: never used since import at ").unwrap());
+
+    Command::cargo_bin("kbs")
+        .unwrap()
+        .args([
+            "-c",
+            temp_dir.path().to_str().unwrap(),
+            "prune",
+            "--unused-for",
+            "90d",
+            "--dry-run=false",
+        ])
+        .assert()
+        .success();
+
+    Command::cargo_bin("kbs")
+        .unwrap()
+        .args(["-c", temp_dir.path().to_str().unwrap(), "list"])
+        .assert()
+        .success()
+        .stdout(is_match("^kubernetes.embik.me\nlocalhost\n$").unwrap());
+
+    // the client certificate in test.kubeconfig has expired.
+    Command::cargo_bin("kbs")
+        .unwrap()
+        .args([
+            "-c",
+            temp_dir.path().to_str().unwrap(),
+            "prune",
+            "--expired",
+            "--dry-run=false",
+        ])
+        .assert()
+        .success()
+        .stderr(
+            contains("pruned kubeconfig 'kubernetes.embik.me', moved it to trash: client certificate expired at 2024-01-27T21:31:24Z"),
+        );
+
+    Command::cargo_bin("kbs")
+        .unwrap()
+        .args(["-c", temp_dir.path().to_str().unwrap(), "list"])
+        .assert()
+        .success()
+        .stdout(is_match("^localhost\n$").unwrap());
+
+    let metadata = std::fs::read_to_string(temp_dir.path().join("metadata.json")).unwrap();
+    assert!(!metadata.contains("kubernetes.embik.me"));
+}
+
+#[test]
+fn test_kbs_prune_hanging_servers() {
+    let temp_dir = tempdir().unwrap();
+
+    // the kernel accepts connections to listeners, but nothing ever responds.
+    let listeners: Vec<TcpListener> = (0..4)
+        .map(|_| TcpListener::bind("127.0.0.1:0").unwrap())
+        .collect();
+
+    for (i, listener) in listeners.iter().enumerate() {
+        common::import_kubeconfig(
+            &temp_dir.path().join("kbs"),
+            &format!("hanging-{i}"),
+            &format!("https://{}", listener.local_addr().unwrap()),
+            "token: opaque-token",
+        );
+    }
+
+    // count the connections that are open at the same time. Each one is
+    // kept open by kbs until its check times out.
+    let open = Arc::new(AtomicUsize::new(0));
+    let max_open = Arc::new(AtomicUsize::new(0));
+
+    for listener in listeners {
+        let (open, max_open) = (open.clone(), max_open.clone());
+
+        std::thread::spawn(move || {
+            for mut stream in listener.incoming().flatten() {
+                max_open.fetch_max(open.fetch_add(1, Ordering::SeqCst) + 1, Ordering::SeqCst);
+                let _ = stream.read_to_end(&mut vec![]);
+                open.fetch_sub(1, Ordering::SeqCst);
+            }
+        });
+    }
+
+    Command::cargo_bin("kbs")
+        .unwrap()
+        .args([
+            "-c",
+            temp_dir.path().join("kbs").to_str().unwrap(),
+            "prune",
+            "--timeout",
+            "1s",
+            "--concurrency",
+            "4",
+        ])
+        .assert()
+        .success()
+        .stdout(contains("NAME        STATUS        FAILURE   REASON\n"))
+        .stdout(contains(
+            "hanging-0   unreachable   timeout   timed out after 1s\n",
+        ))
+        .stdout(contains(
+            "hanging-3   unreachable   timeout   timed out after 1s\n",
+        ))
+        .stderr(contains(
+            "'hanging-3' should be pruned: timeout failure (timed out after 1s)",
+        ));
+
+    // all servers are checked at the same time.
+    assert_eq!(4, max_open.load(Ordering::SeqCst));
+
+    Command::cargo_bin("kbs")
+        .unwrap()
+        .args([
+            "-c",
+            temp_dir.path().join("kbs").to_str().unwrap(),
+            "prune",
+            "--concurrency",
+            "0",
+        ])
+        .assert()
+        .failure();
+}
+
+#[test]
+fn test_kbs_prune_failure_classes() {
+    let temp_dir = tempdir().unwrap();
+
+    // nothing listens on the port once the listener is dropped.
+    let refused = TcpListener::bind("127.0.0.1:0")
+        .unwrap()
+        .local_addr()
+        .unwrap();
+
+    common::import_kubeconfig(
+        &temp_dir.path().join("kbs"),
+        "refused",
+        &format!("https://{refused}"),
+        "token: opaque-token",
+    );
+    common::import_kubeconfig(
+        &temp_dir.path().join("kbs"),
+        "dns",
+        "https://kubernetes.invalid:6443",
+        "token: opaque-token",
+    );
+    common::import_kubeconfig(
+        &temp_dir.path().join("kbs"),
+        "exec",
+        &format!("https://{refused}"),
+        "exec:\n      apiVersion: client.authentication.k8s.io/v1\n      command: \"false\"",
+    );
+
+    // only network-level failures are pruned by default.
+    Command::cargo_bin("kbs")
+        .unwrap()
+        .args([
+            "-c",
+            temp_dir.path().join("kbs").to_str().unwrap(),
+            "prune",
+            "--timeout",
+            "5s",
+            "--dry-run=false",
+        ])
+        .assert()
+        .success()
+        .stdout(contains("dns       unreachable   dns       "))
+        .stdout(contains("exec      error         exec      "))
+        .stdout(contains("refused   unreachable   network   "))
+        .stderr(contains("not pruning 'exec' for exec failure"));
+
+    Command::cargo_bin("kbs")
+        .unwrap()
+        .args(["-c", temp_dir.path().join("kbs").to_str().unwrap(), "list"])
+        .assert()
+        .success()
+        .stdout(is_match("^exec\n$").unwrap());
+
+    Command::cargo_bin("kbs")
+        .unwrap()
+        .args([
+            "-c",
+            temp_dir.path().join("kbs").to_str().unwrap(),
+            "prune",
+            "--prune-on",
+            "exec,unauthorized",
+            "--dry-run=false",
+        ])
+        .assert()
+        .success()
+        .stderr(contains("pruned kubeconfig 'exec'"));
+
+    Command::cargo_bin("kbs")
+        .unwrap()
+        .args([
+            "-c",
+            temp_dir.path().join("kbs").to_str().unwrap(),
+            "prune",
+            "--prune-on",
+            "everything",
+        ])
+        .assert()
+        .failure();
+}
+
+#[test]
+fn test_kbs_prune_on_setting() {
+    let temp_dir = tempdir().unwrap();
+    let config_dir = temp_dir.path().join("kbs");
+
+    let refused = TcpListener::bind("127.0.0.1:0")
+        .unwrap()
+        .local_addr()
+        .unwrap();
+
+    common::import_kubeconfig(
+        &config_dir,
+        "exec",
+        &format!("https://{refused}"),
+        "exec:\n      apiVersion: client.authentication.k8s.io/v1\n      command: \"false\"",
+    );
+
+    Command::cargo_bin("kbs")
+        .unwrap()
+        .args([
+            "-c",
+            config_dir.to_str().unwrap(),
+            "config",
+            "set",
+            "prune.prune-on",
+            "exec",
+        ])
+        .assert()
+        .success();
+
+    // the environment variable takes precedence over the setting.
+    Command::cargo_bin("kbs")
+        .unwrap()
+        .env("KBS_PRUNE_ON", "network,dns")
+        .args([
+            "-c",
+            config_dir.to_str().unwrap(),
+            "prune",
+            "--dry-run=false",
+        ])
+        .assert()
+        .success()
+        .stderr(contains("not pruning 'exec' for exec failure"));
+
+    Command::cargo_bin("kbs")
+        .unwrap()
+        .args([
+            "-c",
+            config_dir.to_str().unwrap(),
+            "prune",
+            "--dry-run=false",
+        ])
+        .assert()
+        .success()
+        .stderr(contains("pruned kubeconfig 'exec'"));
+}
@@ -0,0 +1,168 @@
+use std::fs;
+
+use assert_cmd::Command;
+use predicates::str::{contains, is_match};
+use tempfile::tempdir;
+
+#[test]
+fn test_kbs_refresh() {
+    let temp_dir = tempdir().unwrap();
+    let source_dir = tempdir().unwrap();
+    let base_dir = std::path::PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/files");
+
+    let source = source_dir.path().join("admin.kubeconfig");
+    fs::copy(base_dir.join("test.kubeconfig"), &source).unwrap();
+
+    Command::cargo_bin("kbs")
+        .unwrap()
+        .args([
+            "-c",
+            temp_dir.path().to_str().unwrap(),
+            "import",
+            source.to_str().unwrap(),
+            "-l",
+            "owner=embik",
+        ])
+        .assert()
+        .success();
+
+    // nothing changed at the source yet.
+    Command::cargo_bin("kbs")
+        .unwrap()
+        .args(["-c", temp_dir.path().to_str().unwrap(), "refresh"])
+        .assert()
+        .success()
+        .stderr(contains("'kubernetes.embik.me' is up to date"));
+
+    // simulate a rotated CA certificate at the source.
+    let content = fs::read_to_string(&source).unwrap().replace(
+        "certificate-authority-data: \"\"",
+        "certificate-authority-data: \"Zm9vCg==\"",
+    );
+    fs::write(&source, content).unwrap();
+
+    Command::cargo_bin("kbs")
+        .unwrap()
+        .args([
+            "-c",
+            temp_dir.path().to_str().unwrap(),
+            "refresh",
+            "--dry-run",
+        ])
+        .assert()
+        .success()
+        .stderr(contains("'kubernetes.embik.me' has changed"));
+
+    Command::cargo_bin("kbs")
+        .unwrap()
+        .args(["-c", temp_dir.path().to_str().unwrap(), "refresh"])
+        .assert()
+        .success()
+        .stderr(contains("refreshed 'kubernetes.embik.me'"));
+
+    // the refreshed kubeconfig keeps its normalized context name and labels.
+    let refreshed =
+        fs::read_to_string(temp_dir.path().join("kubernetes.embik.me.kubeconfig")).unwrap();
+    assert!(refreshed.contains("Zm9vCg=="));
+    assert!(refreshed.contains("current-context: kubernetes.embik.me"));
+
+    Command::cargo_bin("kbs")
+        .unwrap()
+        .args([
+            "-c",
+            temp_dir.path().to_str().unwrap(),
+            "list",
+            "-l",
+            "owner=embik",
+        ])
+        .assert()
+        .success()
+        .stdout(is_match("^kubernetes.embik.me\n$").unwrap());
+}
+
+#[test]
+fn test_kbs_refresh_missing_source() {
+    let temp_dir = tempdir().unwrap();
+    let source_dir = tempdir().unwrap();
+    let base_dir = std::path::PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/files");
+
+    let source = source_dir.path().join("admin.kubeconfig");
+    fs::copy(base_dir.join("test.kubeconfig"), &source).unwrap();
+
+    Command::cargo_bin("kbs")
+        .unwrap()
+        .args([
+            "-c",
+            temp_dir.path().to_str().unwrap(),
+            "import",
+            source.to_str().unwrap(),
+            "--delete",
+        ])
+        .assert()
+        .success();
+
+    Command::cargo_bin("kbs")
+        .unwrap()
+        .args(["-c", temp_dir.path().to_str().unwrap(), "refresh"])
+        .assert()
+        .failure()
+        .stderr(contains("failed to refresh 1 kubeconfig(s)"));
+}
+
+#[test]
+fn test_kbs_refresh_partial_failure() {
+    let temp_dir = tempdir().unwrap();
+    let source_dir = tempdir().unwrap();
+    let base_dir = std::path::PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/files");
+
+    for file in ["test.kubeconfig", "localhost.kubeconfig"] {
+        let source = source_dir.path().join(file);
+        fs::copy(base_dir.join(file), &source).unwrap();
+
+        Command::cargo_bin("kbs")
+            .unwrap()
+            .args([
+                "-c",
+                temp_dir.path().to_str().unwrap(),
+                "import",
+                source.to_str().unwrap(),
+            ])
+            .assert()
+            .success();
+
+        let content = fs::read_to_string(&source).unwrap().replace(
+            "certificate-authority-data: \"\"",
+            "certificate-authority-data: \"Zm9vCg==\"",
+        );
+        fs::write(&source, content).unwrap();
+    }
+
+    // the first kubeconfig cannot be loaded from the data store anymore.
+    fs::write(
+        temp_dir.path().join("kubernetes.embik.me.kubeconfig"),
+        "not: [a kubeconfig",
+    )
+    .unwrap();
+
+    Command::cargo_bin("kbs")
+        .unwrap()
+        .args(["-c", temp_dir.path().to_str().unwrap(), "refresh"])
+        .assert()
+        .failure()
+        .stderr(contains("failed to save 'kubernetes.embik.me'"))
+        .stderr(contains("refreshed 'localhost'"))
+        .stderr(contains("failed to refresh 1 kubeconfig(s)"));
+
+    // metadata of the refreshed kubeconfig has been updated nonetheless.
+    Command::cargo_bin("kbs")
+        .unwrap()
+        .args([
+            "-c",
+            temp_dir.path().to_str().unwrap(),
+            "refresh",
+            "localhost",
+        ])
+        .assert()
+        .success()
+        .stderr(contains("'localhost' is up to date"));
+}
@@ -142,6 +142,73 @@ fn test_kbs_remove_by_selector() {
         .stdout(is_empty());
 }
 
+#[test]
+fn test_kbs_remove_by_set_based_selector() {
+    let temp_dir = tempdir().unwrap();
+    let base_dir = std::path::PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/files");
+
+    for (name, labels) in [
+        ("prod", "env=prod,team=platform"),
+        ("staging", "env=staging"),
+        ("dev", "env=dev,team=platform"),
+    ] {
+        Command::cargo_bin("kbs")
+            .unwrap()
+            .args([
+                "-c",
+                temp_dir.path().to_str().unwrap(),
+                "import",
+                base_dir.join("test.kubeconfig").to_str().unwrap(),
+                "-l",
+                labels,
+                "-n",
+                name,
+            ])
+            .assert()
+            .success();
+    }
+
+    // removing kubeconfigs without a team label should succeed.
+    Command::cargo_bin("kbs")
+        .unwrap()
+        .args([
+            "-c",
+            temp_dir.path().to_str().unwrap(),
+            "remove",
+            "-l",
+            "!team",
+        ])
+        .assert()
+        .success();
+
+    Command::cargo_bin("kbs")
+        .unwrap()
+        .args(["-c", temp_dir.path().to_str().unwrap(), "list"])
+        .assert()
+        .success()
+        .stdout(is_match("^dev\nprod\n$").unwrap());
+
+    // removing kubeconfigs by set-based selector should succeed.
+    Command::cargo_bin("kbs")
+        .unwrap()
+        .args([
+            "-c",
+            temp_dir.path().to_str().unwrap(),
+            "remove",
+            "-l",
+            "env in (dev,staging)",
+        ])
+        .assert()
+        .success();
+
+    Command::cargo_bin("kbs")
+        .unwrap()
+        .args(["-c", temp_dir.path().to_str().unwrap(), "list"])
+        .assert()
+        .success()
+        .stdout(is_match("^prod\n$").unwrap());
+}
+
 #[test]
 fn test_kbs_remove_active() {
     let temp_dir = tempdir().unwrap();
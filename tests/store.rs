@@ -0,0 +1,511 @@
+use age::secrecy::ExposeSecret;
+use assert_cmd::Command;
+use predicates::prelude::*;
+use predicates::str::{contains, is_match, starts_with};
+use std::os::unix::fs::{MetadataExt, PermissionsExt};
+use tempfile::tempdir;
+
+#[test]
+fn test_kbs_store_encrypt_identity_file() {
+    let temp_dir = tempdir().unwrap();
+    let config_dir = temp_dir.path().join("kbs");
+    let base_dir = std::path::PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/files");
+
+    let identity = age::x25519::Identity::generate();
+    let identity_file = temp_dir.path().join("key.txt");
+    std::fs::write(
+        &identity_file,
+        format!("{}\n", identity.to_string().expose_secret()),
+    )
+    .unwrap();
+
+    Command::cargo_bin("kbs")
+        .unwrap()
+        .args([
+            "-c",
+            config_dir.to_str().unwrap(),
+            "import",
+            base_dir.join("test.kubeconfig").to_str().unwrap(),
+        ])
+        .assert()
+        .success();
+
+    Command::cargo_bin("kbs")
+        .unwrap()
+        .args([
+            "-c",
+            config_dir.to_str().unwrap(),
+            "store",
+            "encrypt",
+            "--identity",
+            identity_file.to_str().unwrap(),
+        ])
+        .assert()
+        .success();
+
+    // kubeconfigs imported later on are encrypted as well.
+    Command::cargo_bin("kbs")
+        .unwrap()
+        .args([
+            "-c",
+            config_dir.to_str().unwrap(),
+            "import",
+            base_dir.join("localhost.kubeconfig").to_str().unwrap(),
+        ])
+        .assert()
+        .success();
+
+    for name in ["kubernetes.embik.me", "localhost"] {
+        let content = std::fs::read(config_dir.join(format!("{name}.kubeconfig"))).unwrap();
+        assert!(content.starts_with(b"age-encryption.org/v1\n"));
+    }
+
+    Command::cargo_bin("kbs")
+        .unwrap()
+        .args([
+            "-c",
+            config_dir.to_str().unwrap(),
+            "show",
+            "kubernetes.embik.me",
+        ])
+        .assert()
+        .success()
+        .stdout(contains(
+            "# Server:         https://kubernetes.embik.me:6443\n",
+        ));
+
+    // kubeconfigs are decrypted to the runtime directory to use them.
+    let runtime_dir = temp_dir.path().join("run");
+    std::fs::create_dir(&runtime_dir).unwrap();
+    let kubeconfig = runtime_dir.join("kbs/localhost.kubeconfig");
+
+    Command::cargo_bin("kbs")
+        .unwrap()
+        .env("XDG_RUNTIME_DIR", &runtime_dir)
+        .args(["-c", config_dir.to_str().unwrap(), "use", "localhost"])
+        .assert()
+        .success()
+        .stdout(format!("export KUBECONFIG={}", kubeconfig.display()));
+
+    assert!(std::fs::read_to_string(&kubeconfig)
+        .unwrap()
+        .contains("server: https://localhost:6443"));
+    assert_eq!(
+        0o600,
+        std::fs::metadata(&kubeconfig).unwrap().permissions().mode() & 0o777
+    );
+
+    // without a runtime directory, the same directory for the current user
+    // in the temporary directory is used every time.
+    let tmp_dir = temp_dir.path().join("tmp");
+    std::fs::create_dir(&tmp_dir).unwrap();
+    let uid = std::fs::metadata(&tmp_dir).unwrap().uid();
+    let kubeconfig = tmp_dir.join(format!("kbs-{uid}/localhost.kubeconfig"));
+
+    for _ in 0..2 {
+        Command::cargo_bin("kbs")
+            .unwrap()
+            .env_remove("XDG_RUNTIME_DIR")
+            .env("TMPDIR", &tmp_dir)
+            .args(["-c", config_dir.to_str().unwrap(), "use", "localhost"])
+            .assert()
+            .success()
+            .stdout(format!("export KUBECONFIG={}", kubeconfig.display()));
+    }
+
+    assert_eq!(1, std::fs::read_dir(&tmp_dir).unwrap().count());
+    assert_eq!(
+        0o700,
+        std::fs::metadata(kubeconfig.parent().unwrap())
+            .unwrap()
+            .permissions()
+            .mode()
+            & 0o777
+    );
+
+    // a directory (or symlink) that might belong to someone else is not used.
+    let tmp_dir = temp_dir.path().join("tmp-symlink");
+    std::fs::create_dir(&tmp_dir).unwrap();
+    std::os::unix::fs::symlink(&runtime_dir, tmp_dir.join(format!("kbs-{uid}"))).unwrap();
+
+    Command::cargo_bin("kbs")
+        .unwrap()
+        .env_remove("XDG_RUNTIME_DIR")
+        .env("TMPDIR", &tmp_dir)
+        .args(["-c", config_dir.to_str().unwrap(), "use", "localhost"])
+        .assert()
+        .failure()
+        .stderr(contains("not a directory owned by the current user"));
+
+    // encrypting again picks up kubeconfigs left in plain text, e.g. by an
+    // interrupted run.
+    std::fs::copy(
+        base_dir.join("test.kubeconfig"),
+        config_dir.join("plain.kubeconfig"),
+    )
+    .unwrap();
+
+    Command::cargo_bin("kbs")
+        .unwrap()
+        .args(["-c", config_dir.to_str().unwrap(), "store", "encrypt"])
+        .assert()
+        .success()
+        .stderr(contains("data store is already encrypted"))
+        .stderr(contains("encrypted 1 file(s)"));
+
+    assert!(std::fs::read(config_dir.join("plain.kubeconfig"))
+        .unwrap()
+        .starts_with(b"age-encryption.org/v1\n"));
+
+    Command::cargo_bin("kbs")
+        .unwrap()
+        .args([
+            "-c",
+            config_dir.to_str().unwrap(),
+            "store",
+            "encrypt",
+            "--identity",
+            identity_file.to_str().unwrap(),
+        ])
+        .assert()
+        .failure()
+        .stderr(contains("data store is already encrypted"));
+
+    Command::cargo_bin("kbs")
+        .unwrap()
+        .args(["-c", config_dir.to_str().unwrap(), "remove", "plain"])
+        .assert()
+        .success();
+
+    Command::cargo_bin("kbs")
+        .unwrap()
+        .args(["-c", config_dir.to_str().unwrap(), "store", "decrypt"])
+        .assert()
+        .success();
+
+    assert!(!config_dir.join("encryption.toml").exists());
+    for name in ["kubernetes.embik.me", "localhost"] {
+        let content =
+            std::fs::read_to_string(config_dir.join(format!("{name}.kubeconfig"))).unwrap();
+        assert!(content.contains("apiVersion: v1"));
+    }
+
+    Command::cargo_bin("kbs")
+        .unwrap()
+        .env("XDG_RUNTIME_DIR", &runtime_dir)
+        .args(["-c", config_dir.to_str().unwrap(), "use", "localhost"])
+        .assert()
+        .success()
+        .stdout(is_match("^export KUBECONFIG=.*/kbs/localhost.kubeconfig$").unwrap())
+        .stdout(starts_with(format!("export KUBECONFIG={}", runtime_dir.display())).not());
+}
+
+#[test]
+fn test_kbs_store_encrypt_passphrase() {
+    let temp_dir = tempdir().unwrap();
+    let base_dir = std::path::PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/files");
+
+    Command::cargo_bin("kbs")
+        .unwrap()
+        .args([
+            "-c",
+            temp_dir.path().to_str().unwrap(),
+            "import",
+            base_dir.join("test.kubeconfig").to_str().unwrap(),
+        ])
+        .assert()
+        .success();
+
+    Command::cargo_bin("kbs")
+        .unwrap()
+        .env("KBS_PASSPHRASE", "correct horse battery staple")
+        .args(["-c", temp_dir.path().to_str().unwrap(), "store", "encrypt"])
+        .assert()
+        .success();
+
+    // listing kubeconfigs does not require decrypting them.
+    Command::cargo_bin("kbs")
+        .unwrap()
+        .env_remove("KBS_PASSPHRASE")
+        .args(["-c", temp_dir.path().to_str().unwrap(), "list"])
+        .assert()
+        .success()
+        .stdout("kubernetes.embik.me\n");
+
+    Command::cargo_bin("kbs")
+        .unwrap()
+        .env("KBS_PASSPHRASE", "wrong")
+        .args([
+            "-c",
+            temp_dir.path().to_str().unwrap(),
+            "get",
+            "kubernetes.embik.me",
+        ])
+        .assert()
+        .failure()
+        .stderr(contains("is the passphrase correct?"));
+
+    Command::cargo_bin("kbs")
+        .unwrap()
+        .env("KBS_PASSPHRASE", "correct horse battery staple")
+        .args(["-c", temp_dir.path().to_str().unwrap(), "store", "decrypt"])
+        .assert()
+        .success();
+
+    assert!(!temp_dir.path().join("identity.age").exists());
+
+    Command::cargo_bin("kbs")
+        .unwrap()
+        .env_remove("KBS_PASSPHRASE")
+        .args([
+            "-c",
+            temp_dir.path().to_str().unwrap(),
+            "get",
+            "kubernetes.embik.me",
+        ])
+        .assert()
+        .success();
+}
+
+#[test]
+fn test_kbs_store_export_import() {
+    let temp_dir = tempdir().unwrap();
+    let source_dir = temp_dir.path().join("source");
+    let target_dir = temp_dir.path().join("target");
+    let bundle = temp_dir.path().join("bundle.tar.zst");
+    let base_dir = std::path::PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/files");
+
+    for (name, file, labels) in [
+        ("test", "test.kubeconfig", "env=prod,team=a"),
+        ("local", "localhost.kubeconfig", "env=dev"),
+    ] {
+        Command::cargo_bin("kbs")
+            .unwrap()
+            .args([
+                "-c",
+                source_dir.to_str().unwrap(),
+                "import",
+                "--exec-credentials",
+                "--name",
+                name,
+                "--labels",
+                labels,
+                base_dir.join(file).to_str().unwrap(),
+            ])
+            .assert()
+            .success();
+    }
+
+    Command::cargo_bin("kbs")
+        .unwrap()
+        .args([
+            "-c",
+            source_dir.to_str().unwrap(),
+            "store",
+            "export",
+            "-l",
+            "env=prod",
+            "-o",
+            bundle.to_str().unwrap(),
+        ])
+        .assert()
+        .success()
+        .stderr(contains("exported 1 kubeconfig(s)"))
+        .stderr(contains("bundle holds credentials in plain text"));
+
+    Command::cargo_bin("kbs")
+        .unwrap()
+        .args([
+            "-c",
+            source_dir.to_str().unwrap(),
+            "store",
+            "export",
+            "-o",
+            bundle.to_str().unwrap(),
+        ])
+        .assert()
+        .success()
+        .stderr(contains("exported 2 kubeconfig(s)"));
+
+    // kubeconfigs imported before a conflict keep their metadata.
+    let conflict_dir = temp_dir.path().join("conflict");
+
+    Command::cargo_bin("kbs")
+        .unwrap()
+        .args([
+            "-c",
+            conflict_dir.to_str().unwrap(),
+            "import",
+            "--name",
+            "test",
+            base_dir.join("localhost.kubeconfig").to_str().unwrap(),
+        ])
+        .assert()
+        .success();
+
+    Command::cargo_bin("kbs")
+        .unwrap()
+        .args([
+            "-c",
+            conflict_dir.to_str().unwrap(),
+            "store",
+            "import",
+            "--on-conflict",
+            "error",
+            bundle.to_str().unwrap(),
+        ])
+        .assert()
+        .failure()
+        .stderr(contains("kubeconfig test already exists"));
+
+    Command::cargo_bin("kbs")
+        .unwrap()
+        .args(["-c", conflict_dir.to_str().unwrap(), "list", "-o", "table"])
+        .assert()
+        .success()
+        .stdout(is_match(r"\nlocal\s+env=dev\s*\ntest\s*\n$").unwrap());
+
+    // the target has the same kubeconfig as "test" (imported from another
+    // file) and a different one named "local".
+    let copy = temp_dir.path().join("copy.kubeconfig");
+    std::fs::copy(base_dir.join("test.kubeconfig"), &copy).unwrap();
+
+    for (name, file) in [
+        ("test", copy.clone()),
+        ("local", base_dir.join("test.kubeconfig")),
+    ] {
+        Command::cargo_bin("kbs")
+            .unwrap()
+            .args([
+                "-c",
+                target_dir.to_str().unwrap(),
+                "import",
+                "--name",
+                name,
+                "--labels",
+                "team=b,owner=me",
+                file.to_str().unwrap(),
+            ])
+            .assert()
+            .success();
+    }
+
+    Command::cargo_bin("kbs")
+        .unwrap()
+        .args([
+            "-c",
+            target_dir.to_str().unwrap(),
+            "store",
+            "import",
+            bundle.to_str().unwrap(),
+        ])
+        .assert()
+        .success()
+        .stderr(contains(
+            "kubeconfig test exists with the same content already",
+        ))
+        .stderr(contains("imported kubeconfig local from bundle as local-1"))
+        .stderr(contains("imported 2 of 2 kubeconfig(s)"));
+
+    Command::cargo_bin("kbs")
+        .unwrap()
+        .args(["-c", target_dir.to_str().unwrap(), "list", "-o", "table"])
+        .assert()
+        .success()
+        .stdout(is_match(r"\nlocal\s+owner=me,team=b\s*\n").unwrap())
+        .stdout(is_match(r"\nlocal-1\s+env=dev\s*\n").unwrap())
+        .stdout(is_match(r"\ntest\s+env=prod,owner=me,team=a\s*\n$").unwrap());
+
+    // identical kubeconfigs keep where they have been imported from.
+    let metadata: serde_json::Value =
+        serde_json::from_slice(&std::fs::read(target_dir.join("metadata.json")).unwrap()).unwrap();
+    assert_eq!(
+        copy.to_str().unwrap(),
+        metadata["kubeconfigs"]["test"]["source"]["origin"]["file"]
+    );
+
+    // credentials have been inlined into the bundle, so they end up in the
+    // kubeconfig unless requested otherwise.
+    let kubeconfig = std::fs::read_to_string(target_dir.join("local-1.kubeconfig")).unwrap();
+    assert!(kubeconfig.contains("client-key-data"));
+    assert!(!kubeconfig.contains("kbs"));
+}
+
+#[test]
+fn test_kbs_store_export_import_encrypted() {
+    let temp_dir = tempdir().unwrap();
+    let source_dir = temp_dir.path().join("source");
+    let target_dir = temp_dir.path().join("target");
+    let bundle = temp_dir.path().join("bundle.tar.zst.age");
+    let base_dir = std::path::PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/files");
+
+    Command::cargo_bin("kbs")
+        .unwrap()
+        .args([
+            "-c",
+            source_dir.to_str().unwrap(),
+            "import",
+            base_dir.join("test.kubeconfig").to_str().unwrap(),
+        ])
+        .assert()
+        .success();
+
+    Command::cargo_bin("kbs")
+        .unwrap()
+        .env("KBS_BUNDLE_PASSPHRASE", "correct horse battery staple")
+        .args([
+            "-c",
+            source_dir.to_str().unwrap(),
+            "store",
+            "export",
+            "--encrypt",
+            "-o",
+            bundle.to_str().unwrap(),
+        ])
+        .assert()
+        .success()
+        .stderr(contains("plain text").not());
+
+    assert!(std::fs::read(&bundle)
+        .unwrap()
+        .starts_with(b"age-encryption.org/v1"));
+
+    Command::cargo_bin("kbs")
+        .unwrap()
+        .env("KBS_BUNDLE_PASSPHRASE", "wrong")
+        .args([
+            "-c",
+            target_dir.to_str().unwrap(),
+            "store",
+            "import",
+            bundle.to_str().unwrap(),
+        ])
+        .assert()
+        .failure()
+        .stderr(contains("is the passphrase correct?"));
+
+    Command::cargo_bin("kbs")
+        .unwrap()
+        .env("KBS_BUNDLE_PASSPHRASE", "correct horse battery staple")
+        .args([
+            "-c",
+            target_dir.to_str().unwrap(),
+            "store",
+            "import",
+            "--exec-credentials",
+            bundle.to_str().unwrap(),
+        ])
+        .assert()
+        .success();
+
+    assert!(target_dir
+        .join("credentials/kubernetes.embik.me.json")
+        .exists());
+
+    Command::cargo_bin("kbs")
+        .unwrap()
+        .args(["-c", target_dir.to_str().unwrap(), "list"])
+        .assert()
+        .success()
+        .stdout("kubernetes.embik.me\n");
+}
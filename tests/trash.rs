@@ -0,0 +1,213 @@
+use assert_cmd::Command;
+use predicates::prelude::*;
+use predicates::str::{contains, is_match};
+use tempfile::tempdir;
+
+#[test]
+fn test_kbs_trash_restore() {
+    let temp_dir = tempdir().unwrap();
+    let base_dir = std::path::PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/files");
+
+    for (name, file) in [
+        ("test", "test.kubeconfig"),
+        ("local", "localhost.kubeconfig"),
+    ] {
+        Command::cargo_bin("kbs")
+            .unwrap()
+            .args([
+                "-c",
+                temp_dir.path().to_str().unwrap(),
+                "import",
+                "--exec-credentials",
+                "--name",
+                name,
+                "--labels",
+                "env=prod",
+                base_dir.join(file).to_str().unwrap(),
+            ])
+            .assert()
+            .success();
+    }
+
+    Command::cargo_bin("kbs")
+        .unwrap()
+        .args(["-c", temp_dir.path().to_str().unwrap(), "remove", "test"])
+        .assert()
+        .success()
+        .stderr(contains("moved kubeconfig test to trash"));
+
+    Command::cargo_bin("kbs")
+        .unwrap()
+        .args([
+            "-c",
+            temp_dir.path().to_str().unwrap(),
+            "prune",
+            "--unreachable",
+            "--timeout",
+            "1s",
+            "--dry-run=false",
+            "--prune-on",
+            "other,network",
+        ])
+        .assert()
+        .success();
+
+    assert!(!temp_dir.path().join("test.kubeconfig").exists());
+    assert!(!temp_dir.path().join("credentials/test.json").exists());
+    assert!(!temp_dir.path().join("local.kubeconfig").exists());
+
+    Command::cargo_bin("kbs")
+        .unwrap()
+        .args(["-c", temp_dir.path().to_str().unwrap(), "trash", "list"])
+        .assert()
+        .success()
+        .stdout(is_match(r"^NAME\s+REMOVED\s+LABELS\s+REASON\n").unwrap())
+        .stdout(is_match(r"\nlocal\s+\S+\s+env=prod\s+pruned: \w+ failure").unwrap())
+        .stdout(is_match(r"\ntest\s+\S+\s+env=prod\s+removed\n$").unwrap());
+
+    Command::cargo_bin("kbs")
+        .unwrap()
+        .args(["-c", temp_dir.path().to_str().unwrap(), "restore", "test"])
+        .assert()
+        .success()
+        .stderr(contains("restored kubeconfig test"));
+
+    // labels and credentials are restored along with the kubeconfig.
+    Command::cargo_bin("kbs")
+        .unwrap()
+        .args([
+            "-c",
+            temp_dir.path().to_str().unwrap(),
+            "list",
+            "-o",
+            "table",
+        ])
+        .assert()
+        .success()
+        .stdout(is_match(r"\ntest\s+env=prod\n$").unwrap());
+
+    Command::cargo_bin("kbs")
+        .unwrap()
+        .args([
+            "-c",
+            temp_dir.path().to_str().unwrap(),
+            "credential",
+            "test",
+            "--user",
+            "kubernetes-admin",
+        ])
+        .assert()
+        .success()
+        .stdout(contains(r#""clientKeyData": "-----BEGIN"#));
+
+    Command::cargo_bin("kbs")
+        .unwrap()
+        .args(["-c", temp_dir.path().to_str().unwrap(), "restore", "test"])
+        .assert()
+        .failure()
+        .stderr(contains("no kubeconfig named test in trash"));
+
+    // restoring does not overwrite kubeconfigs in the data store.
+    Command::cargo_bin("kbs")
+        .unwrap()
+        .args([
+            "-c",
+            temp_dir.path().to_str().unwrap(),
+            "import",
+            "--name",
+            "local",
+            base_dir.join("localhost.kubeconfig").to_str().unwrap(),
+        ])
+        .assert()
+        .success();
+
+    Command::cargo_bin("kbs")
+        .unwrap()
+        .args(["-c", temp_dir.path().to_str().unwrap(), "restore", "local"])
+        .assert()
+        .failure()
+        .stderr(contains(
+            "kubeconfig local exists in the data store already",
+        ));
+}
+
+#[test]
+fn test_kbs_trash_empty() {
+    let temp_dir = tempdir().unwrap();
+    let base_dir = std::path::PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/files");
+
+    Command::cargo_bin("kbs")
+        .unwrap()
+        .args([
+            "-c",
+            temp_dir.path().to_str().unwrap(),
+            "import",
+            base_dir.join("test.kubeconfig").to_str().unwrap(),
+        ])
+        .assert()
+        .success();
+
+    Command::cargo_bin("kbs")
+        .unwrap()
+        .args([
+            "-c",
+            temp_dir.path().to_str().unwrap(),
+            "remove",
+            "kubernetes.embik.me",
+        ])
+        .assert()
+        .success();
+
+    Command::cargo_bin("kbs")
+        .unwrap()
+        .args([
+            "-c",
+            temp_dir.path().to_str().unwrap(),
+            "trash",
+            "empty",
+            "--older-than",
+            "30d",
+        ])
+        .assert()
+        .success()
+        .stderr(contains("deleted 0 kubeconfig(s) from trash"));
+
+    // deleting everything has to be requested explicitly.
+    Command::cargo_bin("kbs")
+        .unwrap()
+        .args(["-c", temp_dir.path().to_str().unwrap(), "trash", "empty"])
+        .assert()
+        .failure();
+
+    Command::cargo_bin("kbs")
+        .unwrap()
+        .args([
+            "-c",
+            temp_dir.path().to_str().unwrap(),
+            "trash",
+            "empty",
+            "--all",
+        ])
+        .assert()
+        .success()
+        .stderr(contains("deleted 1 kubeconfig(s) from trash"));
+
+    Command::cargo_bin("kbs")
+        .unwrap()
+        .args(["-c", temp_dir.path().to_str().unwrap(), "trash", "list"])
+        .assert()
+        .success()
+        .stdout(predicate::str::is_empty())
+        .stderr(contains("trash is empty"));
+
+    Command::cargo_bin("kbs")
+        .unwrap()
+        .args([
+            "-c",
+            temp_dir.path().to_str().unwrap(),
+            "restore",
+            "kubernetes.embik.me",
+        ])
+        .assert()
+        .failure();
+}